@@ -0,0 +1,481 @@
+//! Export installed Flutter versions to portable archives and import them again
+//!
+//! Meant for provisioning air-gapped machines: a version is installed on a
+//! connected machine, exported together with its engine, and imported offline.
+//!
+//! Archive layout (tar):
+//! - `fvm-export.json` - manifest (version, engine hash, commit, channel)
+//! - `flutter/...` - the version's SDK files, without git metadata or the engine link
+//! - `engine/...` - the shared engine the version is linked to
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use tar::{Archive, Builder, Entry, Header};
+use tokio::task;
+use tracing::{debug, warn};
+
+use crate::{config_manager, sdk_manager, utils};
+
+const MANIFEST_NAME: &str = "fvm-export.json";
+const FLUTTER_PREFIX: &str = "flutter";
+const ENGINE_PREFIX: &str = "engine";
+
+/// Metadata stored alongside the exported files
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportManifest {
+    version: String,
+    engine_hash: String,
+    commit: Option<String>,
+    channel: Option<String>,
+}
+
+/// Result of exporting a version
+pub struct ExportSummary {
+    pub engine_hash: String,
+    pub archive_size: u64,
+}
+
+/// Result of importing a version
+pub struct ImportSummary {
+    pub version: String,
+    pub engine_hash: String,
+    /// The engine was already cached, so the archive's copy was not extracted
+    pub engine_reused: bool,
+}
+
+/// Bundle an installed version and its engine into a tar archive
+pub async fn export_version(version: &str, destination: &Path) -> Result<ExportSummary> {
+    let flutter_dir = utils::flutter_version_dir(version)?;
+    if !flutter_dir.exists() {
+        anyhow::bail!("Flutter version {} is not installed", version);
+    }
+
+    let engine_hash = sdk_manager::get_engine_hash_for_version(version)
+        .await?
+        .with_context(|| format!("Flutter {} has no linked engine to export", version))?;
+    let engine_dir = utils::shared_engine_hash_dir(&engine_hash)?;
+    if !engine_dir.exists() {
+        anyhow::bail!(
+            "Engine {} for Flutter {} is missing from the cache",
+            engine_hash,
+            version
+        );
+    }
+
+    let manifest = ExportManifest {
+        version: version.to_string(),
+        engine_hash: engine_hash.clone(),
+        commit: sdk_manager::installed_commit(version)?,
+        channel: git2::Repository::open(&flutter_dir)
+            .ok()
+            .and_then(|repo| repo.head().ok()?.shorthand().map(str::to_string)),
+    };
+
+    let destination = destination.to_path_buf();
+    let archive_size = task::spawn_blocking(move || -> Result<u64> {
+        let file = File::create(&destination)
+            .with_context(|| format!("Failed to create {}", destination.display()))?;
+        let mut builder = Builder::new(BufWriter::new(file));
+        builder.follow_symlinks(false);
+
+        // The manifest goes first so import can validate it before extracting anything
+        let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+        let mut header = Header::new_gnu();
+        header.set_size(manifest_json.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(chrono::Utc::now().timestamp().max(0) as u64);
+        builder.append_data(&mut header, MANIFEST_NAME, manifest_json.as_slice())?;
+
+        // The worktree's .git file points into this machine's shared repository,
+        // and the dart-sdk link is recreated on import
+        let skip_flutter = |relative: &Path| {
+            relative == Path::new(".git") || relative == Path::new("bin/cache/dart-sdk")
+        };
+        add_directory(
+            &mut builder,
+            &flutter_dir,
+            Path::new(""),
+            FLUTTER_PREFIX,
+            &skip_flutter,
+        )?;
+        add_directory(
+            &mut builder,
+            &engine_dir,
+            Path::new(""),
+            ENGINE_PREFIX,
+            &|_| false,
+        )?;
+
+        builder.into_inner()?.flush()?;
+        Ok(std::fs::metadata(&destination)?.len())
+    })
+    .await??;
+
+    Ok(ExportSummary {
+        engine_hash,
+        archive_size,
+    })
+}
+
+/// Recursively add a directory's files to the archive under `prefix`
+///
+/// Symlinks are stored as links, not followed.
+fn add_directory(
+    builder: &mut Builder<BufWriter<File>>,
+    root: &Path,
+    relative: &Path,
+    prefix: &str,
+    skip: &dyn Fn(&Path) -> bool,
+) -> Result<()> {
+    for entry in std::fs::read_dir(root.join(relative))? {
+        let entry = entry?;
+        let relative_path = relative.join(entry.file_name());
+        if skip(&relative_path) {
+            debug!("Skipping {}", relative_path.display());
+            continue;
+        }
+
+        let name = Path::new(prefix).join(&relative_path);
+        builder
+            .append_path_with_name(entry.path(), &name)
+            .with_context(|| format!("Failed to add {} to the archive", entry.path().display()))?;
+
+        if entry.file_type()?.is_dir() {
+            add_directory(builder, root, &relative_path, prefix, skip)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Unpack an exported archive into the cache and link its engine
+///
+/// The imported SDK gets a fresh single-commit git repository (tagged with the
+/// version) so Flutter can still report its version without the upstream history.
+pub async fn import_version(archive_path: &Path) -> Result<ImportSummary> {
+    let path = archive_path.to_path_buf();
+    let manifest = task::spawn_blocking(move || read_manifest(&path)).await??;
+
+    sdk_manager::ensure_cache_writable()?;
+    let flutter_dir = utils::flutter_version_dir(&manifest.version)?;
+    let engine_dir = utils::shared_engine_hash_dir(&manifest.engine_hash)?;
+
+    // The same locks install takes, so a concurrent install of this version
+    // or engine waits for the import instead of racing with the extraction
+    let options = sdk_manager::InstallOptions::default();
+    let _version_lock = sdk_manager::acquire_install_lock(&flutter_dir, &options).await?;
+    let _engine_lock = sdk_manager::acquire_install_lock(&engine_dir, &options).await?;
+
+    if flutter_dir.exists() {
+        anyhow::bail!(
+            "Flutter {} is already installed. Remove it first: fvm-rs remove {}",
+            manifest.version,
+            manifest.version
+        );
+    }
+    let engine_reused = engine_dir.exists();
+
+    let extracted = {
+        let archive_path = archive_path.to_path_buf();
+        let flutter_dir = flutter_dir.clone();
+        let engine_dir = engine_dir.clone();
+        task::spawn_blocking(move || {
+            extract_archive(&archive_path, &flutter_dir, &engine_dir, engine_reused)
+        })
+        .await
+        .map_err(anyhow::Error::from)
+        .and_then(|result| result)
+    };
+
+    let summary = ImportSummary {
+        version: manifest.version.clone(),
+        engine_hash: manifest.engine_hash.clone(),
+        engine_reused,
+    };
+
+    let finished = match extracted {
+        Ok(()) => finish_import(&engine_dir, &flutter_dir, manifest).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = finished {
+        remove_partial_import(&flutter_dir, (!engine_reused).then_some(&engine_dir));
+        return Err(e);
+    }
+
+    Ok(summary)
+}
+
+/// Read and validate the manifest without extracting anything
+fn read_manifest(archive_path: &Path) -> Result<ExportManifest> {
+    let file = File::open(archive_path)
+        .with_context(|| format!("Failed to open {}", archive_path.display()))?;
+    let mut archive = Archive::new(file);
+
+    let mut manifest = None;
+    let mut engine_version = None;
+    let engine_version_path = Path::new(FLUTTER_PREFIX).join("bin/internal/engine.version");
+
+    for entry in archive
+        .entries_with_seek()
+        .context("Not a valid fvm-rs export archive")?
+    {
+        let mut entry = entry.context("Not a valid fvm-rs export archive")?;
+        let path = entry_path(&entry)?;
+
+        if path == Path::new(MANIFEST_NAME) {
+            let parsed: ExportManifest =
+                serde_json::from_reader(&mut entry).context("Invalid fvm-export.json manifest")?;
+            manifest = Some(parsed);
+        } else if path == engine_version_path {
+            let mut contents = String::new();
+            entry.read_to_string(&mut contents)?;
+            engine_version = Some(contents.trim().to_string());
+        }
+    }
+
+    let manifest = manifest.context("Archive has no fvm-export.json manifest")?;
+    validate_manifest(&manifest, engine_version.as_deref())?;
+    Ok(manifest)
+}
+
+/// Link the engine and create the git repository for freshly extracted files
+async fn finish_import(engine_dir: &Path, flutter_dir: &Path, manifest: ExportManifest) -> Result<()> {
+    sdk_manager::link_engine_to_flutter(engine_dir, flutter_dir).await?;
+
+    let flutter_dir = flutter_dir.to_path_buf();
+    task::spawn_blocking(move || init_imported_repo(&flutter_dir, &manifest)).await?
+}
+
+/// Write the archive's SDK and engine files into the cache
+///
+/// Symlinks are created only after every regular file has been written, so an
+/// entry can never be written through a link that came from the archive.
+fn extract_archive(
+    archive_path: &Path,
+    flutter_dir: &Path,
+    engine_dir: &Path,
+    engine_reused: bool,
+) -> Result<()> {
+    let file = File::open(archive_path)
+        .with_context(|| format!("Failed to open {}", archive_path.display()))?;
+    let mut archive = Archive::new(file);
+    let mut symlinks = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry_path(&entry)?;
+
+        let target = if let Ok(relative) = path.strip_prefix(FLUTTER_PREFIX) {
+            flutter_dir.join(relative)
+        } else if let Ok(relative) = path.strip_prefix(ENGINE_PREFIX) {
+            if engine_reused {
+                continue;
+            }
+            engine_dir.join(relative)
+        } else {
+            continue;
+        };
+
+        let kind = entry.header().entry_type();
+        if kind.is_symlink() {
+            let link_target = entry
+                .link_name()?
+                .with_context(|| format!("Archive symlink has no target: {}", path.display()))?
+                .to_string_lossy()
+                .into_owned();
+            validate_symlink_target(&path, &link_target)?;
+            symlinks.push((target, link_target));
+            continue;
+        }
+        // Hard links and devices are never exported, and could reach outside the cache
+        if !kind.is_file() && !kind.is_dir() {
+            anyhow::bail!("Archive contains an unsupported entry: {}", path.display());
+        }
+
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        entry
+            .unpack(&target)
+            .with_context(|| format!("Failed to extract {}", path.display()))?;
+    }
+
+    for (target, link_target) in symlinks {
+        create_symlink(&link_target, &target)?;
+    }
+
+    Ok(())
+}
+
+/// Get an entry's path, rejecting absolute paths and `..`
+fn entry_path(entry: &Entry<'_, File>) -> Result<PathBuf> {
+    let path = entry.path().context("Archive contains an unreadable path")?;
+    if path.as_os_str().is_empty()
+        || path
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        anyhow::bail!("Archive contains an unsafe path: {}", path.display());
+    }
+    Ok(path.into_owned())
+}
+
+/// Reject symlinks that could point outside the directory they are extracted into
+fn validate_symlink_target(name: &Path, link_target: &str) -> Result<()> {
+    let path = Path::new(link_target);
+    if link_target.is_empty()
+        || path.is_absolute()
+        || path
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        anyhow::bail!(
+            "Archive contains an unsafe symlink: {} -> {}",
+            name.display(),
+            link_target
+        );
+    }
+    Ok(())
+}
+
+/// Best-effort removal of files left behind by a failed import
+fn remove_partial_import(flutter_dir: &Path, engine_dir: Option<&PathBuf>) {
+    for dir in std::iter::once(flutter_dir).chain(engine_dir.map(PathBuf::as_path)) {
+        if dir.exists() {
+            debug!("Removing partially imported files: {}", dir.display());
+            if let Err(e) = std::fs::remove_dir_all(dir) {
+                warn!("Failed to remove {}: {}", dir.display(), e);
+            }
+        }
+    }
+}
+
+/// Reject manifests that would write outside the cache or don't match the archive
+fn validate_manifest(manifest: &ExportManifest, engine_version: Option<&str>) -> Result<()> {
+    let version_path = Path::new(&manifest.version);
+    if manifest.version.is_empty()
+        || version_path.is_absolute()
+        || version_path
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_)))
+    {
+        anyhow::bail!("Invalid version in manifest: {}", manifest.version);
+    }
+
+    if manifest.engine_hash.len() != 40
+        || !manifest.engine_hash.chars().all(|c| c.is_ascii_hexdigit())
+    {
+        anyhow::bail!("Invalid engine hash in manifest: {}", manifest.engine_hash);
+    }
+
+    // The SDK pins its engine; it must be the one shipped in the archive
+    if let Some(expected) = engine_version
+        && expected != manifest.engine_hash
+    {
+        anyhow::bail!(
+            "Archive engine {} does not match the engine Flutter {} expects ({})",
+            manifest.engine_hash,
+            manifest.version,
+            expected
+        );
+    }
+
+    Ok(())
+}
+
+fn create_symlink(link_target: &str, target: &Path) -> Result<()> {
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(link_target, target)?;
+
+    #[cfg(windows)]
+    {
+        // Archive links use '/', which Windows symlinks don't resolve
+        let link_target: PathBuf = link_target.split('/').collect();
+        let resolved = target.parent().unwrap_or(Path::new("")).join(&link_target);
+        let linked = if resolved.is_dir() {
+            std::os::windows::fs::symlink_dir(&link_target, target)
+        } else {
+            std::os::windows::fs::symlink_file(&link_target, target)
+        };
+
+        // Symlinks need Developer Mode or admin rights; fall back to a copy
+        if let Err(e) = linked {
+            debug!(
+                "Failed to link {} ({}), copying {} instead",
+                target.display(),
+                e,
+                resolved.display()
+            );
+            copy_path(&resolved, target)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(windows)]
+fn copy_path(source: &Path, destination: &Path) -> Result<()> {
+    if source.is_dir() {
+        std::fs::create_dir_all(destination)?;
+        for entry in std::fs::read_dir(source)? {
+            let entry = entry?;
+            copy_path(&entry.path(), &destination.join(entry.file_name()))?;
+        }
+    } else {
+        std::fs::copy(source, destination)
+            .with_context(|| format!("Failed to copy {}", source.display()))?;
+    }
+    Ok(())
+}
+
+/// Create a single-commit repository for an imported SDK
+///
+/// Flutter derives its version from git (`git describe --tags`), so the commit
+/// is tagged with the release name and placed on the original channel branch.
+fn init_imported_repo(flutter_dir: &Path, manifest: &ExportManifest) -> Result<()> {
+    let repo =
+        git2::Repository::init(flutter_dir).context("Failed to initialize git repository")?;
+
+    let mut index = repo.index()?;
+    index.add_all(["*"], git2::IndexAddOption::DEFAULT, None)?;
+    index.write()?;
+    let tree = repo.find_tree(index.write_tree()?)?;
+
+    let channel = manifest.channel.as_deref().unwrap_or("stable");
+    let branch_ref = format!("refs/heads/{}", channel);
+    let signature = git2::Signature::now("fvm-rs", "fvm-rs@localhost")?;
+    let message = format!(
+        "Flutter {} imported by fvm-rs (upstream commit {})",
+        manifest.version,
+        manifest.commit.as_deref().unwrap_or("unknown")
+    );
+    let commit_id = repo.commit(
+        Some(&branch_ref),
+        &signature,
+        &signature,
+        &message,
+        &tree,
+        &[],
+    )?;
+    repo.set_head(&branch_ref)?;
+
+    // Channels and commit-pinned versions have no release tag to recreate
+    let release = sdk_manager::release_name(&manifest.version);
+    if !config_manager::is_channel(&release) && !release.contains('@') {
+        repo.tag_lightweight(&release, &repo.find_object(commit_id, None)?, false)?;
+    }
+
+    debug!(
+        "Initialized imported repository at {}",
+        flutter_dir.display()
+    );
+    Ok(())
+}
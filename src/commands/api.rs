@@ -4,7 +4,8 @@ use serde::Serialize;
 use serde_json::json;
 use std::env;
 use std::path::PathBuf;
-use tracing::info;
+use tokio::task;
+use tracing::{debug, info};
 
 use crate::{config_manager, sdk_manager, utils};
 
@@ -76,6 +77,9 @@ struct VersionInfo {
     name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     size: Option<String>,
+    /// Channel, install date, engine hash and repository URL, where known
+    #[serde(flatten)]
+    metadata: sdk_manager::InstallMetadata,
 }
 
 async fn api_list(skip_size: bool) -> Result<serde_json::Value> {
@@ -88,11 +92,20 @@ async fn api_list(skip_size: bool) -> Result<serde_json::Value> {
         let size = if skip_size {
             None
         } else {
-            // Calculate directory size (simplified - would need proper implementation)
-            None // TODO: Implement size calculation if needed
+            // Includes the linked engine, which may be shared with other versions
+            let path = utils::flutter_version_dir(&version)?;
+            let bytes = task::spawn_blocking(move || utils::dir_size_following_links(&path)).await?;
+            match bytes {
+                Ok(bytes) => Some(utils::format_size(bytes)),
+                Err(e) => {
+                    debug!("Failed to calculate size of {}: {}", version, e);
+                    None
+                }
+            }
         };
 
         version_infos.push(VersionInfo {
+            metadata: sdk_manager::install_metadata(&version).await?,
             name: version,
             size,
         });
@@ -109,34 +122,14 @@ async fn api_releases(limit: Option<usize>, filter_channel: Option<&str>) -> Res
 
     let releases = sdk_manager::list_available_versions().await?;
 
-    let mut filtered_releases: Vec<_> = releases.releases.iter().collect();
-
-    // Filter by channel if specified
-    if let Some(channel) = filter_channel {
-        filtered_releases.retain(|r| r.channel == channel);
-    }
-
-    // Apply limit if specified
-    if let Some(max) = limit {
-        filtered_releases.truncate(max);
-    }
-
-    Ok(json!({
-        "current": {
-            "stable": releases.current_releases.stable.version,
-            "beta": releases.current_releases.beta.version,
-            "dev": releases.current_releases.dev.version,
-        },
-        "releases": filtered_releases,
-        "total": filtered_releases.len(),
-    }))
+    Ok(releases.to_json(filter_channel, limit))
 }
 
 async fn api_context() -> Result<serde_json::Value> {
     info!("API: Fetching environment context");
 
     let config = config_manager::GlobalConfig::read().await?;
-    let fvm_dir = utils::get_fvm_dir()?;
+    let fvm_dir = utils::cache_root_dir()?;
     let global_version = config_manager::get_global_flutter_version().await?;
 
     // Check for project version
@@ -158,6 +151,14 @@ async fn api_context() -> Result<serde_json::Value> {
             "updateVscodeSettings": config.update_vscode_settings,
             "updateGitignore": config.update_gitignore,
             "forks": config.forks,
+            "proxy": config.proxy,
+            "enginePlatform": config.engine_platform,
+            "scopedPubCache": config.scoped_pub_cache,
+            "pubCachePath": config.pub_cache_path,
+            "httpRetries": config.http_retries,
+            "releasesCacheTtl": config.releases_cache_ttl,
+            "storageBaseUrl": config.storage_base_url,
+            "shallowClone": config.shallow_clone,
         },
     }))
 }
@@ -0,0 +1,159 @@
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use std::path::PathBuf;
+use tokio::task;
+use tracing::{debug, info};
+
+use crate::{sdk_manager, utils};
+
+#[derive(Args, Debug, Clone)]
+pub struct CacheArgs {
+    #[command(subcommand)]
+    command: Option<CacheCommands>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum CacheCommands {
+    /// Prune worktree entries whose version directory no longer exists
+    Clean,
+}
+
+pub async fn run(args: CacheArgs) -> Result<()> {
+    match args.command {
+        Some(CacheCommands::Clean) => clean().await,
+        None => report().await,
+    }
+}
+
+/// Print disk usage of the cache and any orphaned worktrees
+async fn report() -> Result<()> {
+    info!("Reporting cache usage");
+    let root = utils::cache_root_dir()?;
+
+    println!("FVM-RS Cache");
+    println!("Located at: {}\n", root.display());
+
+    if !root.exists() {
+        println!("The cache is empty.");
+        return Ok(());
+    }
+
+    // Version sizes exclude their linked engine, which is counted once below
+    let versions = sdk_manager::list_installed_versions().await?;
+    let mut version_sizes = Vec::new();
+    for version in &versions {
+        version_sizes.push((
+            version.clone(),
+            size_of(utils::flutter_version_dir(version)?).await,
+        ));
+    }
+    let versions_total: u64 = version_sizes.iter().map(|(_, size)| size).sum();
+
+    println!(
+        "Versions ({}): {}",
+        versions.len(),
+        utils::format_size(versions_total)
+    );
+    for (version, size) in &version_sizes {
+        println!("  {:<24} {}", version, utils::format_size(*size));
+    }
+
+    let engines = engine_dirs()?;
+    let mut engines_total = 0;
+    for engine in &engines {
+        engines_total += size_of(engine.clone()).await;
+    }
+    println!(
+        "\nShared engines ({}): {}",
+        engines.len(),
+        utils::format_size(engines_total)
+    );
+
+    let repos = sdk_manager::shared_repo_dirs()?;
+    let shared_root = utils::shared_dir()?;
+    let mut repo_sizes = Vec::new();
+    for repo in &repos {
+        let name = repo
+            .strip_prefix(&shared_root)
+            .unwrap_or(repo)
+            .display()
+            .to_string();
+        repo_sizes.push((name, size_of(repo.clone()).await));
+    }
+    let repos_total: u64 = repo_sizes.iter().map(|(_, size)| size).sum();
+    println!(
+        "\nShared repositories ({}): {}",
+        repos.len(),
+        utils::format_size(repos_total)
+    );
+    for (name, size) in &repo_sizes {
+        println!("  {:<24} {}", name, utils::format_size(*size));
+    }
+
+    let total = size_of(root).await;
+    println!("\nTotal: {}", utils::format_size(total));
+
+    let orphaned = sdk_manager::orphaned_worktrees(false).await?;
+    if orphaned.is_empty() {
+        println!("\n✓ No orphaned worktrees");
+    } else {
+        println!("\n⚠ {} orphaned worktree(s):", orphaned.len());
+        for worktree in &orphaned {
+            println!(
+                "  • {} -> {} (missing)",
+                worktree.name,
+                worktree.path.display()
+            );
+        }
+        println!("\nRun: fvm-rs cache clean");
+    }
+
+    Ok(())
+}
+
+/// Prune orphaned worktree entries across all shared repositories
+async fn clean() -> Result<()> {
+    info!("Pruning orphaned worktrees");
+    let pruned = sdk_manager::orphaned_worktrees(true).await?;
+
+    if pruned.is_empty() {
+        println!("✓ No orphaned worktrees to prune");
+        return Ok(());
+    }
+
+    for worktree in &pruned {
+        println!("  • Pruned {} ({})", worktree.name, worktree.repo.display());
+    }
+    println!("✓ Pruned {} orphaned worktree(s)", pruned.len());
+
+    Ok(())
+}
+
+/// Get the engine directories in the shared engine cache
+fn engine_dirs() -> Result<Vec<PathBuf>> {
+    let Ok(entries) = std::fs::read_dir(utils::shared_engine_dir()?) else {
+        return Ok(vec![]);
+    };
+
+    Ok(entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .collect())
+}
+
+/// Calculate the size of a directory, treating unreadable ones as empty
+async fn size_of(path: PathBuf) -> u64 {
+    let result = task::spawn_blocking(move || utils::dir_size(&path).map_err(|e| (path, e))).await;
+    match result {
+        Ok(Ok(size)) => size,
+        Ok(Err((path, e))) => {
+            debug!("Failed to calculate size of {}: {}", path.display(), e);
+            0
+        }
+        Err(e) => {
+            debug!("Size calculation failed: {}", e);
+            0
+        }
+    }
+}
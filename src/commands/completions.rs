@@ -0,0 +1,60 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use clap_complete::Shell;
+use clap_complete::env::Shells;
+use tracing::info;
+
+/// Environment variable that switches fvm-rs into answering completion requests
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
+#[derive(Debug, Clone, Args)]
+pub struct CompletionsArgs {
+    /// Shell to generate the completion script for
+    shell: Shell,
+
+    /// Ask fvm-rs for completions while typing, so installed versions and the
+    /// project's flavors are offered too (the script calls back into this binary)
+    #[arg(long)]
+    dynamic: bool,
+}
+
+/// Print a completion script for `shell` to stdout
+///
+/// Source the output from the shell's startup file, or write it to the
+/// shell's completion directory, e.g.:
+///   fvm-rs completions bash > ~/.local/share/bash-completion/completions/fvm-rs
+///   fvm-rs completions zsh > "${fpath[1]}/_fvm-rs"
+///   fvm-rs completions fish > ~/.config/fish/completions/fvm-rs.fish
+///   fvm-rs completions powershell >> $PROFILE
+pub fn run(args: CompletionsArgs, mut cmd: clap::Command) -> Result<()> {
+    let bin_name = cmd.get_name().to_string();
+    let mut stdout = std::io::stdout();
+
+    if !args.dynamic {
+        info!("Generating static {} completions", args.shell);
+        clap_complete::generate(args.shell, &mut cmd, bin_name, &mut stdout);
+        return Ok(());
+    }
+
+    info!("Generating dynamic {} completions", args.shell);
+    let shell_name = args.shell.to_string();
+    let shells = Shells::builtins();
+    let shell = shells
+        .completer(&shell_name)
+        .with_context(|| format!("Dynamic completions are not supported for {}", shell_name))?;
+
+    // The script calls this binary back by absolute path, so it keeps working
+    // when fvm-rs isn't on PATH under the same name
+    let completer = std::env::current_exe().context("Failed to locate the fvm-rs executable")?;
+    shell
+        .write_registration(
+            COMPLETE_VAR,
+            &bin_name,
+            &bin_name,
+            &completer.to_string_lossy(),
+            &mut stdout,
+        )
+        .context("Failed to write the completion script")?;
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
-use anyhow::Result;
-use clap::Args;
+use anyhow::{Context, Result};
+use clap::{Args, Subcommand};
+use dialoguer::{Confirm, theme::ColorfulTheme};
 use tracing::info;
 
 use crate::config_manager::GlobalConfig;
@@ -7,6 +8,9 @@ use crate::utils;
 
 #[derive(Args, Debug, Clone)]
 pub struct ConfigArgs {
+    #[command(subcommand)]
+    command: Option<ConfigCommands>,
+
     /// Set custom cache path for Flutter versions
     #[arg(long)]
     cache_path: Option<String>,
@@ -26,6 +30,52 @@ pub struct ConfigArgs {
     /// Enable or disable automatic update checking
     #[arg(long, value_name = "BOOL")]
     update_check: Option<bool>,
+
+    /// Set HTTP(S) proxy URL for downloads and git operations
+    #[arg(long, value_name = "URL")]
+    proxy: Option<String>,
+
+    /// Override the engine platform (e.g. "linux-arm64") instead of auto-detecting
+    #[arg(long, value_name = "PLATFORM")]
+    engine_platform: Option<String>,
+
+    /// Enable or disable a separate PUB_CACHE per Flutter version
+    #[arg(long, value_name = "BOOL")]
+    scoped_pub_cache: Option<bool>,
+
+    /// Set base directory for version-scoped pub caches
+    #[arg(long)]
+    pub_cache_path: Option<String>,
+
+    /// Set how many times HTTP requests are attempted on connection errors or 5xx responses
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    http_retries: Option<u32>,
+
+    /// Set how many minutes the cached releases feed is used before fetching it again
+    #[arg(long, value_name = "MINUTES")]
+    releases_cache_ttl: Option<u64>,
+
+    /// Set a Flutter storage mirror for releases and engine downloads (e.g. "https://storage.flutter-io.cn")
+    #[arg(long, value_name = "URL")]
+    storage_base_url: Option<String>,
+
+    /// Enable or disable fetching only the needed branch and tag without history
+    #[arg(long, value_name = "BOOL")]
+    shallow_clone: Option<bool>,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+enum ConfigCommands {
+    /// Clear all global settings, restoring the defaults
+    Reset {
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Keep configured fork aliases
+        #[arg(long)]
+        keep_forks: bool,
+    },
 }
 
 impl ConfigArgs {
@@ -36,10 +86,22 @@ impl ConfigArgs {
             || self.git_cache_path.is_some()
             || self.flutter_url.is_some()
             || self.update_check.is_some()
+            || self.proxy.is_some()
+            || self.engine_platform.is_some()
+            || self.scoped_pub_cache.is_some()
+            || self.pub_cache_path.is_some()
+            || self.http_retries.is_some()
+            || self.releases_cache_ttl.is_some()
+            || self.storage_base_url.is_some()
+            || self.shallow_clone.is_some()
     }
 }
 
 pub async fn run(args: ConfigArgs) -> Result<()> {
+    if let Some(ConfigCommands::Reset { yes, keep_forks }) = args.command {
+        return reset_config(yes, keep_forks).await;
+    }
+
     if args.has_any_set() {
         // Set mode: update configuration
         set_config(args).await
@@ -71,6 +133,20 @@ async fn display_config() -> Result<()> {
     println!("  gitCachePath: {}", config.get_git_cache_path()?.display());
     println!("  flutterUrl: {}", config.get_flutter_url());
     println!("  updateCheck: {}", config.get_update_check_enabled());
+    println!("  proxy: {}", config.get_proxy().unwrap_or_else(|| "(none)".to_string()));
+    println!(
+        "  enginePlatform: {}",
+        config.get_engine_platform().unwrap_or_else(|| "(auto-detect)".to_string())
+    );
+    println!("  scopedPubCache: {}", config.get_scoped_pub_cache());
+    println!("  pubCachePath: {}", config.get_pub_cache_path()?.display());
+    println!("  httpRetries: {}", config.get_http_retries());
+    println!("  releasesCacheTtl: {} min", config.get_releases_cache_ttl().as_secs() / 60);
+    println!(
+        "  storageBaseUrl: {}",
+        config.get_storage_base_url().unwrap_or_else(|| "(default hosts)".to_string())
+    );
+    println!("  shallowClone: {}", config.get_shallow_clone());
 
     if !config.is_empty() {
         println!("\nNote: Values shown include defaults for unset options.");
@@ -83,9 +159,23 @@ async fn display_config() -> Result<()> {
 async fn set_config(args: ConfigArgs) -> Result<()> {
     info!("Updating global configuration");
 
-    // Read existing config
-    let mut config = GlobalConfig::read().await?;
+    // Apply changes under the config lock so concurrent updates aren't lost
+    let changes = GlobalConfig::update(move |config| Ok(apply_settings(config, args))).await?;
+
+    println!("\n✓ Settings saved successfully!");
+
+    if !changes.is_empty() {
+        println!("\nUpdated:");
+        for change in changes {
+            println!("  • {}", change);
+        }
+    }
+
+    Ok(())
+}
 
+/// Set the explicitly passed fields and describe each change
+fn apply_settings(config: &mut GlobalConfig, args: ConfigArgs) -> Vec<String> {
     // Track what's being changed
     let mut changes = Vec::new();
 
@@ -120,16 +210,95 @@ async fn set_config(args: ConfigArgs) -> Result<()> {
         changes.push(format!("updateCheck: {}", enabled));
     }
 
-    // Save configuration
-    println!("\nSaving settings...");
-    config.save().await?;
+    if let Some(proxy) = args.proxy {
+        println!("Setting proxy to: {}", proxy);
+        config.proxy = Some(proxy.clone());
+        changes.push(format!("proxy: {}", proxy));
+    }
 
-    println!("✓ Settings saved successfully!");
+    if let Some(platform) = args.engine_platform {
+        println!("Setting engine-platform to: {}", platform);
+        config.engine_platform = Some(platform.clone());
+        changes.push(format!("enginePlatform: {}", platform));
+    }
 
-    if !changes.is_empty() {
-        println!("\nUpdated:");
-        for change in changes {
-            println!("  • {}", change);
+    if let Some(enabled) = args.scoped_pub_cache {
+        println!("Setting scoped-pub-cache to: {}", enabled);
+        config.scoped_pub_cache = Some(enabled);
+        changes.push(format!("scopedPubCache: {}", enabled));
+    }
+
+    if let Some(path) = args.pub_cache_path {
+        println!("Setting pub-cache-path to: {}", path);
+        config.pub_cache_path = Some(path.clone());
+        changes.push(format!("pubCachePath: {}", path));
+    }
+
+    if let Some(retries) = args.http_retries {
+        println!("Setting http-retries to: {}", retries);
+        config.http_retries = Some(retries);
+        changes.push(format!("httpRetries: {}", retries));
+    }
+
+    if let Some(minutes) = args.releases_cache_ttl {
+        println!("Setting releases-cache-ttl to: {} min", minutes);
+        config.releases_cache_ttl = Some(minutes);
+        changes.push(format!("releasesCacheTtl: {}", minutes));
+    }
+
+    if let Some(url) = args.storage_base_url {
+        println!("Setting storage-base-url to: {}", url);
+        config.storage_base_url = Some(url.clone());
+        changes.push(format!("storageBaseUrl: {}", url));
+    }
+
+    if let Some(enabled) = args.shallow_clone {
+        println!("Setting shallow-clone to: {}", enabled);
+        config.shallow_clone = Some(enabled);
+        changes.push(format!("shallowClone: {}", enabled));
+    }
+
+    changes
+}
+
+async fn reset_config(yes: bool, keep_forks: bool) -> Result<()> {
+    info!("Resetting global configuration");
+    let config_path = utils::get_global_config_path()?;
+
+    let proceed = yes
+        || Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(format!(
+                "Reset all settings in {}{}?",
+                config_path.display(),
+                if keep_forks { " (keeping forks)" } else { "" }
+            ))
+            .default(false)
+            .interact()
+            .context("Failed to get confirmation")?;
+
+    if !proceed {
+        println!("Cancelled.");
+        return Ok(());
+    }
+
+    let cleared = GlobalConfig::update(move |config| {
+        let forks = if keep_forks { config.forks.take() } else { None };
+        let cleared = config.configured_keys();
+        *config = GlobalConfig {
+            forks,
+            ..Default::default()
+        };
+        Ok(cleared)
+    })
+    .await?;
+
+    println!("✓ Global configuration reset");
+    if cleared.is_empty() {
+        println!("  Nothing was configured");
+    } else {
+        println!("\nCleared:");
+        for key in cleared {
+            println!("  • {}", key);
         }
     }
 
@@ -2,10 +2,14 @@ use anyhow::Result;
 use clap::Args;
 use tracing::{debug, info};
 
-use crate::{config_manager, utils};
+use crate::{config_manager, sdk_manager, utils};
 
 #[derive(Debug, Clone, Args)]
 pub struct DartArgs {
+    /// Fail instead of installing the resolved version when it is missing
+    #[arg(long)]
+    no_install: bool,
+
     /// Arguments to pass to dart command
     #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
     args: Vec<String>,
@@ -23,16 +27,11 @@ pub async fn run(args: DartArgs) -> Result<i32> {
         debug!("Using project version: {}", version);
         info!("Running Dart from project version: {}", version);
 
+        ensure_version(&version, args.no_install).await?;
+
         // Get the Flutter installation path
         let flutter_path = utils::flutter_version_dir(&version)?;
 
-        // Check if version is installed
-        if !flutter_path.exists() {
-            eprintln!("✗ Flutter version {} is not installed", version);
-            eprintln!("  Run: fvm-rs install {}", version);
-            anyhow::bail!("Flutter version {} not found", version);
-        }
-
         // Execute with modified PATH
         let exit_code = utils::execute_with_flutter_path("dart", &args.args, &flutter_path)?;
         Ok(exit_code)
@@ -40,16 +39,11 @@ pub async fn run(args: DartArgs) -> Result<i32> {
         debug!("Using global version: {}", version);
         info!("Running Dart from global version: {}", version);
 
+        ensure_version(&version, args.no_install).await?;
+
         // Get the Flutter installation path
         let flutter_path = utils::flutter_version_dir(&version)?;
 
-        // Check if version is installed
-        if !flutter_path.exists() {
-            eprintln!("✗ Flutter version {} is not installed", version);
-            eprintln!("  Run: fvm-rs install {}", version);
-            anyhow::bail!("Flutter version {} not found", version);
-        }
-
         // Execute with modified PATH
         let exit_code = utils::execute_with_flutter_path("dart", &args.args, &flutter_path)?;
         Ok(exit_code)
@@ -62,3 +56,19 @@ pub async fn run(args: DartArgs) -> Result<i32> {
         Ok(exit_code)
     }
 }
+
+/// Install the version if missing (like exec), or fail with --no-install
+async fn ensure_version(version: &str, no_install: bool) -> Result<()> {
+    if !no_install {
+        // Auto-install if configured but not cached
+        return sdk_manager::ensure_installed(version).await;
+    }
+
+    if !utils::flutter_version_dir(version)?.exists() {
+        eprintln!("✗ Flutter version {} is not installed", version);
+        eprintln!("  Run: fvm-rs install {}", version);
+        anyhow::bail!("Flutter version {} not found", version);
+    }
+
+    Ok(())
+}
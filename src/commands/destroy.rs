@@ -13,7 +13,7 @@ pub struct DestroyArgs {
 }
 
 pub async fn run(args: DestroyArgs) -> Result<()> {
-    let fvm_dir = utils::fvm_rs_root_dir()?;
+    let fvm_dir = utils::cache_root_dir()?;
 
     info!("Destroy command invoked");
     debug!("FVM directory: {}", fvm_dir.display());
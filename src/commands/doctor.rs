@@ -1,193 +1,1346 @@
 use anyhow::{Context, Result};
+use chrono::NaiveDate;
 use clap::Args;
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::env;
+use std::path::{Path, PathBuf};
 use tracing::info;
 
-use crate::{config_manager, utils};
+use crate::{config_manager, gitignore_manager, ide_manager, sdk_manager, utils};
+
+/// Size above which .fvm likely contains SDK files rather than config and a symlink
+const FVM_DIR_SIZE_WARNING: u64 = 1024 * 1024;
+
+/// A project platform directory and the first stable Flutter release supporting it
+struct PlatformSupport {
+    dir: &'static str,
+    name: &'static str,
+    stable_since: (u32, u32),
+    released: (i32, u32, u32),
+}
+
+const PLATFORM_SUPPORT: &[PlatformSupport] = &[
+    PlatformSupport { dir: "web", name: "Web", stable_since: (2, 0), released: (2021, 3, 3) },
+    PlatformSupport { dir: "windows", name: "Windows desktop", stable_since: (2, 10), released: (2022, 2, 3) },
+    PlatformSupport { dir: "macos", name: "macOS desktop", stable_since: (3, 0), released: (2022, 5, 11) },
+    PlatformSupport { dir: "linux", name: "Linux desktop", stable_since: (3, 0), released: (2022, 5, 11) },
+];
 
 #[derive(Debug, Clone, Args)]
-pub struct DoctorArgs {}
+pub struct DoctorArgs {
+    /// Regenerate missing project files (.fvm/.gitignore, IDE settings, SDK link),
+    /// relink broken engine links, remove a dangling or orphaned global link and
+    /// restore execute permissions on installed flutter/dart binaries
+    #[arg(long)]
+    fix: bool,
+
+    /// Install the project's configured version first if it is missing
+    #[arg(long)]
+    install: bool,
+
+    /// Print the project, IDE and environment diagnostics as JSON, with counts of
+    /// failed checks and warnings (exits 1 when any check failed)
+    #[arg(long, conflicts_with_all = ["fix", "install"])]
+    json: bool,
+}
+
+/// Returns the process exit code: 1 when any check failed (✗), 0 otherwise
+pub async fn run(args: DoctorArgs) -> Result<i32> {
+    // Installing first lets --fix link the project to the new version
+    if args.install && install_project_version().await? {
+        println!();
+    }
+
+    if args.fix {
+        fix_project().await?;
+        fix_engine_links().await?;
+        fix_global_link().await;
+        fix_permissions().await?;
+        println!();
+    }
 
-pub async fn run(_args: DoctorArgs) -> Result<()> {
     info!("Running FVM doctor diagnostics");
+    let report = DoctorReport::gather().await?;
+
+    // With --json the sections still run, silently, to count failed checks
+    let mut summary = Summary {
+        quiet: args.json,
+        ..Default::default()
+    };
 
-    println!("FVM Doctor");
-    println!("══════════════════════════════════════════════════");
-    println!();
+    summary.line("FVM Doctor");
+    summary.line("══════════════════════════════════════════════════");
+    summary.line("");
 
     // Project Info Section
-    print_project_info().await?;
-    println!();
+    print_project_info(&report.project, &mut summary);
+    summary.line("");
 
     // IDE Integration Section
-    print_ide_integration().await?;
-    println!();
+    print_ide_integration(&report.ide, &mut summary);
+    summary.line("");
 
     // Environment Section
-    print_environment_info().await?;
-    println!();
+    print_environment_info(&report.environment, &mut summary);
+    summary.line("");
 
-    println!("══════════════════════════════════════════════════");
+    if args.json {
+        let json = JsonReport {
+            report: &report,
+            errors: summary.errors,
+            warnings: summary.warnings,
+        };
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    } else {
+        println!("══════════════════════════════════════════════════");
+        summary.print();
+    }
     info!("Doctor diagnostics completed");
 
+    Ok(if summary.errors > 0 { 1 } else { 0 })
+}
+
+/// Tally of the ✓/⚠/✗ checks printed by the sections, for the final verdict
+#[derive(Default)]
+struct Summary {
+    /// Count checks without printing them (for `--json`)
+    quiet: bool,
+    ok: usize,
+    warnings: usize,
+    errors: usize,
+    /// Whether the last reported check was a ✗, to rank suggested actions
+    last_was_error: bool,
+    error_action: Option<String>,
+    warning_action: Option<String>,
+}
+
+impl Summary {
+    /// Print a line of a section that isn't a check of its own
+    fn line(&self, line: impl AsRef<str>) {
+        if !self.quiet {
+            println!("{}", line.as_ref());
+        }
+    }
+
+    fn ok(&mut self, line: impl AsRef<str>) {
+        self.line(line);
+        self.ok += 1;
+        self.last_was_error = false;
+    }
+
+    fn warning(&mut self, line: impl AsRef<str>) {
+        self.line(line);
+        self.warnings += 1;
+        self.last_was_error = false;
+    }
+
+    fn error(&mut self, line: impl AsRef<str>) {
+        self.line(line);
+        self.errors += 1;
+        self.last_was_error = true;
+    }
+
+    /// Record the command fixing the last reported problem; the first ✗ action wins
+    fn suggest(&mut self, action: impl Into<String>) {
+        let slot = if self.last_was_error {
+            &mut self.error_action
+        } else {
+            &mut self.warning_action
+        };
+        slot.get_or_insert_with(|| action.into());
+    }
+
+    fn print(&self) {
+        let plural = |count: usize, word: &str| {
+            format!("{} {}{}", count, word, if count == 1 { "" } else { "s" })
+        };
+
+        if self.errors == 0 && self.warnings == 0 {
+            println!("✓ No issues found ({} checks passed)", self.ok);
+        } else if self.errors == 0 {
+            println!("⚠ {} ({} checks passed)", plural(self.warnings, "warning"), self.ok);
+        } else {
+            println!(
+                "✗ {} found, {} ({} checks passed)",
+                plural(self.errors, "issue"),
+                plural(self.warnings, "warning"),
+                self.ok
+            );
+        }
+
+        if let Some(action) = self.error_action.as_ref().or(self.warning_action.as_ref()) {
+            println!("  Run: {}", action);
+        }
+    }
+}
+
+/// Install the project's configured version if it is missing
+///
+/// Returns whether anything was printed, which is nothing when the version is installed.
+async fn install_project_version() -> Result<bool> {
+    let current_dir = env::current_dir().context("Failed to get current directory")?;
+    let Some(config) = config_manager::read_project_config(&current_dir).await? else {
+        println!("✗ Nothing to install: this project is not configured");
+        println!("  Run 'fvm-rs use <version>' to configure it");
+        return Ok(true);
+    };
+
+    if utils::flutter_version_dir(&config.flutter)?.exists() {
+        info!("Flutter SDK {} is already installed", config.flutter);
+        return Ok(false);
+    }
+
+    println!("Installing Flutter SDK {}...", config.flutter);
+    match sdk_manager::ensure_installed(&config.flutter).await {
+        Ok(()) => println!("✓ Installed Flutter SDK {}", config.flutter),
+        Err(e) => eprintln!("✗ Failed to install Flutter SDK {}: {:#}", config.flutter, e),
+    }
+
+    Ok(true)
+}
+
+/// Re-apply the project side effects of `use` without changing the pinned version
+async fn fix_project() -> Result<()> {
+    info!("Repairing project FVM setup");
+
+    let current_dir = env::current_dir().context("Failed to get current directory")?;
+    let Some(config) = config_manager::read_project_config(&current_dir).await? else {
+        println!("✗ Nothing to fix: this project is not configured");
+        println!("  Run 'fvm-rs use <version>' to configure it");
+        return Ok(());
+    };
+
+    println!("Repairing project setup for Flutter SDK {}...", config.flutter);
+
+    report_fix(
+        "config files (.fvmrc, .fvm/fvm_config.json)",
+        config_manager::sync_project_config_files(&current_dir).await,
+    );
+    report_fix(
+        ".fvm/.gitignore",
+        gitignore_manager::update_fvm_gitignore(&current_dir).await,
+    );
+    report_fix(
+        "VS Code settings",
+        ide_manager::update_vscode_settings(&current_dir).await,
+    );
+    report_fix(
+        "VS Code workspace files",
+        ide_manager::update_vscode_workspace(&current_dir).await,
+    );
+    report_fix(
+        "IntelliJ/Android Studio settings",
+        ide_manager::update_intellij_settings(&current_dir).await,
+    );
+    report_fix(
+        ".fvm/flutter_sdk symlink",
+        sdk_manager::link_project_sdk(&current_dir, &config.flutter).await.map(|_| ()),
+    );
+
+    Ok(())
+}
+
+/// Relink the engine of every installed version whose dart-sdk link is broken
+async fn fix_engine_links() -> Result<()> {
+    for version in sdk_manager::list_installed_versions().await? {
+        match sdk_manager::repair_engine_link(&version).await {
+            Ok(Some(hash)) => println!("✓ Repaired engine link of Flutter {} (engine {})", version, hash),
+            Ok(None) => {}
+            Err(e) => eprintln!("✗ Failed to repair engine link of Flutter {}: {:#}", version, e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove the global version link if its version no longer exists, and the
+/// one left in ~/.fvm-rs when the cache path was changed
+async fn fix_global_link() {
+    match sdk_manager::remove_dangling_global_link().await {
+        Ok(Some(target)) => {
+            println!("✓ Removed dangling global link to {}", target.display());
+            println!("  Set a new global version with 'fvm-rs global <version>'");
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("✗ Failed to remove dangling global link: {:#}", e),
+    }
+
+    match sdk_manager::remove_orphaned_global_link().await {
+        Ok(Some(old_link)) => {
+            println!("✓ Removed old global link {}", old_link.display());
+            if let Ok(global_link) = utils::get_global_link_path() {
+                println!("  Put {} on your PATH instead", global_link.join("bin").display());
+            }
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("✗ Failed to remove old global link: {:#}", e),
+    }
+}
+
+/// Restore the executable bit on flutter/dart binaries of every installed version
+async fn fix_permissions() -> Result<()> {
+    for version in sdk_manager::list_installed_versions().await? {
+        let binaries = match sdk_manager::non_executable_binaries(&version) {
+            Ok(binaries) => binaries,
+            Err(e) => {
+                eprintln!("✗ Failed to check permissions of Flutter {}: {}", version, e);
+                continue;
+            }
+        };
+
+        for binary in binaries {
+            report_fix(
+                &format!("execute permission on {}", binary.display()),
+                sdk_manager::make_executable(&binary),
+            );
+        }
+    }
+
     Ok(())
 }
 
-async fn print_project_info() -> Result<()> {
-    println!("📋 Project Information");
-    println!("──────────────────────────────────────────────────");
+fn report_fix(item: &str, result: Result<()>) {
+    match result {
+        Ok(()) => println!("✓ Repaired {}", item),
+        Err(e) => eprintln!("✗ Failed to repair {}: {}", item, e),
+    }
+}
+
+/// Everything the doctor sections check, shared by the printed report and `--json`
+#[derive(Serialize)]
+struct DoctorReport {
+    project: ProjectInfo,
+    ide: IdeInfo,
+    environment: EnvironmentInfo,
+}
+
+/// `doctor --json` output: the report plus the tally of its checks
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    #[serde(flatten)]
+    report: &'a DoctorReport,
+    /// Number of failed (✗) checks; the exit code is 1 when this is non-zero
+    errors: usize,
+    warnings: usize,
+}
+
+impl DoctorReport {
+    async fn gather() -> Result<Self> {
+        let current_dir = env::current_dir().context("Failed to get current directory")?;
+
+        Ok(Self {
+            project: ProjectInfo::gather(&current_dir).await?,
+            ide: IdeInfo::gather(&current_dir).await?,
+            environment: EnvironmentInfo::gather().await,
+        })
+    }
+}
+
+/// The outcome of a check that can fail to run, serialized as the value or `{"error": ...}`
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Checked<T> {
+    Ok(T),
+    Failed { error: String },
+}
+
+impl<T> From<Result<T>> for Checked<T> {
+    fn from(result: Result<T>) -> Self {
+        match result {
+            Ok(value) => Checked::Ok(value),
+            Err(e) => Checked::Failed {
+                error: format!("{:#}", e),
+            },
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectInfo {
+    directory: PathBuf,
+    fvm_configured: bool,
+    #[serde(flatten)]
+    config: Option<ConfiguredProject>,
+    /// Whether the directory has a pubspec.yaml
+    flutter_project: bool,
+}
+
+/// Project details only available when it has an FVM config
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConfiguredProject {
+    flutter_version: String,
+    /// Flavor name to Flutter version
+    flavors: BTreeMap<String, String>,
+    config_file: &'static str,
+    /// fvm-rs version that wrote .fvm/fvm_config.json, None if unknown
+    written_by: Checked<Option<String>>,
+    config_mismatches: Vec<config_manager::ConfigMismatch>,
+    version_installed: bool,
+    version_dir: PathBuf,
+    install_metadata: Option<Checked<sdk_manager::InstallMetadata>>,
+    platform_support: Vec<PlatformStatus>,
+}
+
+/// A platform directory of the project and whether the pinned version predates it
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PlatformStatus {
+    dir: &'static str,
+    name: &'static str,
+    stable_since: String,
+    predates: bool,
+}
+
+impl ProjectInfo {
+    async fn gather(current_dir: &Path) -> Result<Self> {
+        let config = match config_manager::read_project_config(current_dir).await? {
+            Some(cfg) => Some(ConfiguredProject::gather(current_dir, cfg).await?),
+            None => None,
+        };
+
+        Ok(Self {
+            directory: current_dir.to_path_buf(),
+            fvm_configured: config.is_some(),
+            config,
+            flutter_project: current_dir.join("pubspec.yaml").exists(),
+        })
+    }
+}
+
+impl ConfiguredProject {
+    async fn gather(current_dir: &Path, cfg: config_manager::ProjectConfig) -> Result<Self> {
+        let flavors = cfg
+            .flavors
+            .iter()
+            .flatten()
+            .map(|(name, flavor)| (name.clone(), flavor.version().to_string()))
+            .collect();
+
+        let config_file = if current_dir.join(".fvmrc").exists() {
+            ".fvmrc"
+        } else {
+            ".fvm/fvm_config.json"
+        };
+
+        let version_dir = utils::flutter_version_dir(&cfg.flutter)?;
+        let version_installed = version_dir.exists();
+        let install_metadata = if version_installed {
+            Some(sdk_manager::install_metadata(&cfg.flutter).await.into())
+        } else {
+            None
+        };
+
+        Ok(Self {
+            flavors,
+            config_file,
+            written_by: config_manager::config_written_by(current_dir).await.into(),
+            config_mismatches: config_manager::find_config_mismatches(current_dir).await?,
+            version_installed,
+            version_dir,
+            install_metadata,
+            platform_support: platform_support(current_dir, &cfg.flutter).await,
+            flutter_version: cfg.flutter,
+        })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct IdeInfo {
+    vscode_settings_found: bool,
+    /// `dart.flutterSdkPath` from .vscode/settings.json
+    vscode_sdk_path: Checked<Option<SdkPathStatus>>,
+    idea_found: bool,
+    /// Flutter SDK root from .idea/libraries/Dart_SDK.xml
+    dart_sdk_xml: Checked<Option<SdkPathStatus>>,
+    /// SDK paths from every IDE file present
+    sdk_paths: Checked<Vec<SdkPathStatus>>,
+    gitignore_present: bool,
+    flutter_sdk_link: FlutterSdkLink,
+    /// Where .fvm/flutter_sdk/bin/cache/dart-sdk resolves, if the link exists
+    dart_sdk: Option<Checked<ide_manager::DartSdkResolution>>,
+    /// Size of .fvm and committed SDK files, if the directory exists
+    fvm_dir: Option<FvmDirUsage>,
+}
+
+/// An SDK path from an IDE file and whether it points at the project's SDK
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SdkPathStatus {
+    source: String,
+    raw: String,
+    resolved: PathBuf,
+    points_at_sdk: bool,
+}
+
+#[derive(Serialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+enum FlutterSdkLink {
+    Symlink { target: PathBuf },
+    NotSymlink,
+    Missing,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FvmDirUsage {
+    size: u64,
+    /// Files under .fvm/flutter_sdk committed to git
+    tracked_sdk_files: usize,
+}
+
+impl IdeInfo {
+    async fn gather(current_dir: &Path) -> Result<Self> {
+        // IDE paths may also point straight at the configured version's directory
+        let version_dir = match config_manager::read_project_config(current_dir).await {
+            Ok(Some(cfg)) => utils::flutter_version_dir(&cfg.flutter).ok(),
+            _ => None,
+        };
+        let status = |sdk_path: ide_manager::IdeSdkPath| SdkPathStatus {
+            points_at_sdk: sdk_path.points_at_sdk(current_dir, version_dir.as_deref()),
+            source: sdk_path.source,
+            raw: sdk_path.raw,
+            resolved: sdk_path.resolved,
+        };
+
+        let vscode_settings_found = current_dir.join(".vscode/settings.json").exists();
+        let vscode_sdk_path = ide_manager::vscode_sdk_path(current_dir)
+            .await
+            .map(|sdk_path| sdk_path.map(status))
+            .into();
+
+        let idea_found = current_dir.join(".idea").exists();
+        let dart_sdk_xml = if idea_found {
+            ide_manager::dart_sdk_xml_path(current_dir)
+                .await
+                .map(|sdk_path| sdk_path.map(status))
+                .into()
+        } else {
+            Checked::Ok(None)
+        };
+
+        let sdk_paths = ide_manager::read_sdk_paths(current_dir)
+            .await
+            .map(|sdk_paths| sdk_paths.into_iter().map(status).collect())
+            .into();
+
+        let flutter_sdk_link_path = current_dir.join(".fvm/flutter_sdk");
+        let flutter_sdk_link = if !flutter_sdk_link_path.exists() {
+            FlutterSdkLink::Missing
+        } else if flutter_sdk_link_path.is_symlink() {
+            FlutterSdkLink::Symlink {
+                target: tokio::fs::read_link(&flutter_sdk_link_path).await?,
+            }
+        } else {
+            FlutterSdkLink::NotSymlink
+        };
+
+        // IntelliJ's Dart_SDK.xml points at .fvm/flutter_sdk/bin/cache/dart-sdk,
+        // which only works if both symlinks in the chain resolve
+        let dart_sdk = flutter_sdk_link_path
+            .symlink_metadata()
+            .is_ok()
+            .then(|| ide_manager::resolve_dart_sdk(current_dir).into());
+
+        let fvm_dir = current_dir.join(".fvm");
+        let fvm_dir = if fvm_dir.is_dir() {
+            Some(FvmDirUsage {
+                size: utils::dir_size(&fvm_dir).unwrap_or(0),
+                tracked_sdk_files: gitignore_manager::count_tracked_files(current_dir, ".fvm/flutter_sdk")
+                    .await
+                    .unwrap_or(0),
+            })
+        } else {
+            None
+        };
+
+        Ok(Self {
+            vscode_settings_found,
+            vscode_sdk_path,
+            idea_found,
+            dart_sdk_xml,
+            sdk_paths,
+            gitignore_present: current_dir.join(".fvm/.gitignore").exists(),
+            flutter_sdk_link,
+            dart_sdk,
+            fvm_dir,
+        })
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EnvironmentInfo {
+    os: &'static str,
+    arch: &'static str,
+    /// None when the cache directory couldn't be resolved
+    cache_dir: Option<PathBuf>,
+    cache_exists: bool,
+    cache_writable: bool,
+    /// Filesystem type when the cache is on a network mount
+    network_filesystem: Option<String>,
+    /// Cache and version paths with spaces or non-ASCII characters
+    unsafe_paths: Vec<PathBuf>,
+    non_executable_binaries: Vec<PathBuf>,
+    /// Versions whose binaries couldn't be checked
+    permission_check_errors: Vec<VersionError>,
+    broken_engine_links: Vec<String>,
+    dangling_global_link: Option<PathBuf>,
+    /// ~/.fvm-rs/default left behind after `cachePath` moved the cache
+    orphaned_global_link: Option<PathBuf>,
+    global_version: Option<String>,
+    /// Version pinned by the nearest project, which wins over the global one
+    project_version: Option<String>,
+    flutter_in_path: Option<PathBuf>,
+    fvm_executables: Vec<utils::FvmExecutable>,
+    java_home: Option<String>,
+    java: Option<PathBuf>,
+    java_version: Option<String>,
+    environment_variables: Vec<EnvVar>,
+    /// Checks that couldn't run, reported as failed instead of aborting doctor
+    failed_checks: Vec<FailedCheck>,
+}
+
+#[derive(Serialize)]
+struct FailedCheck {
+    check: &'static str,
+    error: String,
+}
+
+/// Keep a check's value, or record why it failed
+fn record_check<T>(failed_checks: &mut Vec<FailedCheck>, check: &'static str, result: Result<T>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(e) => {
+            failed_checks.push(FailedCheck {
+                check,
+                error: format!("{:#}", e),
+            });
+            None
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct VersionError {
+    version: String,
+    error: String,
+}
+
+/// An environment variable; secrets only report whether they are set
+#[derive(Serialize)]
+struct EnvVar {
+    name: &'static str,
+    set: bool,
+    value: Option<String>,
+}
+
+/// Environment variables fvm-rs reads, printed in this order
+const ENV_VARS: &[&str] = &[
+    "FVM_CACHE_PATH",
+    "FVM_USE_GIT_CACHE",
+    "FVM_GIT_CACHE_PATH",
+    "FVM_FLUTTER_URL",
+    "FVM_HOME",
+    "FVM_ENGINE_PLATFORM",
+    "FVM_SCOPED_PUB_CACHE",
+    "FVM_HTTP_RETRIES",
+    "FVM_RELEASES_CACHE_TTL",
+    "FVM_STORAGE_BASE_URL",
+    "FVM_CA_CERT",
+    "FVM_SHALLOW_CLONE",
+    "FLUTTER_STORAGE_BASE_URL",
+];
+
+/// Environment variables whose values must not be shown
+const SECRET_ENV_VARS: &[&str] = &["FVM_GIT_TOKEN"];
+
+impl EnvironmentInfo {
+    async fn gather() -> Self {
+        let mut failed_checks = vec![];
+        let cache_dir = record_check(&mut failed_checks, "FVM Cache", utils::cache_root_dir());
+        let versions = record_check(
+            &mut failed_checks,
+            "Installed Versions",
+            sdk_manager::list_installed_versions().await,
+        )
+        .unwrap_or_default();
+
+        // Spaces and non-ASCII characters in SDK paths break Flutter/Gradle builds
+        let mut unsafe_paths = vec![];
+        if let Some(cache_dir) = &cache_dir
+            && utils::has_unsafe_path_chars(cache_dir)
+        {
+            unsafe_paths.push(cache_dir.clone());
+        }
+        for version in &versions {
+            if let Ok(version_dir) = utils::flutter_version_dir(version)
+                && utils::has_unsafe_path_chars(&version_dir)
+            {
+                unsafe_paths.push(version_dir);
+            }
+        }
+
+        let mut non_executable_binaries = vec![];
+        let mut permission_check_errors = vec![];
+        for version in &versions {
+            match sdk_manager::non_executable_binaries(version) {
+                Ok(binaries) => non_executable_binaries.extend(binaries),
+                Err(e) => permission_check_errors.push(VersionError {
+                    version: version.clone(),
+                    error: format!("{:#}", e),
+                }),
+            }
+        }
+
+        let mut broken_engine_links = vec![];
+        for version in &versions {
+            if sdk_manager::has_broken_engine_link(version).await.unwrap_or(false) {
+                broken_engine_links.push(version.clone());
+            }
+        }
+
+        let java = utils::java_executable();
+        let java_version = java.as_deref().and_then(utils::java_version);
+
+        let environment_variables = ENV_VARS
+            .iter()
+            .map(|&name| EnvVar {
+                name,
+                set: env::var_os(name).is_some(),
+                value: env::var(name).ok(),
+            })
+            .chain(SECRET_ENV_VARS.iter().map(|&name| EnvVar {
+                name,
+                set: env::var_os(name).is_some(),
+                value: None,
+            }))
+            .collect();
+
+        let dangling_global_link = record_check(
+            &mut failed_checks,
+            "Global Link",
+            sdk_manager::dangling_global_link().await,
+        )
+        .flatten();
+        let orphaned_global_link = record_check(
+            &mut failed_checks,
+            "Old Global Link",
+            sdk_manager::orphaned_global_link(),
+        )
+        .flatten();
+        let global_version = record_check(
+            &mut failed_checks,
+            "Global Version",
+            config_manager::get_global_flutter_version().await,
+        )
+        .flatten();
+        let project_version = record_check(
+            &mut failed_checks,
+            "Active Version",
+            config_manager::get_project_flutter_version().await,
+        )
+        .flatten();
+
+        Self {
+            os: env::consts::OS,
+            arch: env::consts::ARCH,
+            cache_exists: cache_dir.as_deref().is_some_and(Path::exists),
+            cache_writable: cache_dir.as_deref().is_some_and(utils::is_writable),
+            network_filesystem: cache_dir.as_deref().and_then(utils::network_filesystem_type),
+            cache_dir,
+            unsafe_paths,
+            non_executable_binaries,
+            permission_check_errors,
+            broken_engine_links,
+            dangling_global_link,
+            orphaned_global_link,
+            global_version,
+            project_version,
+            flutter_in_path: which::which("flutter").ok(),
+            fvm_executables: utils::fvm_executables_in_path(),
+            java_home: env::var("JAVA_HOME").ok().filter(|java_home| !java_home.is_empty()),
+            java,
+            java_version,
+            environment_variables,
+            failed_checks,
+        }
+    }
+}
+
+fn print_project_info(info: &ProjectInfo, summary: &mut Summary) {
+    summary.line("📋 Project Information");
+    summary.line("──────────────────────────────────────────────────");
 
     // Current directory
-    let current_dir = env::current_dir().context("Failed to get current directory")?;
-    println!("  Directory:          {}", current_dir.display());
+    summary.line(format!("  Directory:          {}", info.directory.display()));
 
     // Check if FVM config exists
-    let config = config_manager::read_project_config(&current_dir).await?;
-    if let Some(cfg) = config {
-        println!("  FVM Configured:     ✓ Yes");
-        println!("  Flutter Version:    {}", cfg.flutter);
+    if let Some(cfg) = &info.config {
+        summary.ok("  FVM Configured:     ✓ Yes");
+        summary.line(format!("  Flutter Version:    {}", cfg.flutter_version));
 
-        if let Some(flavors) = &cfg.flavors {
-            println!("  Flavors:            {} configured", flavors.len());
-            for (name, version) in flavors {
-                println!("    • {}: {}", name, version);
-            }
+        if cfg.flavors.is_empty() {
+            summary.line("  Flavors:            None");
         } else {
-            println!("  Flavors:            None");
+            summary.line(format!("  Flavors:            {} configured", cfg.flavors.len()));
+            for (name, version) in &cfg.flavors {
+                summary.line(format!("    • {}: {}", name, version));
+            }
         }
 
-        // Check if .fvmrc exists
-        let fvmrc_path = current_dir.join(".fvmrc");
-        if fvmrc_path.exists() {
-            println!("  Config File:        .fvmrc");
+        if cfg.config_file == ".fvmrc" {
+            summary.line("  Config File:        .fvmrc");
         } else {
-            println!("  Config File:        .fvm/fvm_config.json (legacy)");
+            summary.line("  Config File:        .fvm/fvm_config.json (legacy)");
+        }
+
+        // Helps tell whether a config was written by an fvm-rs with known bugs
+        match &cfg.written_by {
+            Checked::Ok(Some(written_by)) if written_by == env!("CARGO_PKG_VERSION") => {
+                summary.line(format!("  Written By:         fvm-rs {}", written_by));
+            }
+            Checked::Ok(Some(written_by)) => {
+                summary.line(format!(
+                    "  Written By:         fvm-rs {} (running {})",
+                    written_by,
+                    env!("CARGO_PKG_VERSION")
+                ));
+            }
+            Checked::Ok(None) => {
+                summary.line("  Written By:         Unknown (FVM or an older fvm-rs)");
+            }
+            Checked::Failed { error } => {
+                summary.warning(format!("  Written By:         ⚠ Could not read .fvm/fvm_config.json: {}", error));
+            }
+        }
+
+        // Tools reading different files would otherwise see different versions
+        if !cfg.config_mismatches.is_empty() {
+            summary.warning("  Config Files Sync:  ⚠ .fvmrc and .fvm/fvm_config.json disagree");
+            for mismatch in &cfg.config_mismatches {
+                summary.line(format!(
+                    "    • {}: .fvmrc={}, legacy={}",
+                    mismatch.field,
+                    mismatch.fvmrc.as_deref().unwrap_or("(missing)"),
+                    mismatch.legacy.as_deref().unwrap_or("(missing)")
+                ));
+            }
+            summary.line("    Hint:             .fvmrc is authoritative. Run 'fvm-rs doctor --fix' to rewrite both files from it");
+            summary.suggest("fvm-rs doctor --fix");
         }
 
         // Check if version is installed
-        let version_dir = utils::flutter_version_dir(&cfg.flutter)?;
-        if version_dir.exists() {
-            println!("  Version Installed:  ✓ Yes");
+        if cfg.version_installed {
+            summary.ok("  Version Installed:  ✓ Yes");
+            if let Some(metadata) = &cfg.install_metadata {
+                print_install_metadata(metadata, summary);
+            }
         } else {
-            println!("  Version Installed:  ✗ No (run: fvm-rs install {})", cfg.flutter);
+            summary.error(format!("  Version Installed:  ✗ No (run: fvm-rs install {})", cfg.flutter_version));
+            summary.suggest(format!("fvm-rs install {}", cfg.flutter_version));
         }
+
+        print_platform_support(&cfg.platform_support, &cfg.flutter_version, summary);
     } else {
-        println!("  FVM Configured:     ✗ No");
-        println!("  Hint:               Run 'fvm-rs use <version>' to configure this project");
+        summary.warning("  FVM Configured:     ⚠ No");
+        summary.line("  Hint:               Run 'fvm-rs use <version>' to configure this project");
+        summary.suggest("fvm-rs use <version>");
     }
 
     // Check if this is a Flutter project
-    let pubspec_path = current_dir.join("pubspec.yaml");
-    if pubspec_path.exists() {
-        println!("  Flutter Project:    ✓ Yes");
+    if info.flutter_project {
+        summary.ok("  Flutter Project:    ✓ Yes");
     } else {
-        println!("  Flutter Project:    ⚠ No pubspec.yaml found");
+        summary.warning("  Flutter Project:    ⚠ No pubspec.yaml found");
     }
-
-    Ok(())
 }
 
-async fn print_ide_integration() -> Result<()> {
-    println!("🔧 IDE Integration");
-    println!("──────────────────────────────────────────────────");
+/// Print how the project's version was installed and its engine hash
+fn print_install_metadata(metadata: &Checked<sdk_manager::InstallMetadata>, summary: &Summary) {
+    let metadata = match metadata {
+        Checked::Ok(metadata) => metadata,
+        Checked::Failed { error } => {
+            summary.line(format!("    Metadata:         Could not read: {}", error));
+            return;
+        }
+    };
+
+    let source = if metadata.recorded { "recorded at install" } else { "derived, no .fvm-rs-meta.json" };
+    match &metadata.engine_hash {
+        Some(hash) => summary.line(format!("    Engine Hash:      {} ({})", hash, source)),
+        None => summary.line(format!("    Engine Hash:      None linked ({})", source)),
+    }
+    if let Some(installed_at) = metadata.installed_at {
+        summary.line(format!("    Installed:        {}", installed_at.format("%Y-%m-%d %H:%M UTC")));
+    }
+}
 
-    let current_dir = env::current_dir().context("Failed to get current directory")?;
+fn print_ide_integration(info: &IdeInfo, summary: &mut Summary) {
+    summary.line("🔧 IDE Integration");
+    summary.line("──────────────────────────────────────────────────");
 
     // VS Code settings
-    let vscode_settings = current_dir.join(".vscode/settings.json");
-    if vscode_settings.exists() {
-        println!("  VS Code Settings:   ✓ Found");
-        // TODO: Validate that dart.flutterSdkPath is correct
+    if info.vscode_settings_found {
+        match &info.vscode_sdk_path {
+            Checked::Ok(Some(sdk_path)) if sdk_path.points_at_sdk => {
+                summary.ok(format!("  VS Code Settings:   ✓ dart.flutterSdkPath = {}", sdk_path.raw));
+            }
+            Checked::Ok(Some(sdk_path)) => {
+                summary.warning("  VS Code Settings:   ⚠ dart.flutterSdkPath points at another SDK");
+                summary.line(format!("    Path:             {}", sdk_path.raw));
+                summary.line("    Hint:             Run 'fvm-rs use <version>' to point it at .fvm/flutter_sdk");
+                summary.suggest("fvm-rs use <version>");
+            }
+            Checked::Ok(None) => {
+                summary.warning("  VS Code Settings:   ⚠ dart.flutterSdkPath not set");
+                summary.line("    Hint:             Run 'fvm-rs use <version>' to point it at .fvm/flutter_sdk");
+                summary.suggest("fvm-rs use <version>");
+            }
+            Checked::Failed { error } => {
+                summary.warning(format!("  VS Code Settings:   ⚠ Could not read: {}", error));
+            }
+        }
     } else {
-        println!("  VS Code Settings:   ✗ Not found");
-        println!("    Hint:             Create .vscode/settings.json with:");
-        println!("                      {{\"dart.flutterSdkPath\": \".fvm/flutter_sdk\"}}");
+        summary.warning("  VS Code Settings:   ⚠ Not found");
+        summary.suggest("fvm-rs doctor --fix");
+        summary.line("    Hint:             Create .vscode/settings.json with:");
+        summary.line("                      {\"dart.flutterSdkPath\": \".fvm/flutter_sdk\"}");
     }
 
     // IntelliJ/Android Studio settings
-    let idea_dir = current_dir.join(".idea");
-    if idea_dir.exists() {
-        println!("  IntelliJ IDEA:      ✓ .idea directory found");
-        // TODO: Validate libraries/Dart_SDK.xml
+    if info.idea_found {
+        summary.ok("  IntelliJ IDEA:      ✓ .idea directory found");
+        match &info.dart_sdk_xml {
+            Checked::Ok(Some(sdk_path)) if sdk_path.points_at_sdk => {
+                summary.ok("  Dart_SDK.xml:       ✓ Points at the configured SDK");
+            }
+            Checked::Ok(Some(sdk_path)) => {
+                summary.warning("  Dart_SDK.xml:       ⚠ Points at another SDK");
+                summary.line(format!("    Path:             {}", sdk_path.raw));
+                summary.line("    Hint:             Run 'fvm-rs use <version>' to point it at .fvm/flutter_sdk");
+                summary.suggest("fvm-rs use <version>");
+            }
+            Checked::Ok(None) => {
+                summary.line("  Dart_SDK.xml:       - Not found");
+            }
+            Checked::Failed { error } => {
+                summary.warning(format!("  Dart_SDK.xml:       ⚠ Could not read: {}", error));
+            }
+        }
     } else {
-        println!("  IntelliJ IDEA:      ✗ .idea directory not found");
+        summary.line("  IntelliJ IDEA:      - .idea directory not found");
+    }
+
+    // Check that all IDE files agree on the SDK path
+    match &info.sdk_paths {
+        Checked::Ok(sdk_paths) if sdk_paths.is_empty() => {
+            summary.line("  IDE SDK Paths:      - None configured");
+        }
+        Checked::Ok(sdk_paths) => {
+            if sdk_paths.iter().all(|p| p.points_at_sdk) {
+                summary.ok("  IDE SDK Paths:      ✓ Consistent (.fvm/flutter_sdk)");
+            } else {
+                summary.warning("  IDE SDK Paths:      ⚠ Conflicting or unexpected paths");
+                for sdk_path in sdk_paths {
+                    let marker = if sdk_path.points_at_sdk { "✓" } else { "✗" };
+                    summary.line(format!("    {} {:<28} {}", marker, sdk_path.source, sdk_path.raw));
+                }
+                summary.line("    Hint:             Run 'fvm-rs doctor --fix' to point all IDEs at .fvm/flutter_sdk");
+                summary.suggest("fvm-rs doctor --fix");
+            }
+        }
+        Checked::Failed { error } => {
+            summary.warning(format!("  IDE SDK Paths:      ⚠ Could not read IDE files: {}", error));
+        }
     }
 
     // Check .gitignore
-    let gitignore = current_dir.join(".fvm/.gitignore");
-    if gitignore.exists() {
-        println!("  .fvm/.gitignore:    ✓ Present");
+    if info.gitignore_present {
+        summary.ok("  .fvm/.gitignore:    ✓ Present");
     } else {
-        println!("  .fvm/.gitignore:    ⚠ Missing");
+        summary.warning("  .fvm/.gitignore:    ⚠ Missing");
+        summary.suggest("fvm-rs doctor --fix");
     }
 
     // Check .fvm/flutter_sdk symlink (legacy format)
-    let flutter_sdk_link = current_dir.join(".fvm/flutter_sdk");
-    if flutter_sdk_link.exists() {
-        if flutter_sdk_link.is_symlink() {
-            let target = tokio::fs::read_link(&flutter_sdk_link).await?;
-            println!("  Flutter SDK Link:   ✓ Valid symlink");
-            println!("    Target:           {}", target.display());
-        } else {
-            println!("  Flutter SDK Link:   ⚠ Exists but not a symlink");
+    match &info.flutter_sdk_link {
+        FlutterSdkLink::Symlink { target } => {
+            summary.ok("  Flutter SDK Link:   ✓ Valid symlink");
+            summary.line(format!("    Target:           {}", target.display()));
+        }
+        FlutterSdkLink::NotSymlink => {
+            summary.warning("  Flutter SDK Link:   ⚠ Exists but not a symlink");
+        }
+        FlutterSdkLink::Missing => {
+            summary.warning("  Flutter SDK Link:   ⚠ Not found (.fvm/flutter_sdk)");
+            summary.line("    Hint:             IDEs resolve the SDK through this link. Run 'fvm-rs doctor --fix' to create it");
+            summary.suggest("fvm-rs doctor --fix");
         }
-    } else {
-        println!("  Flutter SDK Link:   ✗ Not found (.fvm/flutter_sdk)");
-        println!("    Note:             fvm-rs uses direct config, symlink not required");
     }
 
-    Ok(())
+    match &info.dart_sdk {
+        Some(Checked::Ok(ide_manager::DartSdkResolution::SharedEngine { resolved, engine_hash })) => {
+            summary.ok(format!("  IDE Dart SDK:       ✓ Shared engine {}", engine_hash));
+            summary.line(format!("    Resolved:         {}", resolved.display()));
+        }
+        Some(Checked::Ok(ide_manager::DartSdkResolution::Elsewhere(resolved))) => {
+            summary.warning("  IDE Dart SDK:       ⚠ Not in the shared engine cache");
+            summary.line(format!("    Resolved:         {}", resolved.display()));
+        }
+        Some(Checked::Ok(ide_manager::DartSdkResolution::BrokenLink { link, target })) => {
+            summary.error("  IDE Dart SDK:       ✗ Broken symlink");
+            summary.line(format!("    Link:             {}", link.display()));
+            summary.line(format!("    Target:           {} (missing)", target.display()));
+            if link.ends_with(".fvm/flutter_sdk") {
+                summary.line("    Hint:             IntelliJ will report \"Dart SDK not found\". Run 'fvm-rs doctor --fix' to recreate the link");
+                summary.suggest("fvm-rs doctor --fix");
+            } else {
+                summary.line("    Hint:             IntelliJ will report \"Dart SDK not found\". Reinstall the version with 'fvm-rs install'");
+                summary.suggest("fvm-rs install <version>");
+            }
+        }
+        Some(Checked::Ok(ide_manager::DartSdkResolution::Missing(path))) => {
+            summary.error(format!("  IDE Dart SDK:       ✗ Missing {}", path.display()));
+            summary.line("    Hint:             IntelliJ will report \"Dart SDK not found\". Reinstall the version with 'fvm-rs install'");
+            summary.suggest("fvm-rs install <version>");
+        }
+        Some(Checked::Failed { error }) => {
+            summary.warning(format!("  IDE Dart SDK:       ⚠ Could not resolve: {}", error));
+        }
+        None => {}
+    }
+
+    // A real SDK copied into .fvm bloats the repository, especially once committed
+    if let Some(usage) = &info.fvm_dir
+        && (usage.size > FVM_DIR_SIZE_WARNING || usage.tracked_sdk_files > 0)
+    {
+        summary.warning(format!("  .fvm Size:          ⚠ {}", utils::format_size(usage.size)));
+        if usage.tracked_sdk_files > 0 {
+            summary.line(format!("    Note:             {} file(s) under .fvm/flutter_sdk are committed to git", usage.tracked_sdk_files));
+        }
+        summary.line("    Hint:             .fvm should only hold config and a flutter_sdk symlink.");
+        summary.line("                      Delete the copied SDK (and 'git rm -r --cached .fvm/flutter_sdk' if committed),");
+        summary.line("                      then run 'fvm-rs doctor --fix' to recreate the symlink and .fvm/.gitignore");
+    }
 }
 
-async fn print_environment_info() -> Result<()> {
-    println!("🌍 Environment");
-    println!("──────────────────────────────────────────────────");
+fn print_environment_info(info: &EnvironmentInfo, summary: &mut Summary) {
+    summary.line("🌍 Environment");
+    summary.line("──────────────────────────────────────────────────");
 
     // Platform info
-    println!("  Platform:           {} ({})", env::consts::OS, env::consts::ARCH);
+    summary.line(format!("  Platform:           {} ({})", info.os, info.arch));
+
+    for failed in &info.failed_checks {
+        summary.error(format!("  {:<20}✗ Could not check: {}", format!("{}:", failed.check), failed.error));
+    }
 
     // FVM cache directory
-    let fvm_dir = utils::get_fvm_dir()?;
-    println!("  FVM Cache:          {}", fvm_dir.display());
-    if fvm_dir.exists() {
-        println!("  Cache Exists:       ✓ Yes");
+    if let Some(cache_dir) = &info.cache_dir {
+        summary.line(format!("  FVM Cache:          {}", cache_dir.display()));
+        if info.cache_exists {
+            summary.ok("  Cache Exists:       ✓ Yes");
+        } else {
+            summary.warning("  Cache Exists:       ⚠ No");
+            summary.suggest("fvm-rs install stable");
+        }
+        if !info.cache_writable {
+            summary.warning("  Cache Writable:     ⚠ No (read-only: install/remove unavailable, existing versions still run)");
+        }
+    }
+
+    // Network mounts make git operations slow and symlinks unreliable
+    match &info.network_filesystem {
+        Some(fs_type) => {
+            summary.warning(format!("  Cache Filesystem:   ⚠ Network filesystem ({})", fs_type));
+            summary.line("    Hint:             Git operations may be slow and symlinks may fail.");
+            summary.line("                      Use a local cache path: fvm-rs config --cache-path <path>");
+            summary.suggest("fvm-rs config --cache-path <path>");
+        }
+        None => {
+            summary.ok("  Cache Filesystem:   ✓ Local");
+        }
+    }
+
+    if info.unsafe_paths.is_empty() {
+        summary.ok("  Path Characters:    ✓ No spaces or non-ASCII characters");
+    } else {
+        summary.warning("  Path Characters:    ⚠ Spaces or non-ASCII characters found");
+        for path in &info.unsafe_paths {
+            summary.line(format!("    • {}", path.display()));
+        }
+        summary.line("    Hint:             These may break Flutter and Gradle builds.");
+        summary.line("                      Use a plain ASCII path: fvm-rs config --cache-path <path>");
+        summary.suggest("fvm-rs config --cache-path <path>");
+    }
+
+    // Non-executable binaries fail with "Permission denied" when running flutter
+    for failed in &info.permission_check_errors {
+        summary.warning(format!("  Execute Bits:       ⚠ Could not check Flutter {}: {}", failed.version, failed.error));
+    }
+    if info.non_executable_binaries.is_empty() {
+        summary.ok("  Execute Bits:       ✓ flutter/dart binaries are executable");
+    } else {
+        summary.error("  Execute Bits:       ✗ Binaries missing execute permission");
+        for path in &info.non_executable_binaries {
+            summary.line(format!("    • {}", path.display()));
+        }
+        summary.line("    Hint:             Running them fails with \"Permission denied\".");
+        summary.line("                      Run 'fvm-rs doctor --fix' to restore the permissions");
+        summary.suggest("fvm-rs doctor --fix");
+    }
+
+    if info.broken_engine_links.is_empty() {
+        summary.ok("  Engine Links:       ✓ All installed versions resolve their engine");
     } else {
-        println!("  Cache Exists:       ✗ No");
+        summary.error("  Engine Links:       ✗ bin/cache/dart-sdk doesn't resolve");
+        for version in &info.broken_engine_links {
+            summary.line(format!("    • {}", version));
+        }
+        summary.line("    Hint:             Run 'fvm-rs doctor --fix' to relink (and re-download) the engines");
+        summary.suggest("fvm-rs doctor --fix");
+    }
+
+    if let Some(target) = &info.dangling_global_link {
+        summary.error(format!("  Global Link:        ✗ Points to missing {}", target.display()));
+        summary.line("    Hint:             Run 'fvm-rs doctor --fix' to remove it, then 'fvm-rs global <version>'");
+        summary.suggest("fvm-rs doctor --fix");
+    }
+
+    if let Some(old_link) = &info.orphaned_global_link
+        && let Some(cache_dir) = &info.cache_dir
+    {
+        summary.warning(format!(
+            "  Old Global Link:    ⚠ {} is no longer updated since the cache moved to {}",
+            old_link.display(),
+            cache_dir.display()
+        ));
+        summary.line(format!(
+            "    Hint:             Put {} on PATH instead, then run 'fvm-rs doctor --fix' to remove it",
+            cache_dir.join("default").join("bin").display()
+        ));
+        summary.suggest("fvm-rs doctor --fix");
     }
 
     // Global version
-    let global_version = config_manager::get_global_flutter_version().await?;
-    if let Some(version) = global_version {
-        println!("  Global Version:     {}", version);
+    if let Some(version) = &info.global_version {
+        summary.line(format!("  Global Version:     {}", version));
     } else {
-        println!("  Global Version:     Not set");
+        summary.line("  Global Version:     Not set");
+    }
+
+    // Which version exec/flutter/dart resolve to here: the project pin wins over global
+    match (&info.project_version, &info.global_version) {
+        (Some(project), Some(global)) if project != global => {
+            summary.warning(format!("  Active Version:     ⚠ {} (project pin)", project));
+            summary.line(format!("    Note:             The global version {} is ignored in this project.", global));
+            summary.line(format!("                      Outside it, flutter/dart fall back to {}.", global));
+        }
+        (Some(project), _) => {
+            summary.line(format!("  Active Version:     {} (project pin)", project));
+        }
+        (None, Some(global)) => {
+            summary.line(format!("  Active Version:     {} (global, no project pin found)", global));
+        }
+        (None, None) => {
+            summary.line("  Active Version:     None (no project pin or global version)");
+        }
     }
 
     // Flutter in PATH
-    match which::which("flutter") {
-        Ok(flutter_path) => {
-            println!("  Flutter in PATH:    ✓ {}", flutter_path.display());
+    match &info.flutter_in_path {
+        Some(flutter_path) => {
+            summary.ok(format!("  Flutter in PATH:    ✓ {}", flutter_path.display()));
         }
-        Err(_) => {
-            println!("  Flutter in PATH:    ✗ Not found");
+        None => {
+            summary.warning("  Flutter in PATH:    ⚠ Not found");
         }
     }
 
+    // Dart's fvm and fvm-rs (or an fvm alias of it) side by side cause confusion
+    print_fvm_executables(&info.fvm_executables, summary);
+
+    // Java/Gradle compatibility (advisory only)
+    print_java_info(info, summary);
+
     // Environment variables
-    println!("  Environment Variables:");
-    print_env_var("FVM_CACHE_PATH");
-    print_env_var("FVM_USE_GIT_CACHE");
-    print_env_var("FVM_GIT_CACHE_PATH");
-    print_env_var("FVM_FLUTTER_URL");
-    print_env_var("FVM_HOME");
+    summary.line("  Environment Variables:");
+    for var in &info.environment_variables {
+        match (&var.value, var.set) {
+            (Some(value), _) => summary.line(format!("    {:<20} {}", var.name, value)),
+            (None, true) => summary.line(format!("    {:<20} (set)", var.name)),
+            (None, false) => summary.line(format!("    {:<20} (not set)", var.name)),
+        }
+    }
+}
 
-    Ok(())
+/// List the fvm/fvm-rs executables on PATH and warn when `fvm` isn't fvm-rs
+fn print_fvm_executables(executables: &[utils::FvmExecutable], summary: &mut Summary) {
+    if executables.is_empty() {
+        summary.line("  FVM Tools in PATH:  - None found");
+        return;
+    }
+
+    // Shell aliases aren't visible here, only what PATH lookup finds
+    let fvm = executables.iter().find(|e| e.name == "fvm");
+    match fvm {
+        Some(fvm) if !fvm.is_fvm_rs => {
+            summary.warning("  FVM Tools in PATH:  ⚠ `fvm` resolves to a different tool than fvm-rs");
+        }
+        _ if executables.iter().any(|e| !e.is_fvm_rs) => {
+            summary.warning("  FVM Tools in PATH:  ⚠ Other FVM installations found");
+        }
+        _ => {
+            summary.ok("  FVM Tools in PATH:  ✓ Only fvm-rs");
+        }
+    }
+
+    for executable in executables {
+        let version = executable.version.as_deref().unwrap_or("version unknown");
+        let marker = if executable.is_fvm_rs { " (fvm-rs)" } else { "" };
+        summary.line(format!(
+            "    • {:<7} {} [{}]{}",
+            executable.name,
+            executable.path.display(),
+            version,
+            marker
+        ));
+    }
+
+    if let Some(fvm) = fvm.filter(|fvm| !fvm.is_fvm_rs) {
+        summary.line(format!("    Hint:             Running `fvm` starts {}, not fvm-rs.", fvm.path.display()));
+        summary.line("                      Reorder PATH or call fvm-rs directly to avoid surprises");
+    }
+}
+
+/// Check which of the project's platform directories the pinned version likely predates
+///
+/// Advisory only: the release date (or the version number when the releases
+/// feed hasn't been cached) is a rough proxy for what a version supports.
+async fn platform_support(project_root: &Path, version: &str) -> Vec<PlatformStatus> {
+    let platforms: Vec<&PlatformSupport> = PLATFORM_SUPPORT
+        .iter()
+        .filter(|platform| project_root.join(platform.dir).is_dir())
+        .collect();
+
+    // Channels track current releases
+    if platforms.is_empty() || config_manager::is_channel(version) {
+        return vec![];
+    }
+
+    let release = sdk_manager::release_name(version);
+    // Only the cached feed, so doctor never waits on the network
+    let source = sdk_manager::ReleasesSource::Offline;
+    let release_date = sdk_manager::list_available_versions_from(source)
+        .await
+        .ok()
+        .and_then(|releases| {
+            releases
+                .releases
+                .iter()
+                .find(|r| r.version == release)
+                .map(|r| r.release_date.date_naive())
+        });
+    let release_minor = flutter_release_minor(&release);
+
+    let predates = |platform: &PlatformSupport| match (release_date, release_minor) {
+        (Some(date), _) => {
+            let (year, month, day) = platform.released;
+            NaiveDate::from_ymd_opt(year, month, day).is_some_and(|since| date < since)
+        }
+        (None, Some(minor)) => minor < platform.stable_since,
+        (None, None) => false,
+    };
+
+    platforms
+        .into_iter()
+        .map(|platform| {
+            let (major, minor) = platform.stable_since;
+            PlatformStatus {
+                dir: platform.dir,
+                name: platform.name,
+                stable_since: format!("{}.{}", major, minor),
+                predates: predates(platform),
+            }
+        })
+        .collect()
+}
+
+/// Warn when the pinned version likely predates stable support for a platform the project has
+fn print_platform_support(platforms: &[PlatformStatus], version: &str, summary: &mut Summary) {
+    if platforms.is_empty() {
+        return;
+    }
+
+    if platforms.iter().all(|p| !p.predates) {
+        let dirs: Vec<&str> = platforms.iter().map(|p| p.dir).collect();
+        summary.ok(format!("  Platform Support:   ✓ {}", dirs.join(", ")));
+        return;
+    }
+
+    summary.warning(format!("  Platform Support:   ⚠ Flutter {} likely predates stable support for:", version));
+    for platform in platforms.iter().filter(|p| p.predates) {
+        summary.line(format!(
+            "    • {} ({}/): stable since Flutter {}",
+            platform.name, platform.dir, platform.stable_since
+        ));
+    }
+    summary.line("    Hint:             Advisory only. Consider upgrading if builds for these platforms fail");
 }
 
-fn print_env_var(name: &str) {
-    if let Ok(value) = env::var(name) {
-        println!("    {:<20} {}", name, value);
+/// Report the detected JDK next to Flutter's documented guidance for the pinned version
+///
+/// Android builds commonly break after switching Flutter versions because the
+/// bundled Gradle/AGP templates expect a different JDK. This can't be validated
+/// without running Gradle, so it is only a hint.
+fn print_java_info(info: &EnvironmentInfo, summary: &mut Summary) {
+    match &info.java_home {
+        Some(java_home) => summary.line(format!("  JAVA_HOME:          {}", java_home)),
+        None => summary.line("  JAVA_HOME:          Not set"),
+    }
+
+    let Some(java) = &info.java else {
+        summary.warning("  Java:               ⚠ Not found (needed for Android builds)");
+        return;
+    };
+
+    let Some(version) = &info.java_version else {
+        summary.warning(format!("  Java:               ⚠ Could not run {} -version", java.display()));
+        return;
+    };
+
+    // Guidance from Flutter's Android Java/Gradle migration notes
+    let project_version = info.project_version.as_deref();
+    let guidance = project_version
+        .and_then(flutter_release_minor)
+        .map(|release| match release {
+            (3, minor) if minor >= 27 => (17, "JDK 17 (JDK 21 needs Gradle 8.4+ in android/)"),
+            (3, minor) if minor >= 16 => (17, "JDK 17"),
+            (major, _) if major > 3 => (17, "JDK 17"),
+            _ => (11, "JDK 11"),
+        });
+
+    let too_old = matches!(
+        (guidance, utils::java_major_version(version)),
+        (Some((required, _)), Some(major)) if major < required
+    );
+    if too_old {
+        summary.warning(format!("  Java:               ⚠ {} ({})", version, java.display()));
     } else {
-        println!("    {:<20} (not set)", name);
+        summary.line(format!("  Java:               {} ({})", version, java.display()));
     }
+
+    if let (Some(flutter_version), Some((_, text))) = (project_version, guidance) {
+        summary.line(format!("    Note:             Flutter {} expects {}", flutter_version, text));
+    }
+}
+
+/// Parse "3.24.0" into (3, 24); channels and other names yield None
+fn flutter_release_minor(version: &str) -> Option<(u32, u32)> {
+    let mut parts = version.split('.');
+    Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
 }
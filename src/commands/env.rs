@@ -0,0 +1,110 @@
+use anyhow::{Result, bail};
+use clap::Args;
+use std::path::PathBuf;
+use tracing::{debug, info};
+
+use crate::{config_manager, utils};
+
+#[derive(Debug, Clone, Args)]
+pub struct EnvArgs {
+    /// Shell syntax to print (sh covers bash and zsh)
+    #[arg(
+        long,
+        value_parser = clap::builder::PossibleValuesParser::new(["sh", "fish", "powershell"]),
+        default_value = "sh"
+    )]
+    shell: String,
+}
+
+/// Print environment setup for the project/global version, e.g. for
+/// `eval "$(fvm-rs env)"` in a direnv `.envrc`
+pub async fn run(args: EnvArgs) -> Result<()> {
+    info!("Printing environment for {} shell", args.shell);
+
+    let version = match config_manager::get_project_flutter_version().await? {
+        Some(version) => version,
+        None => match config_manager::get_global_flutter_version().await? {
+            Some(version) => version,
+            None => {
+                eprintln!("✗ No Flutter version configured for this project or globally");
+                eprintln!("  Run: fvm-rs use <version> or fvm-rs global <version>");
+                bail!("No Flutter version configured");
+            }
+        },
+    };
+
+    debug!("Printing environment for version: {}", version);
+    let flutter_path = utils::flutter_version_dir(&version)?;
+    if !flutter_path.exists() {
+        eprintln!("⚠ Flutter {} is not installed", version);
+        eprintln!("  Run: fvm-rs install {}", version);
+    }
+
+    // Same variables execute_with_flutter_path sets for child processes
+    let bin_dirs = utils::flutter_bin_dirs(&flutter_path);
+    let pub_cache = utils::scoped_pub_cache_dir(&flutter_path)?;
+
+    let lines = match args.shell.as_str() {
+        "fish" => fish_lines(&bin_dirs, pub_cache),
+        "powershell" => powershell_lines(&bin_dirs, pub_cache),
+        _ => sh_lines(&bin_dirs, pub_cache),
+    };
+    for line in lines {
+        println!("{}", line);
+    }
+
+    Ok(())
+}
+
+fn sh_lines(bin_dirs: &[PathBuf], pub_cache: Option<PathBuf>) -> Vec<String> {
+    let quote = |path: &PathBuf| {
+        path.display()
+            .to_string()
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('$', "\\$")
+            .replace('`', "\\`")
+    };
+
+    let dirs: Vec<String> = bin_dirs.iter().map(quote).collect();
+    let mut lines = vec![format!("export PATH=\"{}:$PATH\"", dirs.join(":"))];
+    if let Some(pub_cache) = pub_cache {
+        lines.push(format!("export PUB_CACHE=\"{}\"", quote(&pub_cache)));
+    }
+    lines
+}
+
+fn fish_lines(bin_dirs: &[PathBuf], pub_cache: Option<PathBuf>) -> Vec<String> {
+    let quote = |path: &PathBuf| {
+        format!(
+            "'{}'",
+            path.display()
+                .to_string()
+                .replace('\\', "\\\\")
+                .replace('\'', "\\'")
+        )
+    };
+
+    let dirs: Vec<String> = bin_dirs.iter().map(quote).collect();
+    let mut lines = vec![format!("set -gx PATH {} $PATH", dirs.join(" "))];
+    if let Some(pub_cache) = pub_cache {
+        lines.push(format!("set -gx PUB_CACHE {}", quote(&pub_cache)));
+    }
+    lines
+}
+
+fn powershell_lines(bin_dirs: &[PathBuf], pub_cache: Option<PathBuf>) -> Vec<String> {
+    let quote = |path: &PathBuf| format!("'{}'", path.display().to_string().replace('\'', "''"));
+
+    // PathSeparator is ';' on Windows and ':' for PowerShell on macOS/Linux
+    let mut parts: Vec<String> = bin_dirs.iter().map(quote).collect();
+    parts.push("$env:PATH".to_string());
+    let mut lines = vec![format!(
+        "$env:PATH = {}",
+        parts.join(" + [IO.Path]::PathSeparator + ")
+    )];
+    if let Some(pub_cache) = pub_cache {
+        lines.push(format!("$env:PUB_CACHE = {}", quote(&pub_cache)));
+    }
+    lines
+}
@@ -1,16 +1,53 @@
 use anyhow::{bail, Result};
 use clap::Args;
+use serde::Serialize;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::Semaphore;
 use tracing::{debug, info};
 
 use crate::{config_manager, sdk_manager, utils};
 
 #[derive(Debug, Clone, Args)]
 pub struct ExecArgs {
+    /// Run the command against each of these versions and print a JSON report
+    /// (e.g. "3.19.0,3.22.0,stable")
+    #[arg(long, value_name = "VERSIONS", value_delimiter = ',')]
+    with: Vec<String>,
+
+    /// Maximum number of versions to run at once with --with
+    #[arg(long, default_value_t = 1, requires = "with", value_parser = clap::value_parser!(u16).range(1..))]
+    jobs: u16,
+
     /// Command and arguments to execute
     #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
     command_args: Vec<String>,
 }
 
+/// Number of trailing output lines kept per stream in the --with report
+const OUTPUT_TAIL_LINES: usize = 20;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MatrixResult {
+    version: String,
+    /// None when the version could not be installed or the command could not start
+    exit_code: Option<i32>,
+    duration_ms: u128,
+    stdout_tail: String,
+    stderr_tail: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MatrixReport {
+    command: Vec<String>,
+    passed: bool,
+    results: Vec<MatrixResult>,
+}
+
 pub async fn run(args: ExecArgs) -> Result<i32> {
     // Validate that at least a command is provided
     if args.command_args.is_empty() {
@@ -19,6 +56,10 @@ pub async fn run(args: ExecArgs) -> Result<i32> {
         bail!("No command was provided to be executed");
     }
 
+    if !args.with.is_empty() {
+        return run_matrix(&args.with, args.jobs.into(), &args.command_args).await;
+    }
+
     let command = &args.command_args[0];
     let command_args = &args.command_args[1..];
 
@@ -64,3 +105,95 @@ pub async fn run(args: ExecArgs) -> Result<i32> {
         Ok(exit_code)
     }
 }
+
+/// Run a command once per version and print a JSON report for CI
+///
+/// Missing versions are installed up front, one at a time, since installs
+/// share the git cache. Returns 0 only if every run exited successfully.
+async fn run_matrix(versions: &[String], jobs: usize, command_args: &[String]) -> Result<i32> {
+    info!("Running {} against versions: {}", command_args.join(" "), versions.join(", "));
+
+    let mut install_errors = Vec::new();
+    for version in versions {
+        if let Err(e) = sdk_manager::ensure_installed(version).await {
+            install_errors.push((version.clone(), format!("{:#}", e)));
+        }
+    }
+
+    let semaphore = Arc::new(Semaphore::new(jobs));
+    let mut handles = Vec::new();
+
+    for version in versions {
+        if let Some((_, error)) = install_errors.iter().find(|(v, _)| v == version) {
+            let result = failed_result(version, 0, format!("Failed to install: {}", error));
+            handles.push(tokio::spawn(async move { result }));
+            continue;
+        }
+
+        let semaphore = semaphore.clone();
+        let version = version.clone();
+        let command_args = command_args.to_vec();
+        handles.push(tokio::spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let run_version = version.clone();
+            // A panicking run only fails its own version, keeping the report intact
+            tokio::task::spawn_blocking(move || run_for_version(&run_version, &command_args))
+                .await
+                .unwrap_or_else(|e| failed_result(&version, 0, e.to_string()))
+        }));
+    }
+
+    // Collect in input order so the report is stable
+    let mut results = Vec::new();
+    for handle in handles {
+        results.push(handle.await?);
+    }
+
+    let report = MatrixReport {
+        command: command_args.to_vec(),
+        passed: results.iter().all(|r| r.exit_code == Some(0)),
+        results,
+    };
+
+    println!("{}", serde_json::to_string_pretty(&report)?);
+
+    Ok(if report.passed { 0 } else { 1 })
+}
+
+fn run_for_version(version: &str, command_args: &[String]) -> MatrixResult {
+    let started = Instant::now();
+    let output = utils::flutter_version_dir(version).and_then(|flutter_path| {
+        utils::output_with_flutter_path(&command_args[0], &command_args[1..], &flutter_path)
+    });
+    let duration_ms = started.elapsed().as_millis();
+
+    match output {
+        Ok(output) => MatrixResult {
+            version: version.to_string(),
+            exit_code: Some(output.status.code().unwrap_or(1)),
+            duration_ms,
+            stdout_tail: output_tail(&output.stdout),
+            stderr_tail: output_tail(&output.stderr),
+            error: None,
+        },
+        Err(e) => failed_result(version, duration_ms, format!("{:#}", e)),
+    }
+}
+
+fn failed_result(version: &str, duration_ms: u128, error: String) -> MatrixResult {
+    MatrixResult {
+        version: version.to_string(),
+        exit_code: None,
+        duration_ms,
+        stdout_tail: String::new(),
+        stderr_tail: String::new(),
+        error: Some(error),
+    }
+}
+
+/// Keep the last few lines of captured output
+fn output_tail(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    let lines: Vec<&str> = text.lines().collect();
+    lines[lines.len().saturating_sub(OUTPUT_TAIL_LINES)..].join("\n")
+}
@@ -0,0 +1,41 @@
+use anyhow::Result;
+use clap::Args;
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::{archive_manager, utils};
+
+#[derive(Debug, Clone, Args)]
+pub struct ExportArgs {
+    /// Installed Flutter version to export
+    pub version: String,
+
+    /// Archive file to write (tar format)
+    pub file: PathBuf,
+}
+
+pub async fn run(args: ExportArgs) -> Result<()> {
+    info!(
+        "Exporting Flutter {} to {}",
+        args.version,
+        args.file.display()
+    );
+    println!("Exporting Flutter {}...", args.version);
+
+    let summary = archive_manager::export_version(&args.version, &args.file).await?;
+
+    println!(
+        "✓ Exported Flutter {} to {}",
+        args.version,
+        args.file.display()
+    );
+    println!("  Engine: {}", summary.engine_hash);
+    println!("  Size: {}", utils::format_size(summary.archive_size));
+    println!();
+    println!(
+        "Import it on another machine with: fvm-rs import {}",
+        args.file.display()
+    );
+
+    Ok(())
+}
@@ -1,42 +1,88 @@
 use anyhow::{Context, Result};
 use clap::Args;
-use tracing::info;
+use clap_complete::engine::ArgValueCandidates;
+use tracing::{debug, info};
 
-use crate::{config_manager, sdk_manager, utils};
+use crate::{completion, config_manager, sdk_manager, utils};
 
 #[derive(Debug, Clone, Args)]
 pub struct FlavorArgs {
     /// Flavor name to use (e.g., "production", "staging", "development")
-    flavor_name: String,
+    #[arg(
+        required_unless_present_any = ["all_flavors", "list", "remove"],
+        add = ArgValueCandidates::new(completion::project_flavors)
+    )]
+    flavor_name: Option<String>,
 
     /// Flutter command and arguments to execute with the flavor's SDK version
     #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
     flutter_args: Vec<String>,
+
+    /// Run the command once per defined flavor, each with its own SDK version
+    /// (e.g. `fvm-rs flavor --all-flavors build apk`)
+    #[arg(long)]
+    all_flavors: bool,
+
+    /// List the project's flavors and their versions
+    #[arg(long, conflicts_with_all = ["flavor_name", "all_flavors", "remove"])]
+    list: bool,
+
+    /// Remove a flavor from the project config
+    #[arg(
+        long,
+        value_name = "FLAVOR",
+        conflicts_with_all = ["flavor_name", "all_flavors"],
+        add = ArgValueCandidates::new(completion::project_flavors)
+    )]
+    remove: Option<String>,
 }
 
+/// Flutter subcommands that accept --dart-define
+const DART_DEFINE_COMMANDS: &[&str] = &["run", "build", "test", "drive", "attach"];
+
 pub async fn run(args: FlavorArgs) -> Result<()> {
     // Find project root (may be in a subdirectory)
     let project_root = config_manager::find_project_root()
         .await?
         .context("Not in an FVM project. Run 'fvm-rs use' to configure this project first.")?;
 
-    info!("Using flavor '{}' from project at: {}", args.flavor_name, project_root.display());
-
     // Read project config
     let config = config_manager::read_project_config(&project_root)
         .await?
         .context("No FVM configuration found. Run 'fvm-rs use' to configure this project first.")?;
 
-    // Get the version for this flavor
-    let version = config
+    if args.list {
+        print_flavors(&config);
+        return Ok(());
+    }
+
+    if let Some(flavor_name) = args.remove {
+        info!("Removing flavor '{}' from project at: {}", flavor_name, project_root.display());
+        let removed = config_manager::remove_project_flavor(&project_root, &flavor_name).await?;
+        println!("✓ Removed flavor {} ({})", flavor_name, removed.version());
+        println!("  Config saved to .fvmrc and .fvm/fvm_config.json");
+        return Ok(());
+    }
+
+    if args.all_flavors {
+        // Without a flavor name, the first positional argument is the Flutter command
+        let flutter_args: Vec<String> = args.flavor_name.into_iter().chain(args.flutter_args).collect();
+        return run_all_flavors(&config, &flutter_args).await;
+    }
+
+    let flavor_name = args.flavor_name.context("A flavor name or --all-flavors is required")?;
+    info!("Using flavor '{}' from project at: {}", flavor_name, project_root.display());
+
+    // Get the configuration for this flavor
+    let flavor = config
         .flavors
         .as_ref()
-        .and_then(|flavors| flavors.get(&args.flavor_name))
+        .and_then(|flavors| flavors.get(&flavor_name))
         .context(format!(
             "Flavor '{}' is not defined in project configuration.\n\
             Available flavors: {}\n\n\
             Use 'fvm-rs use <version> --flavor {}' to define this flavor.",
-            args.flavor_name,
+            flavor_name,
             config
                 .flavors
                 .as_ref()
@@ -48,11 +94,90 @@ pub async fn run(args: FlavorArgs) -> Result<()> {
                     }
                 })
                 .unwrap_or_else(|| "none".to_string()),
-            args.flavor_name
+            flavor_name
         ))?;
 
-    info!("Flavor '{}' resolved to version: {}", args.flavor_name, version);
-    println!("Running Flutter command with [{}] flavor (version: {})", args.flavor_name, version);
+    let exit_code = run_flavor(&flavor_name, flavor, &args.flutter_args).await?;
+
+    // Exit with the same code as the Flutter command
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
+
+    Ok(())
+}
+
+/// Print each flavor with its version, in name order
+fn print_flavors(config: &config_manager::ProjectConfig) {
+    let mut flavors: Vec<_> = config.flavors.iter().flatten().collect();
+    if flavors.is_empty() {
+        println!("No flavors defined.");
+        println!("  Use 'fvm-rs use <version> --flavor <name>' to define one.");
+        return;
+    }
+    flavors.sort_by_key(|(name, _)| *name);
+
+    println!("Flavors:");
+    for (name, flavor) in flavors {
+        let defines = flavor.dart_defines().len();
+        if defines == 0 {
+            println!("  {} → {}", name, flavor.version());
+        } else {
+            println!("  {} → {} ({} dart-defines)", name, flavor.version(), defines);
+        }
+    }
+}
+
+/// Run the command for every flavor in name order, continuing past failures
+async fn run_all_flavors(config: &config_manager::ProjectConfig, flutter_args: &[String]) -> Result<()> {
+    let mut flavors: Vec<_> = config.flavors.iter().flatten().collect();
+    if flavors.is_empty() {
+        anyhow::bail!("No flavors are defined in project configuration.\n\n\
+            Use 'fvm-rs use <version> --flavor <name>' to define one.");
+    }
+    flavors.sort_by_key(|(name, _)| *name);
+
+    let mut results = Vec::new();
+    for (name, flavor) in flavors {
+        let result = run_flavor(name, flavor, flutter_args).await;
+        println!();
+        results.push((name, flavor.version(), result));
+    }
+
+    println!("Flavor results:");
+    let mut failed_exit_code = None;
+    for (name, version, result) in &results {
+        match result {
+            Ok(0) => println!("  ✓ {} ({})", name, version),
+            Ok(code) => {
+                println!("  ✗ {} ({}): exited with code {}", name, version, code);
+                failed_exit_code.get_or_insert(*code);
+            }
+            Err(e) => {
+                println!("  ✗ {} ({}): {:#}", name, version, e);
+                failed_exit_code.get_or_insert(1);
+            }
+        }
+    }
+
+    // Exit with the first failing flavor's code so CI fails the step
+    if let Some(exit_code) = failed_exit_code {
+        std::process::exit(exit_code);
+    }
+
+    Ok(())
+}
+
+/// Install the flavor's version if needed and run the Flutter command with it
+async fn run_flavor(
+    flavor_name: &str,
+    flavor: &config_manager::FlavorConfig,
+    flutter_args: &[String],
+) -> Result<i32> {
+    let version = flavor.version();
+
+    info!("Flavor '{}' resolved to version: {}", flavor_name, version);
+    println!("Running Flutter command with [{}] flavor (version: {})", flavor_name, version);
 
     // Ensure the version is installed
     sdk_manager::ensure_installed(version).await?;
@@ -65,18 +190,25 @@ pub async fn run(args: FlavorArgs) -> Result<()> {
         anyhow::bail!("Flutter version {} is not installed at expected path: {}", version, flutter_path.display());
     }
 
+    // Append the flavor's dart-defines to commands that accept them
+    let mut flutter_args = flutter_args.to_vec();
+    let accepts_defines = flutter_args
+        .first()
+        .is_some_and(|cmd| DART_DEFINE_COMMANDS.contains(&cmd.as_str()));
+    if accepts_defines {
+        let mut defines: Vec<_> = flavor.dart_defines().into_iter().collect();
+        defines.sort();
+        for (key, value) in defines {
+            debug!("Adding dart-define {}={}", key, value);
+            flutter_args.push(format!("--dart-define={}={}", key, value));
+        }
+    }
+
     // Execute the Flutter command with this version
-    let exit_code = utils::execute_with_flutter_path(
+    utils::execute_with_flutter_path(
         "flutter",
-        &args.flutter_args,
+        &flutter_args,
         &flutter_path,
     )
-    .context("Failed to execute Flutter command")?;
-
-    // Exit with the same code as the Flutter command
-    if exit_code != 0 {
-        std::process::exit(exit_code);
-    }
-
-    Ok(())
+    .context("Failed to execute Flutter command")
 }
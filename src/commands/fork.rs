@@ -1,8 +1,11 @@
 use anyhow::{Context, Result};
 use clap::{Args, Subcommand};
+use dialoguer::{theme::ColorfulTheme, Confirm};
+use std::io::IsTerminal;
 use tracing::info;
 
 use crate::config_manager::GlobalConfig;
+use crate::{progress, sdk_manager, utils};
 
 #[derive(Debug, Clone, Args)]
 pub struct ForkArgs {
@@ -18,28 +21,100 @@ pub enum ForkCommands {
         alias: String,
         /// Git repository URL (must end with .git)
         git_url: String,
+        /// Clone the fork's repository now so the first install is fast
+        #[arg(long)]
+        clone: bool,
+        /// Save the fork without checking that its repository is reachable
+        #[arg(long, conflicts_with = "clone")]
+        no_verify: bool,
     },
     /// Remove a Flutter fork alias
     Remove {
         /// Fork alias name to remove
         alias: String,
     },
+    /// Change the repository URL of a fork alias
+    Update {
+        /// Fork alias name to update
+        alias: String,
+        /// New Git repository URL (must end with .git)
+        git_url: String,
+        /// Save the URL without checking that the repository is reachable
+        #[arg(long)]
+        no_verify: bool,
+    },
+    /// Rename a fork alias
+    Rename {
+        /// Current fork alias name
+        old: String,
+        /// New fork alias name
+        new: String,
+        /// Replace an existing fork with the new name without asking
+        #[arg(short, long)]
+        force: bool,
+    },
     /// List all configured Flutter forks
     List,
+    /// Check that a fork's repository is reachable
+    Verify {
+        /// Fork alias name to verify
+        alias: String,
+    },
 }
 
 pub async fn run(args: ForkArgs) -> Result<()> {
     match args.command {
-        ForkCommands::Add { alias, git_url } => add_fork(&alias, &git_url).await,
+        ForkCommands::Add {
+            alias,
+            git_url,
+            clone,
+            no_verify,
+        } => add_fork(&alias, &git_url, clone, no_verify).await,
         ForkCommands::Remove { alias } => remove_fork(&alias).await,
+        ForkCommands::Update {
+            alias,
+            git_url,
+            no_verify,
+        } => update_fork(&alias, &git_url, no_verify).await,
+        ForkCommands::Rename { old, new, force } => rename_fork(&old, &new, force).await,
         ForkCommands::List => list_forks().await,
+        ForkCommands::Verify { alias } => verify_fork(&alias).await,
     }
 }
 
-async fn add_fork(alias: &str, git_url: &str) -> Result<()> {
+async fn add_fork(alias: &str, git_url: &str, clone: bool, no_verify: bool) -> Result<()> {
     info!("Adding fork: {} -> {}", alias, git_url);
 
-    // Validate git URL format
+    validate_git_url(git_url)?;
+
+    // Clone before saving, so an unreachable fork is never added
+    if clone {
+        if GlobalConfig::read().await?.get_fork_url(alias).is_some() {
+            anyhow::bail!("Fork '{}' already exists", alias);
+        }
+        clone_fork(alias, git_url).await?;
+    } else if !no_verify {
+        verify_remote(git_url).await?;
+    }
+
+    // Add the fork, re-reading the config under lock
+    let (name, url) = (alias.to_string(), git_url.to_string());
+    GlobalConfig::update(move |config| {
+        config.add_fork(name, url).context("Failed to add fork")
+    })
+    .await?;
+
+    println!("✓ Fork '{}' added successfully", alias);
+    println!("  Repository: {}", git_url);
+    println!("\nYou can now use:");
+    println!("  fvm-rs install {}/stable", alias);
+    println!("  fvm-rs install {}/3.24.0", alias);
+    println!("  fvm-rs use {}/stable", alias);
+
+    Ok(())
+}
+
+fn validate_git_url(git_url: &str) -> Result<()> {
     if !git_url.ends_with(".git") {
         anyhow::bail!(
             "Invalid Git URL: {}. URL must end with '.git'",
@@ -47,46 +122,204 @@ async fn add_fork(alias: &str, git_url: &str) -> Result<()> {
         );
     }
 
-    // Read global config
-    let mut config = GlobalConfig::read().await?;
+    Ok(())
+}
 
-    // Add the fork
-    config.add_fork(alias.to_string(), git_url.to_string())
-        .context("Failed to add fork")?;
+/// Check that a repository is reachable before saving its URL, catching typos early
+async fn verify_remote(git_url: &str) -> Result<()> {
+    println!("Checking {}...", git_url);
 
-    // Save updated config
-    config.save().await
-        .context("Failed to save global config")?;
+    let probe = sdk_manager::probe_remote(git_url).await.with_context(|| {
+        format!(
+            "{} is not a reachable Git repository. Check the URL, or pass --no-verify to save it anyway",
+            git_url
+        )
+    })?;
 
-    println!("✓ Fork '{}' added successfully", alias);
-    println!("  Repository: {}", git_url);
-    println!("\nYou can now use:");
-    println!("  fvm-rs install {}/stable", alias);
-    println!("  fvm-rs install {}/3.24.0", alias);
-    println!("  fvm-rs use {}/stable", alias);
+    if probe.channel_branches.is_empty() {
+        println!("  ⚠ No Flutter channel branches found (stable, beta, master)");
+    }
 
     Ok(())
 }
 
+async fn clone_fork(alias: &str, git_url: &str) -> Result<()> {
+    println!("Cloning {}...", git_url);
+
+    let clone_progress = progress::InstallProgress::new(false);
+    let options = sdk_manager::InstallOptions {
+        progress: Some(clone_progress.callback()),
+        ..Default::default()
+    };
+
+    let result = sdk_manager::prefetch_fork(alias, git_url, &options).await;
+    clone_progress.finish();
+
+    match result {
+        Ok(path) => {
+            println!("✓ Cloned fork '{}' to {}", alias, path.display());
+            Ok(())
+        }
+        Err(e) => {
+            println!("✗ Could not clone fork '{}'; it was not added", alias);
+            Err(e)
+        }
+    }
+}
+
 async fn remove_fork(alias: &str) -> Result<()> {
     info!("Removing fork: {}", alias);
 
-    // Read global config
-    let mut config = GlobalConfig::read().await?;
+    // Remove the fork, re-reading the config under lock
+    let name = alias.to_string();
+    GlobalConfig::update(move |config| {
+        config.remove_fork(&name).context("Failed to remove fork")
+    })
+    .await?;
 
-    // Remove the fork
-    config.remove_fork(alias)
-        .context("Failed to remove fork")?;
+    println!("✓ Fork '{}' removed successfully", alias);
 
-    // Save updated config
-    config.save().await
-        .context("Failed to save global config")?;
+    Ok(())
+}
 
-    println!("✓ Fork '{}' removed successfully", alias);
+async fn update_fork(alias: &str, git_url: &str, no_verify: bool) -> Result<()> {
+    info!("Updating fork: {} -> {}", alias, git_url);
+
+    validate_git_url(git_url)?;
+    if !no_verify {
+        verify_remote(git_url).await?;
+    }
+
+    // Update the fork, re-reading the config under lock
+    let (name, url) = (alias.to_string(), git_url.to_string());
+    let previous_url = GlobalConfig::update(move |config| {
+        config.update_fork(&name, url).context("Failed to update fork")
+    })
+    .await?;
+
+    println!("✓ Fork '{}' updated", alias);
+    println!("  Repository: {} → {}", previous_url, git_url);
+
+    // An existing clone keeps fetching from its origin remote
+    match sdk_manager::set_fork_remote_url(alias, git_url).await {
+        Ok(true) => println!("  Cloned repository now fetches from the new URL"),
+        Ok(false) => {}
+        Err(e) => println!("  ⚠ Could not update the cloned repository: {:#}", e),
+    }
+
+    Ok(())
+}
+
+async fn rename_fork(old: &str, new: &str, force: bool) -> Result<()> {
+    info!("Renaming fork: {} -> {}", old, new);
+
+    // Only used to ask before replacing; the rename re-checks both forks under lock
+    let config = GlobalConfig::read().await?;
+    if config.get_fork_url(old).is_none() {
+        anyhow::bail!("Fork '{}' not found. Run 'fvm-rs fork list' to see configured forks", old);
+    }
+
+    // Replacing another fork's definition needs confirmation
+    let overwrite = match config.get_fork_url(new) {
+        None => false,
+        Some(_) if force => true,
+        Some(url) => {
+            if !std::io::stdin().is_terminal() {
+                anyhow::bail!("Fork '{}' already exists ({}). Pass --force to replace it", new, url);
+            }
+            let proceed = Confirm::with_theme(&ColorfulTheme::default())
+                .with_prompt(format!("Fork '{}' already exists ({}). Replace it?", new, url))
+                .default(false)
+                .interact()
+                .context("Failed to get confirmation")?;
+            if !proceed {
+                println!("Operation cancelled");
+                return Ok(());
+            }
+            true
+        }
+    };
+
+    // Rename the fork, re-reading the config under lock
+    let (name, new_name) = (old.to_string(), new.to_string());
+    let url = GlobalConfig::update(move |config| {
+        let url = config
+            .get_fork_url(&name)
+            .with_context(|| format!("Fork '{}' not found", name))?;
+        config.rename_fork(&name, new_name, overwrite).context("Failed to rename fork")?;
+        Ok(url)
+    })
+    .await?;
+
+    println!("✓ Fork '{}' renamed to '{}'", old, new);
+
+    // Also covers leftovers of an earlier fork that used the new alias
+    replace_fork_repo(new, &url).await?;
+
+    // Versions are installed under their alias, so existing ones keep the old name
+    let installed = installed_fork_versions(old)?;
+    if !installed.is_empty() {
+        println!("  ⚠ Installed versions still use the old alias: {}", installed.join(", "));
+        println!("    Reinstall them as {}/<version> and remove the old ones", new);
+        return Ok(());
+    }
+
+    // With nothing installed from it, the clone can follow the alias
+    match sdk_manager::move_fork_repo(old, new).await {
+        Ok(true) => println!("  Moved the cloned repository to the new alias"),
+        // The new alias kept its own clone (now fetching from this URL), so ours is unused
+        Ok(false) => match sdk_manager::remove_fork_repo(old).await {
+            Ok(true) => println!("  Removed the old alias's cloned repository"),
+            Ok(false) => {}
+            Err(e) => println!("  ⚠ Could not remove the old alias's cloned repository: {:#}", e),
+        },
+        Err(e) => println!("  ⚠ Could not move the cloned repository: {:#}", e),
+    }
 
     Ok(())
 }
 
+/// Deal with the clone of a fork whose alias a rename takes over
+///
+/// Versions installed from it keep using the clone, so it is repointed at the
+/// renamed fork's URL; otherwise it is removed to make room for the new one.
+async fn replace_fork_repo(alias: &str, url: &str) -> Result<()> {
+    let installed = installed_fork_versions(alias)?;
+    if installed.is_empty() {
+        match sdk_manager::remove_fork_repo(alias).await {
+            Ok(true) => println!("  Removed the replaced fork's cloned repository"),
+            Ok(false) => {}
+            Err(e) => println!("  ⚠ Could not remove the replaced fork's cloned repository: {:#}", e),
+        }
+        return Ok(());
+    }
+
+    println!(
+        "  ⚠ These versions were installed from the replaced fork: {}",
+        installed.join(", ")
+    );
+    println!("    Remove and reinstall them to get the renamed fork's SDK");
+    match sdk_manager::set_fork_remote_url(alias, url).await {
+        Ok(true) => println!("  The replaced fork's cloned repository now fetches from {}", url),
+        Ok(false) => {}
+        Err(e) => println!("  ⚠ Could not update the replaced fork's cloned repository: {:#}", e),
+    }
+
+    Ok(())
+}
+
+/// Versions installed under a fork alias, as `alias/version`
+fn installed_fork_versions(alias: &str) -> Result<Vec<String>> {
+    Ok(std::fs::read_dir(utils::flutter_version_dir(alias)?)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| format!("{}/{}", alias, entry.file_name().to_string_lossy()))
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
 async fn list_forks() -> Result<()> {
     info!("Listing configured forks");
 
@@ -115,3 +348,33 @@ async fn list_forks() -> Result<()> {
 
     Ok(())
 }
+
+async fn verify_fork(alias: &str) -> Result<()> {
+    info!("Verifying fork: {}", alias);
+
+    let config = GlobalConfig::read().await?;
+    let url = config
+        .get_fork_url(alias)
+        .with_context(|| format!("Fork '{}' not found. Run 'fvm-rs fork list' to see configured forks", alias))?;
+
+    println!("Checking {}...", url);
+
+    let probe = match sdk_manager::probe_remote(&url).await {
+        Ok(probe) => probe,
+        Err(e) => {
+            println!("✗ Fork '{}' is not reachable", alias);
+            return Err(e);
+        }
+    };
+
+    println!("✓ Fork '{}' is reachable", alias);
+    println!("  Default branch:   {}", probe.default_branch.as_deref().unwrap_or("unknown"));
+
+    if probe.channel_branches.is_empty() {
+        println!("  Channels:         ⚠ None found (stable, beta, master)");
+    } else {
+        println!("  Channels:         {}", probe.channel_branches.join(", "));
+    }
+
+    Ok(())
+}
@@ -1,14 +1,15 @@
 use anyhow::{Context, Result};
 use clap::Args;
-use dialoguer::{theme::ColorfulTheme, Select};
+use clap_complete::engine::ArgValueCandidates;
 use std::process::Command;
 use tracing::info;
 
-use crate::{sdk_manager, utils};
+use crate::{completion, interactive, sdk_manager, utils};
 
 #[derive(Args, Debug, Clone)]
 pub struct GlobalArgs {
     /// Flutter version to set as global (e.g., "3.24.0", "stable")
+    #[arg(add = ArgValueCandidates::new(completion::installed_versions))]
     version: Option<String>,
 
     /// Remove the global Flutter SDK version setting
@@ -18,6 +19,10 @@ pub struct GlobalArgs {
     /// Skip Flutter SDK validation checks
     #[arg(short, long)]
     force: bool,
+
+    /// Fail if the version isn't installed instead of installing it
+    #[arg(long, conflicts_with = "unlink")]
+    no_install: bool,
 }
 
 pub async fn run(args: GlobalArgs) -> Result<()> {
@@ -29,19 +34,32 @@ pub async fn run(args: GlobalArgs) -> Result<()> {
         v
     } else {
         // Interactive mode: show menu of installed versions
-        select_version_interactively().await?
+        interactive::pick_installed_version("Select a Flutter version to set as global").await?
     };
 
-    set_global(&version, args.force).await
+    let resolved = sdk_manager::resolve_version(&version).await?;
+    if resolved != version {
+        println!("Resolved {} to version {}", version, resolved);
+    }
+
+    set_global(&resolved, args.force, args.no_install).await
 }
 
-async fn set_global(version: &str, force: bool) -> Result<()> {
+async fn set_global(version: &str, force: bool, no_install: bool) -> Result<()> {
     info!("Setting global Flutter version to: {}", version);
 
     // Attempt to install the version if not already installed
     // (This mirrors FVM's behavior)
     let flutter_version_dir = utils::flutter_version_dir(version)?;
     if !flutter_version_dir.exists() {
+        if no_install {
+            anyhow::bail!(
+                "Flutter version {} is not installed. Run 'fvm-rs install {}' first, or drop --no-install",
+                version,
+                version
+            );
+        }
+
         println!("Flutter version {} is not installed.", version);
         println!("Installing...");
 
@@ -78,53 +96,30 @@ async fn unlink_global() -> Result<()> {
     Ok(())
 }
 
-async fn select_version_interactively() -> Result<String> {
-    info!("Selecting Flutter version interactively");
-
-    // Get list of installed versions
-    let versions = sdk_manager::list_installed_versions().await?;
-
-    if versions.is_empty() {
-        anyhow::bail!(
-            "No Flutter versions installed.\nRun 'fvm-rs install <version>' to install one first."
-        );
-    }
-
-    // Show selection menu
-    let selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select a Flutter version to set as global")
-        .items(&versions)
-        .default(0)
-        .interact()
-        .context("Failed to get user selection")?;
-
-    Ok(versions[selection].clone())
-}
-
 async fn check_path_configuration() -> Result<()> {
     // Check where the `flutter` command currently points
     let which_output = Command::new("which")
         .arg("flutter")
         .output();
 
-    if let Ok(output) = which_output {
-        if output.status.success() {
-            let current_path = String::from_utf8_lossy(&output.stdout).trim().to_string();
-
-            // Expected path for global version
-            let global_link = utils::get_global_link_path()?;
-            let expected_bin = global_link.join("bin");
-            let expected_flutter = expected_bin.join("flutter");
-
-            // Check if current path matches expected
-            if !current_path.starts_with(&expected_bin.to_string_lossy().to_string()) {
-                println!("\n⚠️  Warning: Your configured \"flutter\" path may be incorrect");
-                println!("   CURRENT:   {}", current_path);
-                println!("   EXPECTED:  {}", expected_flutter.display());
-                println!("\n   To fix this, add the following to your PATH:");
-                println!("   export PATH=\"{}:$PATH\"", expected_bin.display());
-                println!("\n   Or add it to your shell profile (~/.bashrc, ~/.zshrc, etc.)");
-            }
+    if let Ok(output) = which_output
+        && output.status.success()
+    {
+        let current_path = String::from_utf8_lossy(&output.stdout).trim().to_string();
+
+        // Expected path for global version
+        let global_link = utils::get_global_link_path()?;
+        let expected_bin = global_link.join("bin");
+        let expected_flutter = expected_bin.join("flutter");
+
+        // Check if current path matches expected
+        if !current_path.starts_with(&expected_bin.to_string_lossy().to_string()) {
+            println!("\n⚠️  Warning: Your configured \"flutter\" path may be incorrect");
+            println!("   CURRENT:   {}", current_path);
+            println!("   EXPECTED:  {}", expected_flutter.display());
+            println!("\n   To fix this, add the following to your PATH:");
+            println!("   export PATH=\"{}:$PATH\"", expected_bin.display());
+            println!("\n   Or add it to your shell profile (~/.bashrc, ~/.zshrc, etc.)");
         }
     }
 
@@ -0,0 +1,28 @@
+use anyhow::Result;
+use clap::Args;
+use std::path::PathBuf;
+use tracing::info;
+
+use crate::archive_manager;
+
+#[derive(Debug, Clone, Args)]
+pub struct ImportArgs {
+    /// Archive created by 'fvm-rs export'
+    pub file: PathBuf,
+}
+
+pub async fn run(args: ImportArgs) -> Result<()> {
+    info!("Importing Flutter from {}", args.file.display());
+    println!("Importing {}...", args.file.display());
+
+    let summary = archive_manager::import_version(&args.file).await?;
+
+    println!("✓ Imported Flutter {}", summary.version);
+    if summary.engine_reused {
+        println!("  Engine: {} (already cached)", summary.engine_hash);
+    } else {
+        println!("  Engine: {}", summary.engine_hash);
+    }
+
+    Ok(())
+}
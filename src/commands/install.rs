@@ -1,35 +1,156 @@
 use anyhow::{Context, Result};
+use chrono::NaiveDate;
 use clap::Args;
-use dialoguer::{theme::ColorfulTheme, Select};
+use serde::Serialize;
+use serde_json::json;
 use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
 use tracing::info;
 
-use crate::{config_manager, sdk_manager};
+use crate::{config_manager, interactive, progress, sdk_manager, utils};
 
 #[derive(Debug, Clone, Args)]
 pub struct InstallArgs {
-    /// Flutter version to install (e.g., "3.24.0", "stable")
+    /// Flutter version to install (e.g., "3.24.0", "stable", "3.10.0@beta")
     version: Option<String>,
 
+    /// Install the release that was current on this date (YYYY-MM-DD)
+    #[arg(long, value_name = "DATE", conflicts_with = "version")]
+    as_of: Option<NaiveDate>,
+
+    /// Channel to resolve --as-of against
+    #[arg(
+        long,
+        requires = "as_of",
+        value_parser = clap::builder::PossibleValuesParser::new(["stable", "beta", "dev"]),
+        default_value = "stable"
+    )]
+    channel: String,
+
     /// Skip downloading SDK dependencies (engine) after install
     #[arg(long)]
     skip_setup: bool,
+
+    /// Run `flutter --version` after installing to confirm the SDK works
+    #[arg(long)]
+    verify_after: bool,
+
+    /// Skip downloading and linking the engine (dart-sdk symlink and stamp files)
+    ///
+    /// For engine developers who populate bin/cache themselves. The installed SDK
+    /// won't run plain `flutter` commands until an engine is present.
+    #[arg(long, conflicts_with = "verify_after")]
+    no_engine_link: bool,
+
+    /// Run `flutter precache` after installing so the first build doesn't download artifacts
+    ///
+    /// Optionally limited to platforms, e.g. --precache=android,ios. Downloads a lot of data.
+    #[arg(
+        long,
+        value_name = "PLATFORMS",
+        num_args = 0..=1,
+        require_equals = true,
+        value_delimiter = ',',
+        value_parser = clap::builder::PossibleValuesParser::new(sdk_manager::PRECACHE_PLATFORMS),
+        conflicts_with_all = ["no_engine_link", "json", "from_manifest"]
+    )]
+    precache: Option<Vec<String>>,
+
+    /// Don't verify the size and SHA-256 checksum of the downloaded engine archive
+    ///
+    /// For air-gapped mirrors that serve archives without checksums or with stale ones.
+    #[arg(long)]
+    no_verify: bool,
+
+    /// Fetch the Flutter repository even if the release tag is already downloaded
+    #[arg(long)]
+    refresh: bool,
+
+    /// Print progress as a line every 10% instead of drawing progress bars
+    ///
+    /// This is the default when stdout is not a terminal.
+    #[arg(long, short = 'q', conflicts_with = "json")]
+    quiet: bool,
+
+    /// Emit newline-delimited JSON progress events and a final result on stdout
+    #[arg(long, conflicts_with = "verify_after")]
+    json: bool,
+
+    /// Abort unless the version's tag points to this commit SHA (full or 7+ characters)
+    #[arg(long, value_name = "SHA")]
+    expect_commit: Option<String>,
+
+    /// Pin master to this commit SHA; installs as "master@<short-sha>"
+    #[arg(long = "ref", value_name = "SHA", conflicts_with_all = ["as_of", "expect_commit"])]
+    git_ref: Option<String>,
+
+    /// Install every version in a JSON manifest and set its global version
+    ///
+    /// The manifest looks like {"versions": ["3.24.0", "stable"], "global": "3.24.0"}.
+    /// Installed versions are skipped, so re-running resumes an interrupted setup.
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["version", "as_of", "git_ref", "expect_commit", "json", "verify_after"]
+    )]
+    from_manifest: Option<PathBuf>,
+
+    /// Maximum number of versions to install at once with --from-manifest
+    #[arg(long, default_value_t = 2, requires = "from_manifest", value_parser = clap::value_parser!(u16).range(1..))]
+    jobs: u16,
 }
 
 pub async fn run(args: InstallArgs) -> Result<()> {
+    if let Some(manifest) = &args.from_manifest {
+        let options = sdk_manager::InstallOptions {
+            skip_engine_link: args.no_engine_link,
+            skip_verify: args.no_verify,
+            refresh: args.refresh,
+            ..Default::default()
+        };
+        return install_from_manifest(manifest, args.jobs.into(), options).await;
+    }
+
     // Get version from args, project config, or interactive selector
-    let version = if let Some(v) = args.version {
-        v
+    let version = if let Some(sha) = &args.git_ref {
+        if args.version.as_deref().is_some_and(|v| v != "master") {
+            anyhow::bail!("--ref can only be used with the master channel: fvm-rs install master --ref <sha>");
+        }
+        sdk_manager::pinned_master_version(sha)?
+    } else if let Some(v) = args.version {
+        let resolved = sdk_manager::resolve_version(&v).await?;
+        if resolved != v && !args.json {
+            println!("Resolved {} to version {}", v, resolved);
+        }
+        resolved
+    } else if let Some(date) = args.as_of {
+        let release = sdk_manager::resolve_release_as_of(date, &args.channel).await?;
+        if !args.json {
+            println!(
+                "Resolved {} as of {} to version {} (released {})",
+                args.channel,
+                date,
+                release.version,
+                release.release_date.format("%Y-%m-%d")
+            );
+        }
+        release.version
     } else {
         // Try to read project config first
         let current_dir = env::current_dir().context("Failed to get current directory")?;
         if let Some(config) = config_manager::read_project_config(&current_dir).await? {
-            println!("Installing Flutter SDK from project config...");
+            if !args.json {
+                println!("Installing Flutter SDK from project config...");
+            }
             info!("Using version from project config: {}", config.flutter);
             config.flutter
+        } else if args.json {
+            anyhow::bail!("A version is required when using --json outside a configured project");
         } else {
             // Fall back to interactive selector
-            select_version_interactively().await?
+            interactive::pick_release("Select a Flutter version to install").await?
         }
     };
 
@@ -41,61 +162,223 @@ pub async fn run(args: InstallArgs) -> Result<()> {
         tracing::warn!("--skip-setup flag is not yet fully implemented");
     }
 
+    let options = sdk_manager::InstallOptions {
+        skip_engine_link: args.no_engine_link,
+        expected_commit: args.expect_commit,
+        skip_verify: args.no_verify,
+        refresh: args.refresh,
+        ..Default::default()
+    };
+
+    if args.json {
+        return install_json(&version, options).await;
+    }
+
+    // Network mounts make git operations slow and symlinks unreliable
+    if let Some(fs_type) = utils::network_filesystem_type(&utils::cache_root_dir()?) {
+        println!("⚠ Warning: The FVM cache is on a network filesystem ({})", fs_type);
+        println!("  Installation may be slow and engine symlinks may fail.");
+        println!("  Consider a local cache path: fvm-rs config --cache-path <path>");
+    }
+
     println!("Installing Flutter SDK {}...", version);
-    sdk_manager::ensure_installed(&version).await?;
+    let install_progress = progress::InstallProgress::new(args.quiet);
+    let options = sdk_manager::InstallOptions {
+        progress: Some(install_progress.callback()),
+        ..options
+    };
+    let result = sdk_manager::ensure_installed_with(&version, &options).await;
+    install_progress.finish();
+    result?;
     println!("✓ Flutter SDK {} has been installed successfully", version);
+    if let Some(commit) = sdk_manager::installed_commit(&version)? {
+        println!("  Commit: {}", commit);
+    }
     info!("Successfully installed Flutter SDK {}", version);
-    return Ok(());
+
+    if args.no_engine_link {
+        println!("  Note: No engine was linked into bin/cache.");
+        println!("  `flutter` won't run until an engine is present (e.g. via --local-engine).");
+    }
+
+    if args.verify_after {
+        verify_installation(&version).await?;
+    }
+
+    if let Some(platforms) = &args.precache {
+        precache(&version, platforms).await?;
+    }
+
+    Ok(())
+}
+
+/// Download platform artifacts up front with `flutter precache`
+async fn precache(version: &str, platforms: &[String]) -> Result<()> {
+    if platforms.is_empty() {
+        println!("Precaching Flutter {} artifacts...", version);
+    } else {
+        println!("Precaching Flutter {} artifacts for {}...", version, platforms.join(", "));
+    }
+    info!("Running flutter precache for {}", version);
+
+    let exit_code = sdk_manager::precache(version, platforms).await?;
+    if exit_code != 0 {
+        anyhow::bail!("`flutter precache` failed with exit code {}", exit_code);
+    }
+
+    println!("✓ Artifacts precached");
+    Ok(())
+}
+
+/// Install all versions listed in a manifest, then set its global version
+///
+/// Already installed versions are skipped. Failures don't stop the other
+/// installs; they are reported in the summary and fail the command.
+async fn install_from_manifest(path: &Path, jobs: usize, options: sdk_manager::InstallOptions) -> Result<()> {
+    let manifest = config_manager::read_versions_manifest(path).await?;
+    let versions = manifest.versions_to_install();
+    info!("Installing {} version(s) from manifest {}", versions.len(), path.display());
+
+    let mut skipped = vec![];
+    let mut pending = vec![];
+    for version in versions {
+        if sdk_manager::verify_installed(&version)? {
+            skipped.push(version);
+        } else {
+            pending.push(version);
+        }
+    }
+
+    for version in &skipped {
+        println!("✓ Flutter SDK {} is already installed", version);
+    }
+    if !pending.is_empty() {
+        if let Some(fs_type) = utils::network_filesystem_type(&utils::cache_root_dir()?) {
+            println!("⚠ Warning: The FVM cache is on a network filesystem ({})", fs_type);
+        }
+        println!("Installing {} version(s), up to {} at a time...", pending.len(), jobs);
+    }
+
+    let semaphore = Arc::new(Semaphore::new(jobs));
+    let mut handles = vec![];
+    for version in pending {
+        let semaphore = semaphore.clone();
+        let options = options.clone();
+        handles.push(tokio::spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            println!("Installing Flutter SDK {}...", version);
+            let result = sdk_manager::ensure_installed_with(&version, &options).await;
+            match &result {
+                Ok(()) => println!("✓ Flutter SDK {} has been installed successfully", version),
+                Err(e) => eprintln!("✗ Failed to install Flutter SDK {}: {:#}", version, e),
+            }
+            (version, result.is_ok())
+        }));
+    }
+
+    let mut installed = vec![];
+    let mut failed = vec![];
+    for handle in handles {
+        let (version, ok) = handle.await?;
+        if ok {
+            installed.push(version);
+        } else {
+            failed.push(version);
+        }
+    }
+
+    if let Some(global) = &manifest.global {
+        if failed.contains(global) {
+            eprintln!("✗ Not setting the global version: {} failed to install", global);
+        } else {
+            sdk_manager::set_global_version(global).await?;
+            println!("✓ Global version set to {}", global);
+        }
+    }
+
+    println!();
+    println!(
+        "Summary: {} installed, {} already installed, {} failed",
+        installed.len(),
+        skipped.len(),
+        failed.len()
+    );
+
+    if !failed.is_empty() {
+        println!("  Re-run the same command to retry: installed versions are skipped");
+        anyhow::bail!("Failed to install: {}", failed.join(", "));
+    }
+
+    Ok(())
 }
 
-async fn select_version_interactively() -> Result<String> {
-    info!("Selecting Flutter version interactively");
-    println!("Fetching available Flutter releases...");
-
-    // Fetch available releases
-    let releases = sdk_manager::list_available_versions().await
-        .context("Failed to fetch available Flutter releases")?;
-
-    // Create list of options: channels first, then recent releases
-    let mut options = vec![
-        "stable (latest stable release)".to_string(),
-        "beta (latest beta release)".to_string(),
-        "dev (latest dev release)".to_string(),
-        "master (bleeding edge)".to_string(),
-    ];
-
-    // Add separator
-    options.push("──────────────────────────────".to_string());
-
-    // Add recent stable releases (limit to 10)
-    for release in releases.releases.iter()
-        .filter(|r| r.channel == "stable")
-        .take(10)
-    {
-        options.push(format!("{} (stable)", release.version));
-    }
-
-    // Show selection menu
-    let selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select a Flutter version to install")
-        .items(&options)
-        .default(0)
-        .interact()
-        .context("Failed to get user selection")?;
-
-    // Extract version from selection
-    let selected = &options[selection];
-
-    if selection < 4 {
-        // It's a channel
-        let channel = selected.split_whitespace().next().unwrap();
-        Ok(channel.to_string())
-    } else if selection == 4 {
-        // It's the separator, shouldn't happen
-        anyhow::bail!("Invalid selection")
+/// Install with newline-delimited JSON events on stdout instead of human output
+///
+/// Every line is a JSON object with an "event" field. The last line is either
+/// a "done" or an "error" event, so front-ends can drive their own progress UI.
+async fn install_json(version: &str, options: sdk_manager::InstallOptions) -> Result<()> {
+    if let Some(fs_type) = utils::network_filesystem_type(&utils::cache_root_dir()?) {
+        print_json_event(&json!({
+            "event": "warning",
+            "message": format!("The FVM cache is on a network filesystem ({})", fs_type),
+        }));
+    }
+
+    let options = sdk_manager::InstallOptions {
+        progress: Some(Arc::new(|event| print_json_event(&event))),
+        ..options
+    };
+
+    match sdk_manager::ensure_installed_with(version, &options).await {
+        Ok(()) => {
+            let path = utils::flutter_version_dir(version)?;
+            print_json_event(&json!({
+                "event": "done",
+                "version": version,
+                "path": path.to_string_lossy(),
+                "commit": sdk_manager::installed_commit(version)?,
+            }));
+            Ok(())
+        }
+        Err(e) => {
+            print_json_event(&json!({
+                "event": "error",
+                "version": version,
+                "message": format!("{:#}", e),
+            }));
+            Err(e)
+        }
+    }
+}
+
+fn print_json_event<T: Serialize>(event: &T) {
+    if let Ok(line) = serde_json::to_string(event) {
+        println!("{}", line);
+    }
+}
+
+async fn verify_installation(version: &str) -> Result<()> {
+    info!("Verifying Flutter SDK {}", version);
+    println!("Verifying Flutter SDK {}...", version);
+
+    let result = sdk_manager::smoke_test(version).await
+        .context("Failed to run Flutter smoke test")?;
+
+    if result.passed() {
+        println!("✓ Flutter SDK {} runs correctly", version);
+        return Ok(());
+    }
+
+    if result.exit_code != 0 {
+        eprintln!("✗ `flutter --version` exited with code {}", result.exit_code);
     } else {
-        // It's a version number
-        let version = selected.split_whitespace().next().unwrap();
-        Ok(version.to_string())
+        eprintln!(
+            "✗ Expected Flutter {}, but the SDK reported {}",
+            result.expected_version.as_deref().unwrap_or("unknown"),
+            result.reported_version.as_deref().unwrap_or("no version")
+        );
     }
+    eprintln!("  Try reinstalling: fvm-rs remove {} && fvm-rs install {}", version, version);
+
+    anyhow::bail!("Verification of Flutter SDK {} failed", version)
 }
@@ -1,24 +1,196 @@
-use crate::sdk_manager;
+use crate::{config_manager, sdk_manager, utils};
 use anyhow::Result;
-use tracing::info;
+use chrono::{DateTime, Utc};
+use clap::Args;
+use serde::Serialize;
+use std::collections::HashSet;
+use tabled::{Table, Tabled, settings::Style};
+use tokio::task;
+use tracing::{debug, info};
 
-pub async fn run() -> Result<()> {
+#[derive(Debug, Clone, Args)]
+pub struct ListArgs {
+    /// Output format; json includes engine and channel metadata
+    #[arg(
+        long,
+        value_parser = clap::builder::PossibleValuesParser::new(["table", "json"]),
+        default_value = "table"
+    )]
+    format: String,
+
+    /// Shorthand for --format json
+    #[arg(long, conflicts_with = "format")]
+    json: bool,
+
+    /// Include each version's size in bytes in the JSON output (slower; needs --format json)
+    #[arg(long)]
+    size: bool,
+
+    /// Show each version's channel, install date and engine hash
+    #[arg(short, long, conflicts_with = "json")]
+    long: bool,
+
+    /// List the newest release first (channels stay at the end)
+    #[arg(short, long)]
+    reverse: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ListedVersion {
+    name: String,
+    path: String,
+    engine_hash: Option<String>,
+    channel: Option<String>,
+    installed_at: Option<DateTime<Utc>>,
+    repo_url: Option<String>,
+    /// Includes the linked engine, which may be shared with other versions
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size_bytes: Option<u64>,
+    is_global: bool,
+    is_project_pinned: bool,
+    in_use: bool,
+}
+
+pub async fn run(args: ListArgs) -> Result<()> {
     info!("Listing installed Flutter SDK versions");
-    let versions = sdk_manager::list_installed_versions().await?;
+    let json = args.json || args.format == "json";
+    if args.size && !json {
+        anyhow::bail!("--size only applies to JSON output. Add --format json");
+    }
+    if args.long && json {
+        anyhow::bail!("--long can't be combined with --format json");
+    }
+
+    let mut versions = sdk_manager::list_installed_versions().await?;
+    if args.reverse {
+        let releases = versions.partition_point(|version| utils::is_version_number(version));
+        versions[..releases].reverse();
+    }
     let global_version = sdk_manager::get_global_version().await?;
 
     info!("Found {} installed version(s)", versions.len());
 
+    // Versions with a running flutter/dart process shouldn't be removed
+    let in_use = utils::versions_in_use().unwrap_or_default();
+
+    if json {
+        return print_json(versions, global_version, &in_use, args.size).await;
+    }
+    if args.long {
+        return print_long(versions, global_version, &in_use).await;
+    }
+
     for version in versions {
+        let suffix = if in_use.contains(&version) { "  (in use)" } else { "" };
+
         // Add indicator for global version
-        if let Some(ref global) = global_version {
-            if global == &version {
-                println!("\u{25cf} {}", version);
-                continue;
-            }
+        if let Some(ref global) = global_version
+            && global == &version
+        {
+            println!("\u{25cf} {}{}", version, suffix);
+            continue;
         }
-        println!("  {}", version);
+        println!("  {}{}", version, suffix);
+    }
+
+    Ok(())
+}
+
+async fn print_json(
+    versions: Vec<String>,
+    global_version: Option<String>,
+    in_use: &HashSet<String>,
+    with_size: bool,
+) -> Result<()> {
+    let project_version = config_manager::get_project_flutter_version().await?;
+    let mut listed = Vec::new();
+    let mut releases_unavailable = false;
+
+    for version in versions {
+        let metadata = sdk_manager::install_metadata(&version).await?;
+
+        // Channel names are their own channel; releases are fetched once per run
+        let channel = match version.rsplit('/').next() {
+            _ if metadata.channel.is_some() => metadata.channel,
+            Some(name) if config_manager::is_channel(name) => Some(name.to_string()),
+            _ if releases_unavailable => None,
+            _ => {
+                let channel = sdk_manager::get_channel_for_version(&version).await.ok();
+                releases_unavailable = channel.is_none();
+                channel
+            }
+        };
+
+        let path = utils::flutter_version_dir(&version)?;
+        let size_bytes = if with_size {
+            let path = path.clone();
+            match task::spawn_blocking(move || utils::dir_size_following_links(&path)).await? {
+                Ok(bytes) => Some(bytes),
+                Err(e) => {
+                    debug!("Failed to calculate size of {}: {}", version, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        listed.push(ListedVersion {
+            path: path.to_string_lossy().to_string(),
+            engine_hash: metadata.engine_hash,
+            channel,
+            installed_at: metadata.installed_at,
+            repo_url: metadata.repo_url,
+            size_bytes,
+            is_global: global_version.as_deref() == Some(version.as_str()),
+            is_project_pinned: project_version.as_deref() == Some(version.as_str()),
+            in_use: in_use.contains(&version),
+            name: version,
+        });
+    }
+
+    println!("{}", serde_json::to_string_pretty(&listed)?);
+    Ok(())
+}
+
+#[derive(Tabled)]
+#[tabled(rename_all = "Upper Title Case")]
+struct VersionRow {
+    version: String,
+    channel: String,
+    installed: String,
+    engine: String,
+}
+
+async fn print_long(
+    versions: Vec<String>,
+    global_version: Option<String>,
+    in_use: &HashSet<String>,
+) -> Result<()> {
+    let mut rows = Vec::new();
+
+    for version in versions {
+        let metadata = sdk_manager::install_metadata(&version).await?;
+
+        let marker = if global_version.as_deref() == Some(version.as_str()) { "\u{25cf} " } else { "" };
+        let suffix = if in_use.contains(&version) { " (in use)" } else { "" };
+        rows.push(VersionRow {
+            version: format!("{}{}{}", marker, version, suffix),
+            channel: metadata.channel.unwrap_or_else(|| "-".to_string()),
+            installed: metadata
+                .installed_at
+                .map(|date| date.format("%b %e, %Y").to_string())
+                .unwrap_or_else(|| "-".to_string()),
+            engine: metadata
+                .engine_hash
+                .map(|hash| hash.chars().take(10).collect())
+                .unwrap_or_else(|| "(none)".to_string()),
+        });
     }
 
-    return Ok(());
+    let mut table = Table::new(rows);
+    table.with(Style::modern());
+    println!("{}", table);
+    Ok(())
 }
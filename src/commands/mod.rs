@@ -1,16 +1,24 @@
 pub mod api;
+pub mod cache;
+pub mod completions;
 pub mod config;
 pub mod dart;
 pub mod destroy;
 pub mod doctor;
+pub mod env;
 pub mod exec;
+pub mod export;
 pub mod flavor;
 pub mod flutter;
 pub mod fork;
 pub mod global;
+pub mod import;
 pub mod install;
 pub mod list;
 pub mod releases;
 pub mod remove;
+pub mod self_update;
 pub mod spawn;
+pub mod upgrade;
 pub mod r#use;
+pub mod which;
@@ -1,28 +1,79 @@
 use std::collections::HashSet;
 
-use crate::sdk_manager;
+use crate::{config_manager, sdk_manager};
 use anyhow::Result;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, SecondsFormat, Utc};
 use clap::Args;
 use colored::Colorize;
-use tabled::{Table, Tabled, settings::Style};
+use tabled::{Table, Tabled, builder::Builder, settings::Style};
 use tracing::info;
 
+/// Release date shown for the master branch, which has no releases
+const ROLLING: &str = "rolling";
+
+/// Dart SDK shown when the feed doesn't say which one a release bundles
+const NO_DART_SDK: &str = "-";
+
 #[derive(Debug, Clone, Args)]
 pub struct ReleasesArgs {
     #[arg(
         long,
-        value_parser = clap::builder::PossibleValuesParser::new(["stable", "beta", "dev", "all"]),
+        value_parser = clap::builder::PossibleValuesParser::new(["stable", "beta", "dev", "master", "all"]),
         default_value = "stable"
     )]
     pub channel: String,
+
+    /// Output format
+    #[arg(
+        long,
+        value_parser = clap::builder::PossibleValuesParser::new(["table", "csv"]),
+        default_value = "table"
+    )]
+    pub format: String,
+
+    /// Print the releases and current channel versions as JSON
+    #[arg(long, conflicts_with = "format")]
+    pub json: bool,
+
+    /// Show only the N most recent releases
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+
+    /// Show only the current beta release, the likely next stable
+    #[arg(long, conflicts_with_all = ["channel", "format", "json", "limit"])]
+    pub next_stable: bool,
+
+    /// Compare two releases side by side (e.g. --compare 3.22.0 3.24.0)
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["V1", "V2"],
+        conflicts_with_all = ["channel", "format", "json", "limit", "next_stable"]
+    )]
+    pub compare: Option<Vec<String>>,
+
+    /// Fetch the releases feed even if the cached copy is still fresh
+    #[arg(long, conflicts_with = "offline")]
+    pub refresh: bool,
+
+    /// Use the cached releases feed without going online, however old it is
+    #[arg(long)]
+    pub offline: bool,
 }
 
 pub async fn run(args: ReleasesArgs) -> Result<()> {
     info!("Fetching available Flutter releases for channel: {}", args.channel);
 
+    let source = if args.refresh {
+        sdk_manager::ReleasesSource::Refresh
+    } else if args.offline {
+        sdk_manager::ReleasesSource::Offline
+    } else {
+        sdk_manager::ReleasesSource::Cached
+    };
+
     let (versions_result, installed_versions_result) = tokio::join!(
-        sdk_manager::list_available_versions(),
+        sdk_manager::list_available_versions_from(source),
         sdk_manager::list_installed_versions()
     );
 
@@ -31,37 +82,96 @@ pub async fn run(args: ReleasesArgs) -> Result<()> {
 
     info!("Retrieved {} releases, {} installed locally", versions.releases.len(), installed_versions.len());
 
-    let releases_rows: Vec<ReleaseRow> = versions
-        .releases
+    if args.next_stable {
+        print_next_stable(&versions.current_releases, &installed_versions);
+        return Ok(());
+    }
+
+    if let Some(compare) = &args.compare {
+        return print_comparison(&versions.releases, &compare[0], &compare[1], &installed_versions).await;
+    }
+
+    let channel = (args.channel != "all").then_some(args.channel.as_str());
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&versions.to_json(channel, args.limit))?);
+        return Ok(());
+    }
+
+    // The feed is newest first; the table and CSV list oldest first
+    let releases: Vec<&sdk_manager::FlutterRelease> =
+        versions.filtered(channel, args.limit).into_iter().rev().collect();
+
+    // master is a rolling branch without entries in the releases feed
+    let master_row = if args.channel == "master" {
+        let tip = sdk_manager::master_tip().await?;
+        if tip.from_cache {
+            eprintln!("⚠ Flutter repository unreachable, showing the last fetched master");
+        }
+        Some(sdk_manager::pinned_master_version(&tip.commit)?)
+    } else {
+        None
+    };
+
+    if args.format == "csv" {
+        return print_csv(
+            &releases,
+            &installed_versions,
+            master_row.as_deref(),
+        );
+    }
+
+    // Annotate the version the current project is pinned to, if any
+    let project_version = config_manager::get_project_flutter_version().await?;
+    let project_marker = |version: &str| {
+        if project_version.as_deref() == Some(version) {
+            " ← current project".yellow()
+        } else {
+            "".normal()
+        }
+    };
+
+    let mut releases_rows: Vec<ReleaseRow> = releases
         .iter()
-        .rev()
-        .filter_map(|release| {
-            if args.channel != "all" && args.channel != release.channel {
-                None
-            } else {
-                Some(ReleaseRow {
-                    version: release.version.clone(),
-                    release_date: release.release_date,
-                    channel: format!(
-                        "{}{}",
-                        release.channel,
-                        if installed_versions.contains(&release.version) {
-                            " ✓".green()
-                        } else {
-                            "".normal()
-                        }
-                    ),
-                })
-            }
+        .map(|release| ReleaseRow {
+            version: release.version.clone(),
+            release_date: format_date(&release.release_date),
+            dart_sdk: format_dart_sdk(release),
+            channel: format!(
+                "{}{}{}",
+                release.channel,
+                if installed_versions.contains(&release.version) {
+                    " ✓".green()
+                } else {
+                    "".normal()
+                },
+                project_marker(&release.version)
+            ),
         })
         .collect();
 
+    if let Some(master) = &master_row {
+        releases_rows.push(ReleaseRow {
+            version: master.clone(),
+            release_date: ROLLING.to_string(),
+            dart_sdk: NO_DART_SDK.to_string(),
+            channel: format!(
+                "master{}{}",
+                if installed_versions.contains("master") {
+                    " ✓".green()
+                } else {
+                    "".normal()
+                },
+                project_marker("master")
+            ),
+        });
+    }
+
     let mut releases_table = Table::new(releases_rows);
     releases_table.with(Style::modern());
 
-    println!("{}", releases_table.to_string());
+    println!("{}", releases_table);
 
-    let channels_rows: Vec<ChannelRow> = vec![
+    let mut channels_rows: Vec<ChannelRow> = [
         versions.current_releases.stable,
         versions.current_releases.beta,
     ]
@@ -71,30 +181,150 @@ pub async fn run(args: ReleasesArgs) -> Result<()> {
             None
         } else {
             Some(ChannelRow {
-                channel: release.channel.clone(),
+                // Projects pinned to a channel are matched on the channel name
+                channel: format!("{}{}", release.channel, project_marker(&release.channel)),
                 version: release.version.clone(),
-                release_date: release.release_date,
+                release_date: format_date(&release.release_date),
+                dart_sdk: format_dart_sdk(release),
             })
         }
     })
     .collect();
 
+    if let Some(master) = master_row {
+        channels_rows.push(ChannelRow {
+            channel: format!("master{}", project_marker("master")),
+            version: master,
+            release_date: ROLLING.to_string(),
+            dart_sdk: NO_DART_SDK.to_string(),
+        });
+    }
+
     let mut channels_table = Table::new(channels_rows);
     channels_table.with(Style::modern());
 
     println!();
     println!("Latest releases:");
-    println!("{}", channels_table.to_string());
+    println!("{}", channels_table);
+
+    Ok(())
+}
+
+/// Show the beta tip, which usually becomes the next stable release
+fn print_next_stable(current: &sdk_manager::CurrentReleases, installed_versions: &HashSet<String>) {
+    let beta = &current.beta;
+    let stable = &current.stable;
+
+    println!("Likely next stable: {}", beta.version.bold());
+    println!("  On beta since:    {}", format_date(&beta.release_date));
+    println!("  Current stable:   {} ({})", stable.version, format_date(&stable.release_date));
 
-    return Ok(());
+    if installed_versions.contains(&beta.version) {
+        println!("  {}", "✓ Installed".green());
+    } else {
+        println!("  Try it with: fvm-rs install {}", beta.version);
+    }
+}
+
+/// Print two releases side by side, highlighting the fields that differ
+async fn print_comparison(
+    releases: &[sdk_manager::FlutterRelease],
+    first: &str,
+    second: &str,
+    installed_versions: &HashSet<String>,
+) -> Result<()> {
+    let find = |version: &str| {
+        releases.iter().find(|r| r.version == version).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Release {} not found. Run 'fvm-rs releases --channel all' to list available versions",
+                version
+            )
+        })
+    };
+    let (first, second) = (find(first)?, find(second)?);
+
+    // Engine hashes aren't in the releases feed
+    let (first_engine, second_engine) = tokio::join!(
+        sdk_manager::release_engine_hash(&first.version),
+        sdk_manager::release_engine_hash(&second.version)
+    );
+    let engine = |hash: Result<String>| hash.unwrap_or_else(|e| format!("unknown ({})", e));
+
+    let installed = |release: &sdk_manager::FlutterRelease| {
+        if installed_versions.contains(&release.version) { "✓ Yes" } else { "No" }.to_string()
+    };
+
+    let rows = [
+        ("Channel", first.channel.clone(), second.channel.clone()),
+        ("Release Date", format_date(&first.release_date), format_date(&second.release_date)),
+        (
+            "Dart SDK",
+            first.dart_sdk_version.clone().unwrap_or_else(|| "unknown".to_string()),
+            second.dart_sdk_version.clone().unwrap_or_else(|| "unknown".to_string()),
+        ),
+        ("Commit", first.hash.clone(), second.hash.clone()),
+        ("Engine Hash", engine(first_engine), engine(second_engine)),
+        ("Installed", installed(first), installed(second)),
+    ];
+
+    let mut builder = Builder::default();
+    builder.push_record(["", first.version.as_str(), second.version.as_str()]);
+    for (field, first_value, second_value) in rows {
+        let second_value = if first_value == second_value {
+            second_value
+        } else {
+            second_value.yellow().to_string()
+        };
+        builder.push_record([field.to_string(), first_value, second_value]);
+    }
+
+    let mut table = builder.build();
+    table.with(Style::modern());
+    println!("{}", table);
+
+    Ok(())
+}
+
+/// Print releases as CSV rows (oldest first, matching the table order)
+fn print_csv(
+    releases: &[&sdk_manager::FlutterRelease],
+    installed_versions: &HashSet<String>,
+    master_row: Option<&str>,
+) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(std::io::stdout());
+    writer.write_record(["version", "channel", "release_date", "dart_sdk_version", "installed"])?;
+
+    for release in releases {
+        writer.write_record([
+            release.version.as_str(),
+            release.channel.as_str(),
+            &release.release_date.to_rfc3339_opts(SecondsFormat::Secs, true),
+            release.dart_sdk_version.as_deref().unwrap_or(""),
+            if installed_versions.contains(&release.version) { "true" } else { "false" },
+        ])?;
+    }
+
+    if let Some(master) = master_row {
+        writer.write_record([
+            master,
+            "master",
+            ROLLING,
+            "",
+            if installed_versions.contains("master") { "true" } else { "false" },
+        ])?;
+    }
+
+    writer.flush()?;
+    Ok(())
 }
 
 #[derive(Tabled)]
 #[tabled(rename_all = "Upper Title Case")]
 struct ReleaseRow {
     version: String,
-    #[tabled(display = "format_date")]
-    release_date: DateTime<Utc>,
+    release_date: String,
+    #[tabled(rename = "Dart SDK")]
+    dart_sdk: String,
     channel: String,
 }
 
@@ -103,10 +333,15 @@ struct ReleaseRow {
 struct ChannelRow {
     channel: String,
     version: String,
-    #[tabled(display = "format_date")]
-    release_date: DateTime<Utc>,
+    release_date: String,
+    #[tabled(rename = "Dart SDK")]
+    dart_sdk: String,
 }
 
 fn format_date(date: &DateTime<Utc>) -> String {
     date.format("%b %e, %Y").to_string() // e.g., "Jun 25, 2025"
 }
+
+fn format_dart_sdk(release: &sdk_manager::FlutterRelease) -> String {
+    release.dart_sdk_version.clone().unwrap_or_else(|| NO_DART_SDK.to_string())
+}
@@ -1,13 +1,14 @@
-use crate::sdk_manager;
+use crate::{completion, config_manager, sdk_manager, utils};
 use anyhow::{Result, bail};
 use clap::Args;
+use clap_complete::engine::ArgValueCandidates;
 use std::io::{self, Write};
 use tracing::info;
 
 #[derive(Debug, Clone, Args)]
 pub struct RemoveArgs {
     /// Flutter version to remove (e.g., 3.24.0)
-    #[arg(required_unless_present = "all")]
+    #[arg(required_unless_present = "all", add = ArgValueCandidates::new(completion::installed_versions))]
     version: Option<String>,
 
     /// Remove all installed versions
@@ -17,6 +18,14 @@ pub struct RemoveArgs {
     /// Skip engine cleanup (faster, but may leave unused engines)
     #[arg(long)]
     skip_engine_cleanup: bool,
+
+    /// Remove versions even if the current project or the global setting uses them
+    #[arg(short, long)]
+    force: bool,
+
+    /// Show the version directories and engines that would be removed, without deleting anything
+    #[arg(long)]
+    dry_run: bool,
 }
 
 pub async fn run(args: RemoveArgs) -> Result<()> {
@@ -29,18 +38,38 @@ pub async fn run(args: RemoveArgs) -> Result<()> {
         bail!("Cannot specify both a version and --all flag");
     }
 
+    if args.dry_run {
+        return print_dry_run(&args).await;
+    }
+
     // Handle --all flag
     if args.all {
         info!("Removing all installed Flutter versions");
 
-        // Get confirmation from user
-        print!("Are you sure you want to remove all installed Flutter versions? (y/N): ");
-        io::stdout().flush()?;
+        let installed = sdk_manager::list_installed_versions().await?;
+        let pins: Vec<_> = pinned_versions()
+            .await?
+            .into_iter()
+            .filter(|(version, _)| installed.contains(version))
+            .collect();
+        if !pins.is_empty() && !args.force {
+            eprintln!("✗ Some installed versions are still in use:");
+            for (version, pinned_by) in &pins {
+                eprintln!("  {} (pinned by {})", version, pinned_by);
+            }
+            eprintln!("  Use --force to remove them anyway");
+            bail!("Refusing to remove pinned versions without --force");
+        }
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
+        let mut in_use: Vec<String> =
+            utils::versions_in_use().unwrap_or_default().into_iter().collect();
+        if !in_use.is_empty() {
+            in_use.sort();
+            println!("⚠ In use by a running flutter/dart process: {}", in_use.join(", "));
+        }
 
-        if !input.trim().eq_ignore_ascii_case("y") && !input.trim().eq_ignore_ascii_case("yes") {
+        // Get confirmation from user
+        if !confirm("Are you sure you want to remove all installed Flutter versions? (y/N): ")? {
             println!("Cancelled.");
             info!("Removal cancelled by user");
             return Ok(());
@@ -76,14 +105,9 @@ pub async fn run(args: RemoveArgs) -> Result<()> {
         if !args.skip_engine_cleanup {
             info!("Starting engine cleanup");
             println!("\nCleaning up unused engines...");
-            match sdk_manager::cleanup_unused_engines().await {
+            match sdk_manager::cleanup_unused_engines(&[], false).await {
                 Ok(result) => {
-                    for hash in &result.removed_engines {
-                        println!("✓ Removed unused engine: {}", hash);
-                    }
-                    for (hash, error) in &result.failed_removals {
-                        eprintln!("✗ Failed to remove engine {}: {}", hash, error);
-                    }
+                    print_engine_cleanup(&result);
                     if result.removed_engines.is_empty() && result.failed_removals.is_empty() {
                         println!("No unused engines to remove");
                     }
@@ -108,6 +132,32 @@ pub async fn run(args: RemoveArgs) -> Result<()> {
         bail!("Flutter version {} is not installed", version);
     }
 
+    // Removing a pinned version breaks the project (or global setup) using it
+    let pinned_by: Vec<String> = pinned_versions()
+        .await?
+        .into_iter()
+        .filter(|(pinned, _)| pinned == version)
+        .map(|(_, pinned_by)| pinned_by)
+        .collect();
+    if !pinned_by.is_empty() {
+        if !args.force {
+            eprintln!("✗ Flutter {} is pinned by {}", version, pinned_by.join(" and "));
+            eprintln!("  Use --force to remove it anyway");
+            bail!("Refusing to remove pinned version {} without --force", version);
+        }
+        println!("⚠ Removing Flutter {} pinned by {}", version, pinned_by.join(" and "));
+    }
+
+    // Removing an SDK under a running build breaks that build
+    if utils::versions_in_use().unwrap_or_default().contains(version) {
+        println!("⚠ Flutter {} is in use by a running flutter/dart process", version);
+        if !confirm("Remove it anyway? (y/N): ")? {
+            println!("Cancelled.");
+            info!("Removal cancelled by user");
+            return Ok(());
+        }
+    }
+
     println!("Removing Flutter {}...", version);
 
     match sdk_manager::uninstall(version).await {
@@ -118,15 +168,8 @@ pub async fn run(args: RemoveArgs) -> Result<()> {
             if !args.skip_engine_cleanup {
                 info!("Starting engine cleanup after version removal");
                 println!("Checking for unused engines...");
-                match sdk_manager::cleanup_unused_engines().await {
-                    Ok(result) => {
-                        for hash in &result.removed_engines {
-                            println!("✓ Removed unused engine: {}", hash);
-                        }
-                        for (hash, error) in &result.failed_removals {
-                            eprintln!("✗ Failed to remove engine {}: {}", hash, error);
-                        }
-                    }
+                match sdk_manager::cleanup_unused_engines(&[], false).await {
+                    Ok(result) => print_engine_cleanup(&result),
                     Err(e) => {
                         eprintln!("Warning: Engine cleanup failed: {}", e);
                     }
@@ -141,5 +184,106 @@ pub async fn run(args: RemoveArgs) -> Result<()> {
         }
     }
 
-    return Ok(());
+    Ok(())
+}
+
+/// Print what `remove` would delete and how much space it would free
+async fn print_dry_run(args: &RemoveArgs) -> Result<()> {
+    info!("Previewing removal (dry run)");
+
+    let installed = sdk_manager::list_installed_versions().await?;
+    let mut versions = match &args.version {
+        Some(version) if !installed.contains(version) => {
+            bail!("Flutter version {} is not installed", version);
+        }
+        Some(version) => vec![version.clone()],
+        None => installed,
+    };
+    versions.sort();
+
+    if versions.is_empty() {
+        println!("No Flutter versions installed.");
+        return Ok(());
+    }
+
+    let pins = pinned_versions().await?;
+    let mut total = 0;
+    for version in &versions {
+        let path = utils::flutter_version_dir(version)?;
+        let size = utils::dir_size(&path).unwrap_or(0);
+        total += size;
+        println!("Would remove Flutter {}: {} ({})", version, path.display(), utils::format_size(size));
+
+        for (_, pinned_by) in pins.iter().filter(|(pinned, _)| pinned == version) {
+            println!("  ⚠ Pinned by {} (needs --force)", pinned_by);
+        }
+    }
+
+    if !args.skip_engine_cleanup {
+        let result = sdk_manager::cleanup_unused_engines(&versions, true).await?;
+        for (hash, size) in &result.removed_engines {
+            println!("Would remove unused engine: {} ({})", hash, utils::format_size(*size));
+        }
+        total += result.reclaimed_bytes();
+    }
+
+    println!();
+    println!("Would reclaim {} (dry run, nothing was deleted)", utils::format_size(total));
+    Ok(())
+}
+
+/// Versions the current project (including its flavors) and the global setting
+/// depend on, with a description of what pins each one
+async fn pinned_versions() -> Result<Vec<(String, String)>> {
+    let mut pins = Vec::new();
+
+    if let Some(project_root) = config_manager::find_project_root().await?
+        && let Some(config) = config_manager::read_project_config(&project_root).await?
+    {
+        if !config.flutter.is_empty() {
+            pins.push((config.flutter.clone(), format!("the project at {}", project_root.display())));
+        }
+
+        let mut flavors: Vec<_> = config.flavors.iter().flatten().collect();
+        flavors.sort_by_key(|(name, _)| *name);
+        for (name, flavor) in flavors {
+            pins.push((flavor.version().to_string(), format!("flavor '{}' of the project", name)));
+        }
+    }
+
+    if let Some(global) = config_manager::get_global_flutter_version().await? {
+        pins.push((global, "the global version".to_string()));
+    }
+
+    Ok(pins)
+}
+
+fn confirm(prompt: &str) -> Result<bool> {
+    print!("{}", prompt);
+    io::stdout().flush()?;
+
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+
+    Ok(input.trim().eq_ignore_ascii_case("y") || input.trim().eq_ignore_ascii_case("yes"))
+}
+
+fn print_engine_cleanup(result: &sdk_manager::EngineCleanupResult) {
+    for (hash, size) in &result.removed_engines {
+        println!("✓ Removed unused engine: {} ({})", hash, utils::format_size(*size));
+    }
+    for (hash, error) in &result.failed_removals {
+        eprintln!("✗ Failed to remove engine {}: {}", hash, error);
+    }
+
+    if !result.removed_engines.is_empty() {
+        println!(
+            "Reclaimed {} from {} engine(s)",
+            utils::format_size(result.reclaimed_bytes()),
+            result.removed_engines.len()
+        );
+    }
+    if !result.failed_removals.is_empty() {
+        eprintln!("⚠ {} engine(s) could not be removed", result.failed_removals.len());
+    }
 }
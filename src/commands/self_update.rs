@@ -0,0 +1,57 @@
+use anyhow::Result;
+use clap::Args;
+use tracing::info;
+
+use crate::{update_manager, utils};
+
+#[derive(Debug, Clone, Args)]
+pub struct SelfUpdateArgs {
+    /// Only report whether a newer version is available
+    #[arg(long)]
+    check: bool,
+
+    /// Install this release instead of the latest (e.g. "0.2.0" or "v0.2.0")
+    #[arg(long, value_name = "TAG")]
+    version: Option<String>,
+}
+
+pub async fn run(args: SelfUpdateArgs) -> Result<()> {
+    let current = update_manager::CURRENT_VERSION;
+    info!("Checking for fvm-rs releases (current: {})", current);
+
+    let release = update_manager::fetch_release(args.version.as_deref()).await?;
+    let version = release.version();
+    let newer = utils::compare_versions(version, current) == std::cmp::Ordering::Greater;
+
+    if args.check {
+        if newer {
+            println!("fvm-rs {} is available (you have {})", version, current);
+            println!("  Run: fvm-rs self-update");
+        } else {
+            println!("✓ fvm-rs {} is up to date", current);
+        }
+        return Ok(());
+    }
+
+    // Without --version, only move forward; a pinned version may downgrade
+    if version == current || (args.version.is_none() && !newer) {
+        println!("✓ fvm-rs {} is up to date", current);
+        return Ok(());
+    }
+
+    // Fail before downloading anything when the binary can't be replaced
+    if let Err(e) = update_manager::replaceable_executable() {
+        eprintln!("✗ Cannot update fvm-rs in place");
+        return Err(e);
+    }
+
+    println!(
+        "Downloading fvm-rs {} ({})...",
+        version,
+        update_manager::platform_asset_name()
+    );
+    update_manager::install_release(&release).await?;
+
+    println!("✓ Updated fvm-rs {} → {}", current, version);
+    Ok(())
+}
@@ -6,9 +6,13 @@ use crate::{sdk_manager, utils};
 
 #[derive(Debug, Clone, Args)]
 pub struct SpawnArgs {
-    /// Flutter SDK version to use
+    /// Flutter SDK version to use (a "@channel" suffix like "3.10.0@beta" sets the branch)
     pub version: Option<String>,
 
+    /// Abort unless the version's tag points to this commit SHA (full or 7+ characters)
+    #[arg(long, value_name = "SHA")]
+    expect_commit: Option<String>,
+
     /// Flutter command and arguments to execute
     #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
     flutter_args: Vec<String>,
@@ -21,12 +25,22 @@ pub async fn run(args: SpawnArgs) -> Result<i32> {
         eprintln!("  Usage: fvm-rs spawn <version> <flutter_command> [args...]");
         anyhow::anyhow!("Need to provide a version to spawn a Flutter command")
     })?;
+    let version = sdk_manager::resolve_version(&version).await?;
 
     debug!("Spawning Flutter command with version: {}", version);
     info!("Spawning version \"{}\"...", version);
 
-    // Ensure version is installed (auto-install if not present)
-    sdk_manager::ensure_installed(&version).await?;
+    // Ensure version is installed (auto-install if not present). Repeated spawns
+    // within a few seconds reuse the previous verification, unless a commit must be checked.
+    if let Some(expected_commit) = args.expect_commit {
+        let options = sdk_manager::InstallOptions {
+            expected_commit: Some(expected_commit),
+            ..Default::default()
+        };
+        sdk_manager::ensure_installed_with(&version, &options).await?;
+    } else {
+        sdk_manager::ensure_installed_cached(&version).await?;
+    }
 
     // Get the Flutter installation path
     let flutter_path = utils::flutter_version_dir(&version)?;
@@ -0,0 +1,65 @@
+use anyhow::{Context, Result};
+use clap::Args;
+use tracing::info;
+
+use crate::{config_manager, progress, sdk_manager};
+
+#[derive(Debug, Clone, Args)]
+pub struct UpgradeArgs {
+    /// Channel install to upgrade (e.g. "stable", "mycompany/beta");
+    /// defaults to the project's, then the global version
+    version: Option<String>,
+
+    /// Hide progress bars and print progress as plain lines
+    #[arg(short, long)]
+    quiet: bool,
+}
+
+pub async fn run(args: UpgradeArgs) -> Result<()> {
+    let version = match args.version {
+        Some(version) => version,
+        None => match config_manager::get_project_flutter_version().await? {
+            Some(version) => version,
+            None => config_manager::get_global_flutter_version()
+                .await?
+                .context("No version given and no project or global version configured")?,
+        },
+    };
+
+    info!("Upgrading Flutter {}", version);
+    println!("Upgrading Flutter {}...", version);
+
+    let progress = progress::InstallProgress::new(args.quiet);
+    let options = sdk_manager::InstallOptions {
+        progress: Some(progress.callback()),
+        ..Default::default()
+    };
+    let result = sdk_manager::upgrade_channel(&version, &options).await;
+    progress.finish();
+
+    match result? {
+        sdk_manager::UpgradeOutcome::UpToDate { commit } => {
+            println!(
+                "✓ Flutter {} is already up to date ({})",
+                version,
+                short_commit(&commit)
+            );
+        }
+        sdk_manager::UpgradeOutcome::Upgraded { commit, new_engine } => {
+            println!(
+                "✓ Upgraded Flutter {} to {}",
+                version,
+                short_commit(&commit)
+            );
+            if let Some(hash) = new_engine {
+                println!("  Engine updated to {}", hash);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn short_commit(commit: &str) -> &str {
+    &commit[..commit.len().min(10)]
+}
@@ -1,14 +1,16 @@
 use anyhow::{Context, Result};
+use chrono::NaiveDate;
 use clap::Args;
-use dialoguer::{theme::ColorfulTheme, Select};
+use clap_complete::engine::ArgValueCandidates;
 use std::env;
 use tracing::info;
 
-use crate::{config_manager, gitignore_manager, ide_manager, sdk_manager};
+use crate::{completion, config_manager, gitignore_manager, ide_manager, interactive, sdk_manager};
 
 #[derive(Debug, Clone, Args)]
 pub struct UseArgs {
-    /// Flutter version to use (e.g., "3.24.0", "stable"), or flavor name to switch to
+    /// Flutter version to use (e.g., "3.24.0", "stable", "latest", "3.10.0@beta"), or flavor name to switch to
+    #[arg(add = ArgValueCandidates::new(completion::versions_and_flavors))]
     version: Option<String>,
 
     /// Skip running "flutter pub get" after switching SDK versions
@@ -30,17 +32,87 @@ pub struct UseArgs {
     /// Pin the latest release of a channel instead of using the channel directly
     #[arg(long, short = 'p')]
     pin: bool,
+
+    /// Run "flutter create" with the selected version before pinning it
+    #[arg(long)]
+    create: bool,
+
+    /// Project name passed to "flutter create" (requires --create)
+    #[arg(long, requires = "create")]
+    project_name: Option<String>,
+
+    /// Organization passed to "flutter create" (requires --create)
+    #[arg(long, requires = "create")]
+    org: Option<String>,
+
+    /// Store a dart-define (KEY=VALUE) for the flavor, passed to `fvm-rs flavor` runs (requires --flavor)
+    #[arg(long = "dart-define", value_name = "KEY=VALUE", value_parser = parse_dart_define, requires = "flavor")]
+    dart_defines: Vec<(String, String)>,
+
+    /// Pin the version configured in the nearest parent project (e.g. a monorepo root)
+    #[arg(long, conflicts_with_all = ["version", "pin"])]
+    inherit: bool,
+
+    /// Pin the release that was current on this date (YYYY-MM-DD)
+    #[arg(long, value_name = "DATE", conflicts_with_all = ["version", "pin", "inherit"])]
+    as_of: Option<NaiveDate>,
+
+    /// Pin the newest stable release whose Dart SDK satisfies pubspec.yaml's environment.sdk
+    #[arg(long, conflicts_with_all = ["version", "pin", "inherit", "as_of"])]
+    match_pubspec: bool,
+
+    /// Channel to resolve --as-of against
+    #[arg(
+        long,
+        requires = "as_of",
+        value_parser = clap::builder::PossibleValuesParser::new(["stable", "beta", "dev"]),
+        default_value = "stable"
+    )]
+    channel: String,
+
+    /// Run `flutter precache` after installing (optionally for platforms, e.g. --precache=android,ios)
+    #[arg(
+        long,
+        value_name = "PLATFORMS",
+        num_args = 0..=1,
+        require_equals = true,
+        value_delimiter = ',',
+        value_parser = clap::builder::PossibleValuesParser::new(sdk_manager::PRECACHE_PLATFORMS)
+    )]
+    precache: Option<Vec<String>>,
+
+    /// Only check that the project's pinned versions are installed and linked; changes nothing
+    #[arg(long, conflicts_with_all = ["version", "pin", "inherit", "as_of", "match_pubspec", "create", "flavor", "precache"])]
+    validate_only: bool,
 }
 
 pub async fn run(args: UseArgs) -> Result<()> {
+    if args.validate_only {
+        return validate_project().await;
+    }
+
     // Get current directory
     let current_dir = env::current_dir().context("Failed to get current directory")?;
 
-    // Get version from args or interactive selector
+    // Get version from args, parent project or interactive selector
     let mut version_input = if let Some(v) = args.version {
         v
+    } else if args.inherit {
+        inherit_parent_version(&current_dir).await?
+    } else if let Some(date) = args.as_of {
+        let release = sdk_manager::resolve_release_as_of(date, &args.channel).await?;
+        println!(
+            "Resolved {} as of {} to version {} (released {})",
+            args.channel,
+            date,
+            release.version,
+            release.release_date.format("%Y-%m-%d")
+        );
+        release.version
+    } else if args.match_pubspec {
+        resolve_pubspec_version(&current_dir).await?
     } else {
-        select_version_interactively().await?
+        interactive::pick_installed_version("Select a Flutter version to use for this project").await?
     };
 
     // Handle --pin flag: convert channel to latest release version
@@ -62,12 +134,7 @@ pub async fn run(args: UseArgs) -> Result<()> {
         info!("Fetching latest release for {} channel", version_input);
         let releases = sdk_manager::list_available_versions().await?;
 
-        let latest_release = match version_input.as_str() {
-            "stable" => &releases.current_releases.stable,
-            "beta" => &releases.current_releases.beta,
-            "dev" => &releases.current_releases.dev,
-            _ => unreachable!(),
-        };
+        let latest_release = releases.current_release(&version_input)?;
 
         println!("Pinning version {} from \"{}\" release channel...",
                  latest_release.version, version_input);
@@ -89,8 +156,12 @@ pub async fn run(args: UseArgs) -> Result<()> {
         );
     }
 
-    // Use the resolved version for installation and config
-    let version_to_install = resolved_version.clone();
+    // Use the resolved version for installation and config; a partial version
+    // like "3.24" is pinned as the concrete release it resolves to
+    let version_to_install = sdk_manager::resolve_version(&resolved_version).await?;
+    if version_to_install != resolved_version {
+        println!("Resolved {} to version {}", resolved_version, version_to_install);
+    }
 
     if is_flavor_switch {
         println!("Using Flutter SDK from flavor: \"{}\" which is \"{}\"", version_input, resolved_version);
@@ -112,28 +183,51 @@ pub async fn run(args: UseArgs) -> Result<()> {
     // Ensure the version is installed first
     sdk_manager::ensure_installed(&version_to_install).await?;
 
+    if let Some(platforms) = &args.precache {
+        println!("Precaching Flutter {} artifacts...", version_to_install);
+        let exit_code = sdk_manager::precache(&version_to_install, platforms).await?;
+        if exit_code != 0 {
+            anyhow::bail!("`flutter precache` failed with exit code {}", exit_code);
+        }
+        println!("✓ Artifacts precached");
+    }
+
+    // Scaffold a new Flutter project with the selected version if requested
+    if args.create {
+        create_project(
+            &current_dir,
+            &version_to_install,
+            args.project_name.as_deref(),
+            args.org.as_deref(),
+        )?;
+    }
+
     info!("Creating FVM configuration in: {}", current_dir.display());
 
     // Update config based on whether we're using --flavor flag
     if let Some(flavor_name) = &args.flavor {
         // Pin version to a flavor
-        config_manager::update_project_config(
+        let changes = config_manager::update_project_config(
             &current_dir,
             Some(&version_to_install),
             Some((flavor_name, &version_to_install)),
+            // Keep previously stored defines unless new ones were given
+            (!args.dart_defines.is_empty()).then(|| args.dart_defines.iter().cloned().collect()),
         )
         .await
         .context("Failed to update project configuration with flavor")?;
 
         println!("✓ Project now uses Flutter SDK: {} on [{}] flavor", version_to_install, flavor_name);
+        print_config_changes(&changes);
         info!("Successfully pinned version {} to flavor {}", version_to_install, flavor_name);
     } else {
         // Regular version switch (may be from flavor resolution)
         // Use update_project_config to preserve existing flavors
-        config_manager::update_project_config(
+        let changes = config_manager::update_project_config(
             &current_dir,
             Some(&version_to_install),
             None, // Don't add/update any flavor, just preserve existing ones
+            None,
         )
         .await
         .context("Failed to write project configuration")?;
@@ -143,6 +237,7 @@ pub async fn run(args: UseArgs) -> Result<()> {
         } else {
             println!("✓ Project now uses Flutter SDK version: {}", version_to_install);
         }
+        print_config_changes(&changes);
         info!("Successfully configured project to use Flutter SDK {}", version_to_install);
     }
 
@@ -153,6 +248,11 @@ pub async fn run(args: UseArgs) -> Result<()> {
         .await
         .context("Failed to update .fvm/.gitignore")?;
 
+    // IDE settings point at .fvm/flutter_sdk, so it must resolve to the pinned version
+    sdk_manager::link_project_sdk(&current_dir, &version_to_install)
+        .await
+        .context("Failed to link .fvm/flutter_sdk")?;
+
     // Read global config to check IDE integration settings
     let global_config = config_manager::GlobalConfig::read().await?;
 
@@ -225,6 +325,52 @@ pub async fn run(args: UseArgs) -> Result<()> {
     Ok(())
 }
 
+/// Parse a KEY=VALUE dart-define argument
+fn parse_dart_define(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+        _ => Err(format!("Invalid dart-define '{}', expected KEY=VALUE", value)),
+    }
+}
+
+/// Read the pinned version from the nearest project above `current_dir`
+///
+/// Used by `use --inherit` so leaf packages in a monorepo get a local config
+/// matching the root's version.
+async fn inherit_parent_version(current_dir: &std::path::Path) -> Result<String> {
+    let parent_root = match current_dir.parent() {
+        Some(parent) => config_manager::find_project_root_from(parent).await?,
+        None => None,
+    }
+    .context("No parent project with an FVM configuration found to inherit from")?;
+
+    let config = config_manager::read_project_config(&parent_root)
+        .await?
+        .context("No FVM configuration found in parent project")?;
+
+    println!("Inheriting Flutter SDK {} from {}", config.flutter, parent_root.display());
+    info!("Inherited version {} from {}", config.flutter, parent_root.display());
+
+    Ok(config.flutter)
+}
+
+/// Pick the newest stable release matching the Dart SDK constraint in pubspec.yaml
+async fn resolve_pubspec_version(project_root: &std::path::Path) -> Result<String> {
+    let constraint = config_manager::read_pubspec_sdk_constraint(project_root)
+        .await?
+        .context("pubspec.yaml has no environment.sdk constraint to match")?;
+
+    let release = sdk_manager::resolve_dart_constraint(&constraint).await?;
+    println!(
+        "Resolved Dart SDK constraint \"{}\" to version {} (Dart {})",
+        constraint,
+        release.version,
+        release.dart_sdk_version.as_deref().unwrap_or("unknown")
+    );
+
+    Ok(release.version)
+}
+
 /// Resolve whether the input is a version or a flavor name
 ///
 /// Returns (resolved_version, is_flavor_switch).
@@ -237,11 +383,11 @@ async fn resolve_version_or_flavor(
     // Try to read existing project config
     if let Some(config) = config_manager::read_project_config(project_root).await? {
         // Check if version_input matches a flavor name
-        if let Some(flavors) = &config.flavors {
-            if let Some(flavor_version) = flavors.get(version_input) {
-                // It's a flavor name! Resolve to its version
-                return Ok((flavor_version.clone(), true));
-            }
+        if let Some(flavors) = &config.flavors
+            && let Some(flavor) = flavors.get(version_input)
+        {
+            // It's a flavor name! Resolve to its version
+            return Ok((flavor.version().to_string(), true));
         }
     }
 
@@ -249,27 +395,64 @@ async fn resolve_version_or_flavor(
     Ok((version_input.to_string(), false))
 }
 
-async fn select_version_interactively() -> Result<String> {
-    info!("Selecting Flutter version interactively");
+/// Print what `use` changed in the config files, e.g. "flutter: 3.19.0 → 3.22.0"
+fn print_config_changes(changes: &[config_manager::ConfigChange]) {
+    if changes.is_empty() {
+        println!("  No config changes (already pinned)");
+        return;
+    }
+
+    for change in changes {
+        match (&change.old, &change.new) {
+            (Some(old), Some(new)) => println!("  ~ {}: {} → {}", change.field, old, new),
+            (None, Some(new)) => println!("  + {}: {}", change.field, new),
+            (Some(old), None) => println!("  - {}: {}", change.field, old),
+            (None, None) => {}
+        }
+    }
+}
+
+/// Run "flutter create" in the project directory using the given version
+///
+/// Skips creation if a pubspec.yaml already exists, so --create is safe to
+/// pass in an existing project.
+fn create_project(
+    project_dir: &std::path::Path,
+    version: &str,
+    project_name: Option<&str>,
+    org: Option<&str>,
+) -> Result<()> {
+    use crate::utils;
 
-    // Get list of installed versions
-    let versions = sdk_manager::list_installed_versions().await?;
+    if project_dir.join("pubspec.yaml").exists() {
+        println!("pubspec.yaml already exists, skipping flutter create");
+        return Ok(());
+    }
 
-    if versions.is_empty() {
-        anyhow::bail!(
-            "No Flutter versions installed.\nRun 'fvm-rs install <version>' to install one first."
-        );
+    info!("Creating Flutter project with version {}", version);
+    println!("Creating Flutter project with Flutter SDK {}...", version);
+
+    let mut create_args = vec!["create".to_string()];
+    if let Some(name) = project_name {
+        create_args.push("--project-name".to_string());
+        create_args.push(name.to_string());
+    }
+    if let Some(org) = org {
+        create_args.push("--org".to_string());
+        create_args.push(org.to_string());
     }
+    create_args.push(".".to_string());
 
-    // Show selection menu
-    let selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select a Flutter version to use for this project")
-        .items(&versions)
-        .default(0)
-        .interact()
-        .context("Failed to get user selection")?;
+    let flutter_path = utils::flutter_version_dir(version)?;
+    let exit_code = utils::execute_with_flutter_path("flutter", &create_args, &flutter_path)?;
 
-    Ok(versions[selection].clone())
+    if exit_code != 0 {
+        eprintln!("✗ flutter create failed with exit code {}", exit_code);
+        anyhow::bail!("Failed to create Flutter project");
+    }
+
+    println!("✓ Flutter project created");
+    Ok(())
 }
 
 async fn run_flutter_pub_get(project_dir: &std::path::Path, version: &str) -> Result<()> {
@@ -292,7 +475,7 @@ async fn run_flutter_pub_get(project_dir: &std::path::Path, version: &str) -> Re
 
     // Run flutter pub get in the project directory
     let output = Command::new(&flutter_bin)
-        .args(&["pub", "get"])
+        .args(["pub", "get"])
         .current_dir(project_dir)
         .output()
         .context("Failed to execute flutter pub get")?;
@@ -304,3 +487,52 @@ async fn run_flutter_pub_get(project_dir: &std::path::Path, version: &str) -> Re
 
     Ok(())
 }
+
+/// Check that every version the project pins is installed and ready, without writing anything
+async fn validate_project() -> Result<()> {
+    info!("Validating project Flutter SDK setup");
+
+    let project_root = config_manager::find_project_root()
+        .await?
+        .context("No FVM config found. Run 'fvm-rs use <version>' to configure this project")?;
+    let config = config_manager::read_project_config(&project_root)
+        .await?
+        .context("Failed to read project config")?;
+
+    // The main version first, then each distinct flavor version
+    let mut versions = vec![config.flutter.clone()];
+    if let Some(flavors) = &config.flavors {
+        let mut flavor_versions: Vec<String> =
+            flavors.values().map(|flavor| flavor.version().to_string()).collect();
+        flavor_versions.sort();
+        for version in flavor_versions {
+            if !versions.contains(&version) {
+                versions.push(version);
+            }
+        }
+    }
+
+    let mut failed = 0;
+    for version in &versions {
+        let problems = sdk_manager::validate_installation(version).await?;
+        if problems.is_empty() {
+            println!("✓ Flutter {} is installed and linked", version);
+        } else {
+            failed += 1;
+            for problem in problems {
+                println!("✗ Flutter {}: {}", version, problem);
+            }
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!(
+            "{} of {} pinned version(s) not ready in {}",
+            failed,
+            versions.len(),
+            project_root.display()
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+use anyhow::{Result, bail};
+use clap::Args;
+use tracing::{debug, info};
+
+use crate::{config_manager, utils};
+
+#[derive(Debug, Clone, Args)]
+pub struct WhichArgs {
+    /// Executable to resolve
+    #[arg(value_parser = clap::builder::PossibleValuesParser::new(["flutter", "dart"]))]
+    command: String,
+
+    /// Print only the path, for scripts
+    #[arg(short, long)]
+    quiet: bool,
+}
+
+pub async fn run(args: WhichArgs) -> Result<()> {
+    info!("Resolving {} executable", args.command);
+
+    // Same resolution as exec/dart: project -> global -> system PATH
+    let project_version = config_manager::get_project_flutter_version().await?;
+    let global_version = config_manager::get_global_flutter_version().await?;
+
+    let (version, source) = match (project_version, global_version) {
+        (Some(version), _) => (version, "project"),
+        (None, Some(version)) => (version, "global"),
+        (None, None) => return print_system_path(&args),
+    };
+
+    debug!("Using {} version: {}", source, version);
+    let flutter_path = utils::flutter_version_dir(&version)?;
+    let bin_path = utils::flutter_bin_path(&flutter_path, &args.command);
+
+    println!("{}", bin_path.display());
+    if !args.quiet {
+        println!("  From Flutter {} ({} version)", version, source);
+    }
+
+    if flutter_path.exists() && !bin_path.exists() {
+        eprintln!("⚠ {} not found in Flutter {}", bin_path.display(), version);
+    } else if !bin_path.exists() {
+        eprintln!(
+            "⚠ Flutter {} is not installed; it will be installed on first use",
+            version
+        );
+        eprintln!("  Run: fvm-rs install {}", version);
+    }
+
+    Ok(())
+}
+
+fn print_system_path(args: &WhichArgs) -> Result<()> {
+    debug!("No FVM version configured, searching system PATH");
+
+    let Ok(path) = which::which(&args.command) else {
+        if !args.quiet {
+            eprintln!(
+                "✗ No FVM version configured and {} is not on PATH",
+                args.command
+            );
+            eprintln!("  Run: fvm-rs use <version> or fvm-rs global <version>");
+        }
+        bail!("{} not found", args.command);
+    };
+
+    println!("{}", path.display());
+    if !args.quiet {
+        println!("  From system PATH (no project or global version configured)");
+    }
+
+    Ok(())
+}
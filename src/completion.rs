@@ -0,0 +1,73 @@
+//! Dynamic candidates for shell completion
+//!
+//! These run while the shell asks for completions (see `fvm-rs completions
+//! --dynamic`), before the async runtime does any work, so they read the cache
+//! and project config synchronously and return nothing on errors.
+
+use clap_complete::engine::CompletionCandidate;
+
+use crate::{config_manager, utils};
+
+/// Installed Flutter versions, for `use`, `remove`, `global` and friends
+pub fn installed_versions() -> Vec<CompletionCandidate> {
+    installed_version_names()
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+/// Flavors defined in the current project's config
+pub fn project_flavors() -> Vec<CompletionCandidate> {
+    project_flavor_names()
+        .into_iter()
+        .map(|name| CompletionCandidate::new(name).help(Some("flavor".into())))
+        .collect()
+}
+
+/// Installed versions followed by project flavors, for `use`
+pub fn versions_and_flavors() -> Vec<CompletionCandidate> {
+    let mut candidates = installed_versions();
+    candidates.extend(project_flavors());
+    candidates
+}
+
+fn installed_version_names() -> Vec<String> {
+    // Completion requests are answered before main() resolves the cache path,
+    // so read it from the config here instead of relying on utils::flutter_dir()
+    let Ok(entries) = config_manager::GlobalConfig::read_blocking()
+        .and_then(|config| config.get_cache_path())
+        .and_then(|cache_path| Ok(std::fs::read_dir(cache_path.join("flutter"))?))
+    else {
+        return vec![];
+    };
+
+    let mut versions: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+        .collect();
+    versions.sort_by(|a, b| utils::compare_versions(b, a));
+    versions
+}
+
+fn project_flavor_names() -> Vec<String> {
+    let Ok(current) = std::env::current_dir() else {
+        return vec![];
+    };
+
+    let Some(project_root) = current
+        .ancestors()
+        .find(|dir| dir.join(".fvmrc").exists() || dir.join(".fvm/fvm_config.json").exists())
+    else {
+        return vec![];
+    };
+
+    let mut flavors: Vec<String> = config_manager::read_project_config_blocking(project_root)
+        .ok()
+        .flatten()
+        .and_then(|config| config.flavors)
+        .map(|flavors| flavors.into_keys().collect())
+        .unwrap_or_default();
+    flavors.sort();
+    flavors
+}
@@ -2,6 +2,7 @@ use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tokio::fs;
 use tracing::debug;
 
@@ -16,15 +17,66 @@ pub struct Fork {
     pub url: String,
 }
 
+/// Flavor configuration in .fvmrc
+///
+/// Either a plain version string (the original FVM format) or an object with
+/// the version and dart-defines to pass to `flutter` when running the flavor.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum FlavorConfig {
+    /// Just the Flutter SDK version (e.g. "3.24.0")
+    Version(String),
+    /// Flutter SDK version plus per-flavor dart-defines
+    Detailed {
+        version: String,
+        #[serde(rename = "dartDefines", default, skip_serializing_if = "HashMap::is_empty")]
+        dart_defines: HashMap<String, String>,
+    },
+}
+
+impl FlavorConfig {
+    /// Create a flavor config, using the plain string form when there are no defines
+    pub fn new(version: impl Into<String>, dart_defines: HashMap<String, String>) -> Self {
+        if dart_defines.is_empty() {
+            Self::Version(version.into())
+        } else {
+            Self::Detailed {
+                version: version.into(),
+                dart_defines,
+            }
+        }
+    }
+
+    /// Flutter SDK version pinned for this flavor
+    pub fn version(&self) -> &str {
+        match self {
+            Self::Version(version) => version,
+            Self::Detailed { version, .. } => version,
+        }
+    }
+
+    /// Dart defines configured for this flavor (empty for the plain string form)
+    pub fn dart_defines(&self) -> HashMap<String, String> {
+        match self {
+            Self::Version(_) => HashMap::new(),
+            Self::Detailed { dart_defines, .. } => dart_defines.clone(),
+        }
+    }
+}
+
 /// Main project configuration format (.fvmrc)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectConfig {
     /// Flutter SDK version
     pub flutter: String,
 
-    /// Optional flavors mapping (flavor_name -> version)
+    /// Optional flavors mapping (flavor_name -> version, optionally with dart-defines)
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub flavors: Option<HashMap<String, String>>,
+    pub flavors: Option<HashMap<String, FlavorConfig>>,
+
+    /// Keys fvm-rs doesn't know about (e.g. other tools' settings), kept as-is on rewrite
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 /// Legacy project configuration format (.fvm/fvm_config.json)
@@ -35,6 +87,16 @@ struct LegacyProjectConfig {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     flavors: Option<HashMap<String, String>>,
+
+    /// Version of fvm-rs that last wrote the project config
+    ///
+    /// Kept out of .fvmrc so upgrading fvm-rs doesn't churn the committed file.
+    #[serde(rename = "fvmRsVersion", default, skip_serializing_if = "Option::is_none")]
+    fvm_rs_version: Option<String>,
+
+    /// Keys fvm-rs doesn't know about, kept as-is on rewrite
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl ProjectConfig {
@@ -43,22 +105,41 @@ impl ProjectConfig {
         Self {
             flutter: version.into(),
             flavors: None,
+            extra: serde_json::Map::new(),
         }
     }
 
     /// Convert to legacy format for backward compatibility
-    fn to_legacy(&self) -> LegacyProjectConfig {
+    ///
+    /// The legacy format only supports flavor versions, so dart-defines are dropped.
+    /// Unknown keys are taken from the existing legacy file, not from .fvmrc.
+    fn to_legacy(&self, extra: serde_json::Map<String, serde_json::Value>) -> LegacyProjectConfig {
         LegacyProjectConfig {
             flutter_sdk_version: self.flutter.clone(),
-            flavors: self.flavors.clone(),
+            flavors: self.flavors.as_ref().map(|flavors| {
+                flavors
+                    .iter()
+                    .map(|(name, flavor)| (name.clone(), flavor.version().to_string()))
+                    .collect()
+            }),
+            fvm_rs_version: Some(env!("CARGO_PKG_VERSION").to_string()),
+            extra,
         }
     }
 
     /// Convert from legacy format
+    ///
+    /// Unknown legacy keys stay in the legacy file (see `write_config_files`).
     fn from_legacy(legacy: LegacyProjectConfig) -> Self {
         Self {
             flutter: legacy.flutter_sdk_version,
-            flavors: legacy.flavors,
+            flavors: legacy.flavors.map(|flavors| {
+                flavors
+                    .into_iter()
+                    .map(|(name, version)| (name, FlavorConfig::Version(version)))
+                    .collect()
+            }),
+            extra: serde_json::Map::new(),
         }
     }
 }
@@ -83,18 +164,21 @@ pub fn validate_flavor_name(flavor_name: &str) -> Result<()> {
 /// This function intelligently merges updates with existing config:
 /// - If `main_version` is provided, updates the main `flutter` field
 /// - If `flavor` is provided, adds/updates that specific flavor (merges with existing flavors)
+/// - If `dart_defines` is provided, replaces the flavor's dart-defines; otherwise
+///   existing defines are kept
 /// - Preserves all existing config that isn't being updated
 ///
 /// Writes to both .fvmrc and .fvm/fvm_config.json for FVM compatibility.
+/// Returns what changed compared to the previous config.
 pub async fn update_project_config(
     project_root: &Path,
     main_version: Option<&str>,
     flavor: Option<(&str, &str)>, // (flavor_name, flavor_version)
-) -> Result<()> {
+    dart_defines: Option<HashMap<String, String>>,
+) -> Result<Vec<ConfigChange>> {
     // Read existing config or start with empty
-    let mut config = read_project_config(project_root)
-        .await?
-        .unwrap_or_else(|| ProjectConfig::new(""));
+    let previous = read_project_config(project_root).await?;
+    let mut config = previous.clone().unwrap_or_else(|| ProjectConfig::new(""));
 
     // Update main version if provided
     if let Some(version) = main_version {
@@ -112,8 +196,17 @@ pub async fn update_project_config(
         // Get existing flavors or create new map
         let mut flavors = config.flavors.take().unwrap_or_default();
 
-        // Add/update the flavor
-        flavors.insert(flavor_name.to_string(), flavor_version.to_string());
+        // Add/update the flavor, keeping its existing defines unless new ones are given
+        let defines = dart_defines.unwrap_or_else(|| {
+            flavors
+                .get(flavor_name)
+                .map(|existing| existing.dart_defines())
+                .unwrap_or_default()
+        });
+        flavors.insert(
+            flavor_name.to_string(),
+            FlavorConfig::new(flavor_version, defines),
+        );
 
         // Store back (only if not empty)
         config.flavors = if flavors.is_empty() {
@@ -124,17 +217,37 @@ pub async fn update_project_config(
     }
 
     // Write both config files
-    write_config_files(project_root, &config).await
+    write_config_files(project_root, &config).await?;
+
+    Ok(diff_project_configs(previous.as_ref(), &config))
 }
 
-/// Write project configuration to both .fvmrc and .fvm/fvm_config.json
+/// Remove a flavor from the project config and rewrite both config files
 ///
-/// This function writes two config files for FVM compatibility:
-/// 1. .fvmrc in the project root (primary format)
-/// 2. .fvm/fvm_config.json (legacy format for backward compatibility)
-pub async fn write_project_config(project_root: &Path, version: &str) -> Result<()> {
-    let config = ProjectConfig::new(version);
-    write_config_files(project_root, &config).await
+/// Removing the last flavor drops the `flavors` key. Returns the removed
+/// flavor, or an error listing the defined flavors if it doesn't exist.
+pub async fn remove_project_flavor(project_root: &Path, flavor_name: &str) -> Result<FlavorConfig> {
+    let mut config = read_project_config(project_root)
+        .await?
+        .context("No FVM configuration found in this project")?;
+
+    let mut flavors = config.flavors.take().unwrap_or_default();
+    let Some(removed) = flavors.remove(flavor_name) else {
+        let mut names: Vec<&String> = flavors.keys().collect();
+        names.sort();
+        let available = if names.is_empty() {
+            "none".to_string()
+        } else {
+            names.iter().map(|name| name.as_str()).collect::<Vec<_>>().join(", ")
+        };
+        anyhow::bail!("Flavor '{}' is not defined. Available flavors: {}", flavor_name, available);
+    };
+
+    debug!("Removing flavor '{}' ({})", flavor_name, removed.version());
+    config.flavors = if flavors.is_empty() { None } else { Some(flavors) };
+    write_config_files(project_root, &config).await?;
+
+    Ok(removed)
 }
 
 /// Internal helper to write both config files
@@ -144,10 +257,9 @@ async fn write_config_files(project_root: &Path, config: &ProjectConfig) -> Resu
     let fvmrc_json = serde_json::to_string_pretty(&config)
         .context("Failed to serialize .fvmrc config")?;
 
+    // Written atomically, so an interrupted write never leaves a truncated config
     debug!("Writing .fvmrc to: {}", fvmrc_path.display());
-    fs::write(&fvmrc_path, fvmrc_json)
-        .await
-        .context("Failed to write .fvmrc")?;
+    utils::write_file_atomic(&fvmrc_path, fvmrc_json).context("Failed to write .fvmrc")?;
 
     // Write .fvm/fvm_config.json (legacy format)
     let fvm_dir = project_root.join(".fvm");
@@ -155,14 +267,20 @@ async fn write_config_files(project_root: &Path, config: &ProjectConfig) -> Resu
         .await
         .context("Failed to create .fvm directory")?;
 
+    // Keep unknown keys other tools stored in the legacy file
     let legacy_path = fvm_dir.join("fvm_config.json");
-    let legacy_config = config.to_legacy();
+    let legacy_extra = read_legacy_file(project_root)
+        .await
+        .ok()
+        .flatten()
+        .map(|legacy| legacy.extra)
+        .unwrap_or_default();
+    let legacy_config = config.to_legacy(legacy_extra);
     let legacy_json = serde_json::to_string_pretty(&legacy_config)
         .context("Failed to serialize legacy config")?;
 
     debug!("Writing legacy config to: {}", legacy_path.display());
-    fs::write(&legacy_path, legacy_json)
-        .await
+    utils::write_file_atomic(&legacy_path, legacy_json)
         .context("Failed to write .fvm/fvm_config.json")?;
 
     Ok(())
@@ -174,38 +292,243 @@ async fn write_config_files(project_root: &Path, config: &ProjectConfig) -> Resu
 /// Returns None if no config file is found.
 pub async fn read_project_config(project_root: &Path) -> Result<Option<ProjectConfig>> {
     // Try .fvmrc first (primary format)
+    if let Some(config) = read_fvmrc(project_root).await? {
+        return Ok(Some(config));
+    }
+
+    // Fall back to .fvm/fvm_config.json (legacy format)
+    if let Some(config) = read_legacy_config(project_root).await? {
+        return Ok(Some(config));
+    }
+
+    // No config found
+    debug!("No FVM config found in: {}", project_root.display());
+    Ok(None)
+}
+
+async fn read_fvmrc(project_root: &Path) -> Result<Option<ProjectConfig>> {
     let fvmrc_path = project_root.join(".fvmrc");
-    if fvmrc_path.exists() {
-        debug!("Reading config from: {}", fvmrc_path.display());
-        let contents = fs::read_to_string(&fvmrc_path)
-            .await
-            .context("Failed to read .fvmrc")?;
+    if !fvmrc_path.exists() {
+        return Ok(None);
+    }
+
+    debug!("Reading config from: {}", fvmrc_path.display());
+    let contents = fs::read_to_string(&fvmrc_path)
+        .await
+        .context("Failed to read .fvmrc")?;
 
-        let config: ProjectConfig = serde_json::from_str(&contents)
-            .context("Failed to parse .fvmrc")?;
+    let config: ProjectConfig = serde_json::from_str(&contents)
+        .context("Failed to parse .fvmrc")?;
+
+    Ok(Some(config))
+}
 
+async fn read_legacy_config(project_root: &Path) -> Result<Option<ProjectConfig>> {
+    Ok(read_legacy_file(project_root)
+        .await?
+        .map(ProjectConfig::from_legacy))
+}
+
+async fn read_legacy_file(project_root: &Path) -> Result<Option<LegacyProjectConfig>> {
+    let legacy_path = project_root.join(".fvm/fvm_config.json");
+    if !legacy_path.exists() {
+        return Ok(None);
+    }
+
+    debug!("Reading legacy config from: {}", legacy_path.display());
+    let contents = fs::read_to_string(&legacy_path)
+        .await
+        .context("Failed to read .fvm/fvm_config.json")?;
+
+    let legacy_config: LegacyProjectConfig = serde_json::from_str(&contents)
+        .context("Failed to parse .fvm/fvm_config.json")?;
+
+    Ok(Some(legacy_config))
+}
+
+/// Read project configuration without an async runtime
+///
+/// Same lookup as `read_project_config`, for synchronous callers such as shell
+/// completion.
+pub fn read_project_config_blocking(project_root: &Path) -> Result<Option<ProjectConfig>> {
+    let fvmrc_path = project_root.join(".fvmrc");
+    if fvmrc_path.exists() {
+        let contents = std::fs::read_to_string(&fvmrc_path).context("Failed to read .fvmrc")?;
+        let config = serde_json::from_str(&contents).context("Failed to parse .fvmrc")?;
         return Ok(Some(config));
     }
 
-    // Fall back to .fvm/fvm_config.json (legacy format)
     let legacy_path = project_root.join(".fvm/fvm_config.json");
     if legacy_path.exists() {
-        debug!("Reading legacy config from: {}", legacy_path.display());
-        let contents = fs::read_to_string(&legacy_path)
-            .await
+        let contents = std::fs::read_to_string(&legacy_path)
             .context("Failed to read .fvm/fvm_config.json")?;
-
-        let legacy_config: LegacyProjectConfig = serde_json::from_str(&contents)
+        let legacy: LegacyProjectConfig = serde_json::from_str(&contents)
             .context("Failed to parse .fvm/fvm_config.json")?;
+        return Ok(Some(ProjectConfig::from_legacy(legacy)));
+    }
+
+    Ok(None)
+}
+
+/// Get the fvm-rs version that last wrote the project config
+///
+/// Recorded in .fvm/fvm_config.json only. Returns None for configs written by
+/// FVM or by fvm-rs versions that predate the field.
+pub async fn config_written_by(project_root: &Path) -> Result<Option<String>> {
+    Ok(read_legacy_file(project_root)
+        .await?
+        .and_then(|legacy| legacy.fvm_rs_version))
+}
+
+/// Read the Dart SDK constraint (`environment.sdk`) from pubspec.yaml
+///
+/// Only the top-level `environment` block is read, which is all this needs
+/// without a YAML parser. Returns None if pubspec.yaml doesn't declare one.
+pub async fn read_pubspec_sdk_constraint(project_root: &Path) -> Result<Option<String>> {
+    let pubspec_path = project_root.join("pubspec.yaml");
+    let contents = fs::read_to_string(&pubspec_path)
+        .await
+        .with_context(|| format!("Failed to read {}", pubspec_path.display()))?;
+
+    let mut in_environment = false;
+    for line in contents.lines() {
+        let line = line.split(" #").next().unwrap_or_default().trim_end();
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
 
-        return Ok(Some(ProjectConfig::from_legacy(legacy_config)));
+        // A non-indented line starts the next top-level key
+        if !line.starts_with([' ', '\t']) {
+            in_environment = line == "environment:";
+            continue;
+        }
+
+        if in_environment && let Some(constraint) = line.trim().strip_prefix("sdk:") {
+            let constraint = constraint.trim().trim_matches(['"', '\'']);
+            debug!("Found Dart SDK constraint in pubspec.yaml: {}", constraint);
+            return Ok(Some(constraint.to_string()));
+        }
     }
 
-    // No config found
-    debug!("No FVM config found in: {}", project_root.display());
     Ok(None)
 }
 
+/// A value that differs between .fvmrc and .fvm/fvm_config.json
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigMismatch {
+    /// "flutter" or "flavors.<name>"
+    pub field: String,
+    pub fvmrc: Option<String>,
+    pub legacy: Option<String>,
+}
+
+/// Compare .fvmrc with .fvm/fvm_config.json when both exist
+///
+/// Only versions are compared; dart-defines are not part of the legacy format.
+/// Returns an empty list if either file is missing or both agree.
+pub async fn find_config_mismatches(project_root: &Path) -> Result<Vec<ConfigMismatch>> {
+    let (Some(fvmrc), Some(legacy)) = (
+        read_fvmrc(project_root).await?,
+        read_legacy_config(project_root).await?,
+    ) else {
+        return Ok(Vec::new());
+    };
+
+    let mismatches = compare_configs(Some(&fvmrc), &legacy, false)
+        .into_iter()
+        .map(|(field, fvmrc, legacy)| ConfigMismatch { field, fvmrc, legacy })
+        .collect();
+
+    Ok(mismatches)
+}
+
+/// A value changed by an update to the project config
+pub struct ConfigChange {
+    /// "flutter", "flavors.<name>" or "flavors.<name>.<DEFINE>"
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// Describe what changed between two versions of a project config
+///
+/// `old` is None when the project had no config yet.
+pub fn diff_project_configs(old: Option<&ProjectConfig>, new: &ProjectConfig) -> Vec<ConfigChange> {
+    compare_configs(old, new, true)
+        .into_iter()
+        .map(|(field, old, new)| ConfigChange { field, old, new })
+        .collect()
+}
+
+/// List (field, left value, right value) for every field that differs
+fn compare_configs(
+    left: Option<&ProjectConfig>,
+    right: &ProjectConfig,
+    include_defines: bool,
+) -> Vec<(String, Option<String>, Option<String>)> {
+    let mut differences = Vec::new();
+
+    let left_flutter = left.map(|config| config.flutter.clone()).filter(|v| !v.is_empty());
+    if left_flutter.as_deref() != Some(right.flutter.as_str()) {
+        differences.push(("flutter".to_string(), left_flutter, Some(right.flutter.clone())));
+    }
+
+    let flavor = |config: Option<&ProjectConfig>, name: &str| {
+        config?.flavors.as_ref()?.get(name).cloned()
+    };
+
+    let mut names: Vec<&String> = left
+        .and_then(|config| config.flavors.as_ref())
+        .into_iter()
+        .chain(right.flavors.iter())
+        .flat_map(|flavors| flavors.keys())
+        .collect();
+    names.sort();
+    names.dedup();
+
+    for name in names {
+        let (left_flavor, right_flavor) = (flavor(left, name), flavor(Some(right), name));
+
+        let left_version = left_flavor.as_ref().map(|f| f.version().to_string());
+        let right_version = right_flavor.as_ref().map(|f| f.version().to_string());
+        if left_version != right_version {
+            differences.push((format!("flavors.{}", name), left_version, right_version));
+        }
+
+        if !include_defines {
+            continue;
+        }
+
+        let left_defines = left_flavor.map(|f| f.dart_defines()).unwrap_or_default();
+        let right_defines = right_flavor.map(|f| f.dart_defines()).unwrap_or_default();
+        let mut keys: Vec<&String> = left_defines.keys().chain(right_defines.keys()).collect();
+        keys.sort();
+        keys.dedup();
+
+        for key in keys {
+            let (left_value, right_value) = (left_defines.get(key), right_defines.get(key));
+            if left_value != right_value {
+                differences.push((
+                    format!("flavors.{}.{}", name, key),
+                    left_value.cloned(),
+                    right_value.cloned(),
+                ));
+            }
+        }
+    }
+
+    differences
+}
+
+/// Rewrite both config files from the preferred source (.fvmrc, else legacy)
+pub async fn sync_project_config_files(project_root: &Path) -> Result<()> {
+    let config = read_project_config(project_root)
+        .await?
+        .context("No FVM config found in this project")?;
+
+    write_config_files(project_root, &config).await
+}
+
 /// Get the Flutter version for the current project
 ///
 /// Searches for FVM config starting from the current directory and walking up.
@@ -228,12 +551,8 @@ pub async fn get_project_flutter_version() -> Result<Option<String>> {
 ///
 /// Returns the version name if a global version is configured.
 pub async fn get_global_flutter_version() -> Result<Option<String>> {
-    // Get home directory
-    let home = dirs::home_dir()
-        .context("Failed to get home directory")?;
-
     // Check ~/.fvm-rs/default first (takes precedence)
-    let fvm_rs_default = home.join(".fvm-rs/default");
+    let fvm_rs_default = utils::get_global_link_path()?;
     if let Ok(target) = tokio::fs::read_link(&fvm_rs_default).await {
         debug!("Found global version at: {}", fvm_rs_default.display());
 
@@ -247,8 +566,9 @@ pub async fn get_global_flutter_version() -> Result<Option<String>> {
     }
 
     // Fall back to ~/.fvm/default (for compatibility with original FVM)
-    let fvm_default = home.join(".fvm/default");
-    if let Ok(target) = tokio::fs::read_link(&fvm_default).await {
+    if let Some(fvm_default) = dirs::home_dir().map(|home| home.join(".fvm/default"))
+        && let Ok(target) = tokio::fs::read_link(&fvm_default).await
+    {
         debug!("Found global version at: {}", fvm_default.display());
 
         // Extract version name from symlink target
@@ -271,6 +591,10 @@ pub fn is_channel(version: &str) -> bool {
     matches!(version, "stable" | "beta" | "master" | "dev")
 }
 
+/// Error shown when upgrading a release version, which is pinned by definition
+pub const RELEASE_UPGRADE_ERROR: &str = "You should not upgrade a release version. \
+    Please install a channel (stable, beta, master) instead to upgrade it.";
+
 /// Check if the user is trying to run `flutter upgrade` and protect against it
 ///
 /// This prevents users from accidentally upgrading a pinned release version,
@@ -301,10 +625,7 @@ pub async fn check_flutter_upgrade(args: &[String]) -> Result<()> {
 
         // Only allow upgrade for channel versions
         if !is_channel(&version_name) {
-            anyhow::bail!(
-                "You should not upgrade a release version. \
-                Please install a channel (stable, beta, master) instead to upgrade it."
-            );
+            anyhow::bail!(RELEASE_UPGRADE_ERROR);
         }
 
         debug!("Version is a channel, upgrade allowed");
@@ -319,9 +640,18 @@ pub async fn check_flutter_upgrade(args: &[String]) -> Result<()> {
 ///
 /// Returns the directory containing .fvmrc or .fvm/fvm_config.json, or None if not found.
 pub async fn find_project_root() -> Result<Option<PathBuf>> {
-    let mut current = std::env::current_dir()
+    let current = std::env::current_dir()
         .context("Failed to get current directory")?;
 
+    find_project_root_from(&current).await
+}
+
+/// Find the project root by walking up from `start` looking for FVM config
+///
+/// Like `find_project_root`, but starts from an arbitrary directory.
+pub async fn find_project_root_from(start: &Path) -> Result<Option<PathBuf>> {
+    let mut current = start.to_path_buf();
+
     loop {
         debug!("Checking for FVM config in: {}", current.display());
 
@@ -345,6 +675,55 @@ pub async fn find_project_root() -> Result<Option<PathBuf>> {
     }
 }
 
+/// A set of Flutter versions to provision, read by `install --from-manifest`
+///
+/// Example `fvm-rs.versions.json`:
+/// `{"versions": ["3.24.0", "3.19.6", "stable"], "global": "3.24.0"}`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VersionsManifest {
+    pub versions: Vec<String>,
+    /// Version to set as the global default once everything is installed
+    #[serde(default)]
+    pub global: Option<String>,
+}
+
+impl VersionsManifest {
+    /// Versions to install in manifest order, without duplicates and including the global
+    pub fn versions_to_install(&self) -> Vec<String> {
+        let mut versions: Vec<String> = vec![];
+        for version in self.versions.iter().chain(self.global.as_ref()) {
+            if !versions.contains(version) {
+                versions.push(version.clone());
+            }
+        }
+        versions
+    }
+}
+
+/// Read and validate a versions manifest
+pub async fn read_versions_manifest(path: &Path) -> Result<VersionsManifest> {
+    debug!("Reading versions manifest from: {}", path.display());
+    let contents = fs::read_to_string(path)
+        .await
+        .with_context(|| format!("Failed to read manifest {}", path.display()))?;
+    let manifest: VersionsManifest = serde_json::from_str(&contents)
+        .with_context(|| format!("Invalid manifest {}", path.display()))?;
+
+    if manifest.versions_to_install().is_empty() {
+        anyhow::bail!("Manifest {} lists no versions", path.display());
+    }
+    if let Some(version) = manifest
+        .versions_to_install()
+        .iter()
+        .find(|v| v.trim().is_empty())
+    {
+        anyhow::bail!("Manifest {} contains an empty version {:?}", path.display(), version);
+    }
+
+    Ok(manifest)
+}
+
 /// Global configuration for fvm-rs
 ///
 /// Stored in ~/.fvm-rs/.fvmrc on all platforms
@@ -382,6 +761,40 @@ pub struct GlobalConfig {
     /// Custom Flutter repository forks
     #[serde(skip_serializing_if = "Option::is_none")]
     pub forks: Option<Vec<Fork>>,
+
+    /// HTTP(S) proxy URL used for downloads and git operations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+
+    /// Override for the engine platform string (e.g. "linux-arm64")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub engine_platform: Option<String>,
+
+    /// Give each Flutter version its own PUB_CACHE managed by fvm-rs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scoped_pub_cache: Option<bool>,
+
+    /// Base directory for version-scoped pub caches
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pub_cache_path: Option<String>,
+
+    /// Maximum attempts for HTTP requests that fail with connection errors or 5xx
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_retries: Option<u32>,
+
+    /// Minutes the releases feed cached on disk is used before fetching it again
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub releases_cache_ttl: Option<u64>,
+
+    /// Flutter storage mirror (e.g. "https://storage.flutter-io.cn") for the
+    /// releases feed and engine archives
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_base_url: Option<String>,
+
+    /// Fetch only the needed channel branch and tag, without history, into the
+    /// shared repository
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shallow_clone: Option<bool>,
 }
 
 impl GlobalConfig {
@@ -407,46 +820,80 @@ impl GlobalConfig {
         Ok(config)
     }
 
-    /// Save global config to disk
-    pub async fn save(&self) -> Result<()> {
+    /// Read global config from disk without an async runtime
+    ///
+    /// For synchronous callers such as subprocess setup in `utils`.
+    pub fn read_blocking() -> Result<Self> {
         let config_path = utils::get_global_config_path()?;
 
-        // Create parent directory if needed
-        if let Some(parent) = config_path.parent() {
-            if !parent.exists() {
-                debug!("Creating config directory: {}", parent.display());
-                fs::create_dir_all(parent)
-                    .await
-                    .context("Failed to create config directory")?;
-            }
+        if !config_path.exists() {
+            return Ok(Self::default());
         }
 
-        let json = serde_json::to_string_pretty(self)
-            .context("Failed to serialize global config")?;
+        let contents = std::fs::read_to_string(&config_path)
+            .context("Failed to read global config")?;
 
-        debug!("Writing global config to: {}", config_path.display());
-        fs::write(&config_path, json)
-            .await
-            .context("Failed to write global config")?;
+        serde_json::from_str(&contents).context("Failed to parse global config")
+    }
 
-        Ok(())
+    /// Modify the global config under an exclusive file lock
+    ///
+    /// The config is re-read after the lock is acquired and written before it is
+    /// released, so concurrent fvm-rs processes don't overwrite each other's changes.
+    pub async fn update<T, F>(modify: F) -> Result<T>
+    where
+        F: FnOnce(&mut GlobalConfig) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        tokio::task::spawn_blocking(move || {
+            let config_path = utils::get_global_config_path()?;
+
+            if let Some(parent) = config_path.parent() {
+                std::fs::create_dir_all(parent).context("Failed to create config directory")?;
+            }
+
+            let lock_path = config_path.with_file_name(".fvmrc.lock");
+            let lock_file = std::fs::OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&lock_path)
+                .with_context(|| format!("Failed to open {}", lock_path.display()))?;
+
+            debug!("Waiting for global config lock: {}", lock_path.display());
+            lock_file.lock().context("Failed to lock global config")?;
+
+            let mut config = Self::read_blocking()?;
+            let result = modify(&mut config)?;
+
+            let json = serde_json::to_string_pretty(&config)
+                .context("Failed to serialize global config")?;
+
+            // Written atomically, so an interrupted write never loses the fork definitions
+            debug!("Writing global config to: {}", config_path.display());
+            utils::write_file_atomic(&config_path, json).context("Failed to write global config")?;
+
+            // The lock is released when lock_file is dropped
+            Ok(result)
+        })
+        .await?
     }
 
     /// Get cache path with fallback to env var and default
     pub fn get_cache_path(&self) -> Result<PathBuf> {
         // Priority: config file -> FVM_CACHE_PATH env -> FVM_HOME env -> default
         if let Some(path) = &self.cache_path {
-            return Ok(PathBuf::from(path));
+            return utils::expand_path(path);
         }
 
         if let Ok(path) = std::env::var("FVM_CACHE_PATH") {
             debug!("Using cache path from FVM_CACHE_PATH: {}", path);
-            return Ok(PathBuf::from(path));
+            return utils::expand_path(&path);
         }
 
         if let Ok(path) = std::env::var("FVM_HOME") {
             debug!("Using cache path from FVM_HOME (legacy): {}", path);
-            return Ok(PathBuf::from(path));
+            return utils::expand_path(&path);
         }
 
         // Default: ~/.fvm-rs
@@ -471,12 +918,12 @@ impl GlobalConfig {
     pub fn get_git_cache_path(&self) -> Result<PathBuf> {
         // Priority: config file -> FVM_GIT_CACHE_PATH env -> default (cache_path/shared/flutter)
         if let Some(path) = &self.git_cache_path {
-            return Ok(PathBuf::from(path));
+            return utils::expand_path(path);
         }
 
         if let Ok(path) = std::env::var("FVM_GIT_CACHE_PATH") {
             debug!("Using git cache path from FVM_GIT_CACHE_PATH: {}", path);
-            return Ok(PathBuf::from(path));
+            return utils::expand_path(&path);
         }
 
         // Default: {cache_path}/shared/flutter
@@ -504,6 +951,138 @@ impl GlobalConfig {
         "https://github.com/flutter/flutter.git".to_string()
     }
 
+    /// Get proxy URL with fallback to the standard proxy env vars
+    pub fn get_proxy(&self) -> Option<String> {
+        // Priority: config file -> HTTPS_PROXY -> HTTP_PROXY -> ALL_PROXY (either case)
+        if let Some(proxy) = &self.proxy {
+            return Some(proxy.clone());
+        }
+
+        for name in ["HTTPS_PROXY", "https_proxy", "HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"] {
+            if let Ok(proxy) = std::env::var(name)
+                && !proxy.is_empty()
+            {
+                debug!("Using proxy from {}: {}", name, proxy);
+                return Some(proxy);
+            }
+        }
+
+        None
+    }
+
+    /// Get engine platform override with fallback to env var
+    pub fn get_engine_platform(&self) -> Option<String> {
+        // Priority: config file -> FVM_ENGINE_PLATFORM env -> None (auto-detect)
+        if let Some(platform) = &self.engine_platform {
+            return Some(platform.clone());
+        }
+
+        if let Ok(platform) = std::env::var("FVM_ENGINE_PLATFORM") {
+            debug!("Using engine platform from FVM_ENGINE_PLATFORM: {}", platform);
+            return Some(platform);
+        }
+
+        None
+    }
+
+    /// Get scoped pub cache enabled status with fallback to env var and default
+    pub fn get_scoped_pub_cache(&self) -> bool {
+        // Priority: config file -> FVM_SCOPED_PUB_CACHE env -> default (false)
+        if let Some(value) = self.scoped_pub_cache {
+            return value;
+        }
+
+        if let Ok(value) = std::env::var("FVM_SCOPED_PUB_CACHE") {
+            return value.to_lowercase() == "true" || value == "1";
+        }
+
+        false // Default: use the user's own PUB_CACHE
+    }
+
+    /// Get the base directory for scoped pub caches with fallback to default
+    pub fn get_pub_cache_path(&self) -> Result<PathBuf> {
+        // Priority: config file -> default (cache_path/pub-cache)
+        if let Some(path) = &self.pub_cache_path {
+            return utils::expand_path(path);
+        }
+
+        Ok(self.get_cache_path()?.join("pub-cache"))
+    }
+
+    /// Get the maximum HTTP request attempts with fallback to env var and default
+    pub fn get_http_retries(&self) -> u32 {
+        // Priority: config file -> FVM_HTTP_RETRIES env -> default (3)
+        if let Some(retries) = self.http_retries {
+            return retries.max(1);
+        }
+
+        if let Ok(value) = std::env::var("FVM_HTTP_RETRIES")
+            && let Ok(retries) = value.trim().parse::<u32>()
+        {
+            debug!("Using HTTP retries from FVM_HTTP_RETRIES: {}", retries);
+            return retries.max(1);
+        }
+
+        3
+    }
+
+    /// Get how long the cached releases feed stays fresh, with fallback to env var and default
+    pub fn get_releases_cache_ttl(&self) -> Duration {
+        // Priority: config file -> FVM_RELEASES_CACHE_TTL env (minutes) -> default (60)
+        if let Some(minutes) = self.releases_cache_ttl {
+            return Duration::from_secs(minutes.saturating_mul(60));
+        }
+
+        if let Ok(value) = std::env::var("FVM_RELEASES_CACHE_TTL")
+            && let Ok(minutes) = value.trim().parse::<u64>()
+        {
+            debug!("Using releases cache TTL from FVM_RELEASES_CACHE_TTL: {} minute(s)", minutes);
+            return Duration::from_secs(minutes.saturating_mul(60));
+        }
+
+        Duration::from_secs(60 * 60)
+    }
+
+    /// Get the storage mirror base URL, if one is configured
+    ///
+    /// None means the default hosts (storage.googleapis.com and
+    /// raw.githubusercontent.com) are used.
+    pub fn get_storage_base_url(&self) -> Option<String> {
+        // Priority: config file -> FVM_STORAGE_BASE_URL -> FLUTTER_STORAGE_BASE_URL -> None
+        if let Some(url) = &self.storage_base_url {
+            return Some(url.trim_end_matches('/').to_string());
+        }
+
+        for name in ["FVM_STORAGE_BASE_URL", "FLUTTER_STORAGE_BASE_URL"] {
+            if let Ok(url) = std::env::var(name)
+                && !url.is_empty()
+            {
+                debug!("Using storage base URL from {}: {}", name, url);
+                return Some(url.trim_end_matches('/').to_string());
+            }
+        }
+
+        None
+    }
+
+    /// Get shallow clone status with fallback to env var and default
+    ///
+    /// Shallow installs are faster the first time, but each other channel or
+    /// release then needs its own fetch, and Flutter can't describe channel
+    /// checkouts without their history.
+    pub fn get_shallow_clone(&self) -> bool {
+        // Priority: config file -> FVM_SHALLOW_CLONE env -> default (false)
+        if let Some(value) = self.shallow_clone {
+            return value;
+        }
+
+        if let Ok(value) = std::env::var("FVM_SHALLOW_CLONE") {
+            return value.to_lowercase() == "true" || value == "1";
+        }
+
+        false
+    }
+
     /// Get update check enabled status
     pub fn get_update_check_enabled(&self) -> bool {
         // If disable_update_check is Some(true), return false (disabled)
@@ -521,15 +1100,31 @@ impl GlobalConfig {
             && self.update_vscode_settings.is_none()
             && self.update_gitignore.is_none()
             && self.forks.is_none()
+            && self.proxy.is_none()
+            && self.engine_platform.is_none()
+            && self.scoped_pub_cache.is_none()
+            && self.pub_cache_path.is_none()
+            && self.http_retries.is_none()
+            && self.releases_cache_ttl.is_none()
+            && self.storage_base_url.is_none()
+            && self.shallow_clone.is_none()
+    }
+
+    /// Get the names of the settings stored in the config file (e.g. "cachePath")
+    pub fn configured_keys(&self) -> Vec<String> {
+        match serde_json::to_value(self) {
+            Ok(serde_json::Value::Object(map)) => map.keys().cloned().collect(),
+            _ => Vec::new(),
+        }
     }
 
     /// Add a new fork to the configuration
     pub fn add_fork(&mut self, name: String, url: String) -> Result<()> {
         // Check if fork already exists
-        if let Some(forks) = &self.forks {
-            if forks.iter().any(|f| f.name == name) {
-                anyhow::bail!("Fork '{}' already exists", name);
-            }
+        if let Some(forks) = &self.forks
+            && forks.iter().any(|f| f.name == name)
+        {
+            anyhow::bail!("Fork '{}' already exists", name);
         }
 
         // Add the fork
@@ -564,6 +1159,40 @@ impl GlobalConfig {
         }
     }
 
+    /// Change the URL of an existing fork, returning the previous URL
+    pub fn update_fork(&mut self, name: &str, url: String) -> Result<String> {
+        let fork = self
+            .forks
+            .as_mut()
+            .and_then(|forks| forks.iter_mut().find(|f| f.name == name))
+            .with_context(|| format!("Fork '{}' not found", name))?;
+
+        Ok(std::mem::replace(&mut fork.url, url))
+    }
+
+    /// Rename a fork, replacing a fork already named `new_name` only if `overwrite` is set
+    pub fn rename_fork(&mut self, name: &str, new_name: String, overwrite: bool) -> Result<()> {
+        let Some(forks) = &mut self.forks else {
+            anyhow::bail!("Fork '{}' not found", name);
+        };
+        if !forks.iter().any(|f| f.name == name) {
+            anyhow::bail!("Fork '{}' not found", name);
+        }
+
+        if forks.iter().any(|f| f.name == new_name) {
+            if !overwrite {
+                anyhow::bail!("Fork '{}' already exists", new_name);
+            }
+            forks.retain(|f| f.name != new_name);
+        }
+
+        if let Some(fork) = forks.iter_mut().find(|f| f.name == name) {
+            fork.name = new_name;
+        }
+
+        Ok(())
+    }
+
     /// Get the URL for a fork by name
     pub fn get_fork_url(&self, name: &str) -> Option<String> {
         self.forks.as_ref()?.iter()
@@ -576,3 +1205,125 @@ impl GlobalConfig {
         self.forks.clone().unwrap_or_default()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn use_keeps_unknown_fvmrc_keys() {
+        let project = tempfile::tempdir().unwrap();
+        std::fs::write(
+            project.path().join(".fvmrc"),
+            r#"{ "flutter": "3.0.0", "runPubGetOnSdkChanges": false, "myTool": { "level": 2 } }"#,
+        )
+        .unwrap();
+
+        update_project_config(project.path(), Some("3.24.0"), None, None)
+            .await
+            .unwrap();
+
+        let written: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(project.path().join(".fvmrc")).unwrap())
+                .unwrap();
+        assert_eq!(written["flutter"], "3.24.0");
+        assert_eq!(written["runPubGetOnSdkChanges"], false);
+        assert_eq!(written["myTool"]["level"], 2);
+    }
+
+    async fn pubspec_sdk_constraint(pubspec: &str) -> Result<Option<String>> {
+        let project = tempfile::tempdir().unwrap();
+        std::fs::write(project.path().join("pubspec.yaml"), pubspec).unwrap();
+        read_pubspec_sdk_constraint(project.path()).await
+    }
+
+    #[tokio::test]
+    async fn pubspec_sdk_constraint_from_environment() {
+        let caret = pubspec_sdk_constraint("name: app\nenvironment:\n  sdk: ^3.4.0\n").await;
+        assert_eq!(caret.unwrap().as_deref(), Some("^3.4.0"));
+
+        let range = pubspec_sdk_constraint(
+            "name: app\n\nenvironment:\n  # Dart 3 only\n  sdk: \">=3.0.0 <4.0.0\" # bumped in May\n  flutter: \">=3.10.0\"\n",
+        )
+        .await;
+        assert_eq!(range.unwrap().as_deref(), Some(">=3.0.0 <4.0.0"));
+
+        let any = pubspec_sdk_constraint("environment:\n  sdk: 'any'\n").await;
+        assert_eq!(any.unwrap().as_deref(), Some("any"));
+    }
+
+    #[tokio::test]
+    async fn pubspec_without_sdk_constraint() {
+        // `sdk: flutter` under dependencies is not the Dart SDK constraint
+        let pubspec = "name: app\nenvironment:\n  flutter: \">=3.10.0\"\ndependencies:\n  flutter:\n    sdk: flutter\n";
+        assert_eq!(pubspec_sdk_constraint(pubspec).await.unwrap(), None);
+
+        assert_eq!(pubspec_sdk_constraint("name: app\n").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn malformed_pubspec_has_no_sdk_constraint() {
+        assert_eq!(pubspec_sdk_constraint("environment: [sdk\n\t::: }").await.unwrap(), None);
+        assert_eq!(pubspec_sdk_constraint("\u{0}\u{1}not yaml at all").await.unwrap(), None);
+
+        // A missing pubspec.yaml is an error, not an absent constraint
+        let project = tempfile::tempdir().unwrap();
+        assert!(read_pubspec_sdk_constraint(project.path()).await.is_err());
+    }
+
+    fn project_config(json: &str) -> ProjectConfig {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn compare_configs_lists_changed_versions_and_defines() {
+        let old = project_config(
+            r#"{ "flutter": "3.22.0", "flavors": { "dev": { "version": "3.22.0", "dartDefines": { "API": "dev" } }, "prod": "3.19.6" } }"#,
+        );
+        let new = project_config(
+            r#"{ "flutter": "3.24.0", "flavors": { "dev": { "version": "3.22.0", "dartDefines": { "API": "staging" } }, "qa": "3.24.0" } }"#,
+        );
+
+        let differences = compare_configs(Some(&old), &new, true);
+        let some = |value: &str| Some(value.to_string());
+        assert_eq!(
+            differences,
+            vec![
+                ("flutter".to_string(), some("3.22.0"), some("3.24.0")),
+                ("flavors.dev.API".to_string(), some("dev"), some("staging")),
+                ("flavors.prod".to_string(), some("3.19.6"), None),
+                ("flavors.qa".to_string(), None, some("3.24.0")),
+            ]
+        );
+
+        // Without defines only versions count, and identical configs don't differ
+        assert_eq!(compare_configs(Some(&old), &new, false).len(), 3);
+        assert!(compare_configs(Some(&new), &new, true).is_empty());
+        // Without a previous config every field is new
+        assert_eq!(compare_configs(None, &old, true).len(), 4);
+    }
+
+    #[tokio::test]
+    async fn fvmrc_and_legacy_config_mismatches() {
+        let project = tempfile::tempdir().unwrap();
+        std::fs::write(
+            project.path().join(".fvmrc"),
+            r#"{ "flutter": "3.24.0", "flavors": { "dev": { "version": "3.22.0", "dartDefines": { "API": "dev" } } } }"#,
+        )
+        .unwrap();
+        std::fs::create_dir(project.path().join(".fvm")).unwrap();
+        std::fs::write(
+            project.path().join(".fvm/fvm_config.json"),
+            r#"{ "flutterSdkVersion": "3.19.6", "flavors": { "dev": "3.22.0" } }"#,
+        )
+        .unwrap();
+
+        let mismatches = find_config_mismatches(project.path()).await.unwrap();
+
+        // dart-defines only exist in .fvmrc, so the dev flavor agrees
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].field, "flutter");
+        assert_eq!(mismatches[0].fvmrc.as_deref(), Some("3.24.0"));
+        assert_eq!(mismatches[0].legacy.as_deref(), Some("3.19.6"));
+    }
+}
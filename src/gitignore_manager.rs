@@ -89,3 +89,41 @@ pub async fn update_project_gitignore(project_root: &Path) -> Result<()> {
 
     Ok(())
 }
+
+/// Count files under `relative_dir` that are tracked in the project's git index
+///
+/// Returns 0 when the project is not inside a git repository.
+pub async fn count_tracked_files(project_root: &Path, relative_dir: &str) -> Result<usize> {
+    let project_root = project_root.to_path_buf();
+    let relative_dir = relative_dir.to_string();
+
+    tokio::task::spawn_blocking(move || {
+        let Ok(repo) = git2::Repository::discover(&project_root) else {
+            debug!("{} is not in a git repository", project_root.display());
+            return Ok(0);
+        };
+        let Some(workdir) = repo.workdir() else {
+            return Ok(0);
+        };
+
+        // Index paths are relative to the repository root, with forward slashes
+        let prefix = project_root
+            .canonicalize()?
+            .strip_prefix(workdir.canonicalize()?)
+            .unwrap_or(Path::new(""))
+            .join(&relative_dir)
+            .to_string_lossy()
+            .replace('\\', "/");
+        let prefix = format!("{}/", prefix.trim_end_matches('/'));
+
+        let index = repo.index().context("Failed to read git index")?;
+        let count = index
+            .iter()
+            .filter(|entry| entry.path.starts_with(prefix.as_bytes()))
+            .count();
+
+        debug!("{} tracked file(s) under {}", count, prefix);
+        Ok(count)
+    })
+    .await?
+}
@@ -1,6 +1,7 @@
 use anyhow::{Context, Result};
+use serde::Serialize;
 use serde_json::{json, Value};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tokio::fs;
 use tracing::debug;
 
@@ -26,8 +27,7 @@ pub async fn update_vscode_settings(project_root: &Path) -> Result<()> {
             .context("Failed to read .vscode/settings.json")?;
 
         debug!("Found existing VS Code settings, merging");
-        serde_json::from_str(&contents)
-            .context("Failed to parse .vscode/settings.json")?
+        parse_json_for_update(&contents, ".vscode/settings.json")?
     } else {
         debug!("Creating new VS Code settings");
         json!({})
@@ -86,8 +86,11 @@ pub async fn update_vscode_workspace(project_root: &Path) -> Result<()> {
             .await
             .context("Failed to read .code-workspace file")?;
 
-        let mut workspace: Value = serde_json::from_str(&contents)
-            .context("Failed to parse .code-workspace file")?;
+        let file_name = workspace_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut workspace = parse_json_for_update(&contents, &file_name)?;
 
         // Update settings.dart.flutterSdkPath
         if let Some(obj) = workspace.as_object_mut() {
@@ -197,8 +200,7 @@ async fn update_dart_sdk_xml(project_root: &Path) -> Result<()> {
     debug!("Updating Dart_SDK.xml at: {}", dart_sdk_path.display());
 
     // Build the absolute path to the Dart SDK
-    let flutter_sdk_path = project_root.join(".fvm/flutter_sdk");
-    let dart_sdk_full_path = flutter_sdk_path.join("bin/cache/dart-sdk");
+    let dart_sdk_full_path = ide_dart_sdk_path(project_root);
     let dart_sdk_str = dart_sdk_full_path
         .to_str()
         .context("Invalid Dart SDK path")?;
@@ -227,3 +229,380 @@ async fn update_dart_sdk_xml(project_root: &Path) -> Result<()> {
 
     Ok(())
 }
+
+/// A Flutter SDK path configured in one of the project's IDE files
+#[derive(Debug, Clone)]
+pub struct IdeSdkPath {
+    /// File the path was read from, relative to the project root
+    pub source: String,
+    /// The path as written in the file
+    pub raw: String,
+    /// The path resolved against the project root
+    pub resolved: PathBuf,
+}
+
+/// Get the SDK path the IDE files are expected to point at
+pub fn expected_sdk_path(project_root: &Path) -> PathBuf {
+    project_root.join(".fvm/flutter_sdk")
+}
+
+/// Get the Dart SDK path written to .idea/libraries/Dart_SDK.xml
+///
+/// It goes through two symlinks: .fvm/flutter_sdk to the installed version,
+/// then bin/cache/dart-sdk to the shared engine.
+pub fn ide_dart_sdk_path(project_root: &Path) -> PathBuf {
+    expected_sdk_path(project_root).join("bin/cache/dart-sdk")
+}
+
+/// Where the IDE Dart SDK path ends up after following its symlinks
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum DartSdkResolution {
+    /// Resolved into the shared engine cache
+    SharedEngine { resolved: PathBuf, engine_hash: String },
+    /// Resolved, but to a directory outside the shared engine cache
+    Elsewhere(PathBuf),
+    /// A symlink in the chain points at a path that doesn't exist
+    BrokenLink { link: PathBuf, target: PathBuf },
+    /// A path in the chain doesn't exist
+    Missing(PathBuf),
+}
+
+/// Follow the IDE Dart SDK path through its symlink chain
+pub fn resolve_dart_sdk(project_root: &Path) -> Result<DartSdkResolution> {
+    let dart_sdk = ide_dart_sdk_path(project_root);
+
+    // Find the first link or directory in the chain that doesn't resolve
+    let relative = dart_sdk.strip_prefix(project_root)?;
+    let mut current = project_root.to_path_buf();
+    for component in relative.components() {
+        current.push(component);
+        if current.exists() {
+            continue;
+        }
+
+        if let Ok(target) = std::fs::read_link(&current) {
+            debug!("Broken symlink {} -> {}", current.display(), target.display());
+            return Ok(DartSdkResolution::BrokenLink {
+                link: current,
+                target,
+            });
+        }
+        return Ok(DartSdkResolution::Missing(current));
+    }
+
+    let resolved = dart_sdk
+        .canonicalize()
+        .with_context(|| format!("Failed to resolve {}", dart_sdk.display()))?;
+    debug!("Dart SDK resolves to {}", resolved.display());
+
+    let engine_root = crate::utils::shared_dir()?.join("engine");
+    let engine_root = engine_root.canonicalize().unwrap_or(engine_root);
+    let engine_hash = resolved
+        .strip_prefix(&engine_root)
+        .ok()
+        .and_then(|rest| rest.components().next())
+        .map(|c| c.as_os_str().to_string_lossy().to_string());
+
+    Ok(match engine_hash {
+        Some(engine_hash) => DartSdkResolution::SharedEngine {
+            resolved,
+            engine_hash,
+        },
+        None => DartSdkResolution::Elsewhere(resolved),
+    })
+}
+
+impl IdeSdkPath {
+    /// Whether this path points at the project's Flutter SDK
+    ///
+    /// Accepts .fvm/flutter_sdk itself, or any path that resolves to the
+    /// configured version's directory when one is given.
+    pub fn points_at_sdk(&self, project_root: &Path, version_dir: Option<&Path>) -> bool {
+        if self.resolved == expected_sdk_path(project_root) {
+            return true;
+        }
+
+        let Some(version_dir) = version_dir else {
+            return false;
+        };
+        match (self.resolved.canonicalize(), version_dir.canonicalize()) {
+            (Ok(resolved), Ok(version_dir)) => resolved == version_dir,
+            _ => false,
+        }
+    }
+}
+
+/// Read `dart.flutterSdkPath` from .vscode/settings.json
+///
+/// Returns None if the file is missing or doesn't set the path.
+pub async fn vscode_sdk_path(project_root: &Path) -> Result<Option<IdeSdkPath>> {
+    let settings_path = project_root.join(".vscode/settings.json");
+    if !settings_path.exists() {
+        return Ok(None);
+    }
+
+    let contents = fs::read_to_string(&settings_path)
+        .await
+        .context("Failed to read .vscode/settings.json")?;
+    let settings = parse_jsonc(&contents).context("Failed to parse .vscode/settings.json")?;
+
+    let Some(raw) = settings.get("dart.flutterSdkPath").and_then(|v| v.as_str()) else {
+        return Ok(None);
+    };
+    debug!("Found dart.flutterSdkPath in .vscode/settings.json: {}", raw);
+
+    Ok(Some(IdeSdkPath {
+        source: ".vscode/settings.json".to_string(),
+        raw: raw.to_string(),
+        resolved: resolve_ide_path(project_root, raw),
+    }))
+}
+
+/// Read the Flutter SDK root from .idea/libraries/Dart_SDK.xml
+///
+/// The library points at <flutter_sdk>/bin/cache/dart-sdk/lib/core, so the
+/// suffix is stripped. Returns None if the file is missing or has no root url.
+pub async fn dart_sdk_xml_path(project_root: &Path) -> Result<Option<IdeSdkPath>> {
+    let dart_sdk_path = project_root.join(".idea/libraries/Dart_SDK.xml");
+    if !dart_sdk_path.exists() {
+        return Ok(None);
+    }
+
+    let contents = fs::read_to_string(&dart_sdk_path)
+        .await
+        .context("Failed to read Dart_SDK.xml")?;
+
+    let Some(url) = contents
+        .split("url=\"file://")
+        .nth(1)
+        .and_then(|rest| rest.split('"').next())
+    else {
+        return Ok(None);
+    };
+
+    let raw = url
+        .trim_end_matches("/lib/core")
+        .trim_end_matches("/bin/cache/dart-sdk");
+    debug!("Found Flutter SDK in Dart_SDK.xml: {}", raw);
+
+    Ok(Some(IdeSdkPath {
+        source: ".idea/libraries/Dart_SDK.xml".to_string(),
+        raw: raw.to_string(),
+        resolved: resolve_ide_path(project_root, raw),
+    }))
+}
+
+/// Read the Flutter SDK path from every IDE file present in the project
+///
+/// Checks .vscode/settings.json and .code-workspace files (`dart.flutterSdkPath`),
+/// android/local.properties (`flutter.sdk`) and .idea/libraries/Dart_SDK.xml.
+/// Files that are missing or don't set a path are skipped.
+pub async fn read_sdk_paths(project_root: &Path) -> Result<Vec<IdeSdkPath>> {
+    let mut paths = Vec::new();
+
+    // VS Code settings
+    if let Some(sdk_path) = vscode_sdk_path(project_root).await? {
+        paths.push(sdk_path);
+    }
+
+    // VS Code workspace files
+    let mut entries = fs::read_dir(project_root)
+        .await
+        .context("Failed to read project directory")?;
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        if path.extension().and_then(|s| s.to_str()) != Some("code-workspace") {
+            continue;
+        }
+
+        let contents = fs::read_to_string(&path)
+            .await
+            .context("Failed to read .code-workspace file")?;
+        let workspace: Value =
+            parse_jsonc(&contents).context("Failed to parse .code-workspace file")?;
+
+        if let Some(raw) = workspace
+            .get("settings")
+            .and_then(|s| s.get("dart.flutterSdkPath"))
+            .and_then(|v| v.as_str())
+        {
+            let name = entry.file_name().to_string_lossy().to_string();
+            debug!("Found dart.flutterSdkPath in {}: {}", name, raw);
+            paths.push(IdeSdkPath {
+                source: name,
+                raw: raw.to_string(),
+                resolved: resolve_ide_path(project_root, raw),
+            });
+        }
+    }
+
+    // android/local.properties
+    let properties_path = project_root.join("android/local.properties");
+    if properties_path.exists() {
+        let contents = fs::read_to_string(&properties_path)
+            .await
+            .context("Failed to read local.properties")?;
+
+        if let Some(raw) = contents
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("flutter.sdk="))
+        {
+            debug!("Found flutter.sdk in local.properties: {}", raw);
+            paths.push(IdeSdkPath {
+                source: "android/local.properties".to_string(),
+                raw: raw.to_string(),
+                resolved: resolve_ide_path(project_root, raw),
+            });
+        }
+    }
+
+    // .idea/libraries/Dart_SDK.xml
+    if let Some(sdk_path) = dart_sdk_xml_path(project_root).await? {
+        paths.push(sdk_path);
+    }
+
+    Ok(paths)
+}
+
+/// Resolve a path from an IDE file against the project root
+///
+/// IntelliJ writes project-relative paths with a `$PROJECT_DIR$` prefix.
+fn resolve_ide_path(project_root: &Path, raw: &str) -> PathBuf {
+    if let Some(rest) = raw.strip_prefix("$PROJECT_DIR$") {
+        return project_root.join(rest.trim_start_matches('/'));
+    }
+
+    let path = PathBuf::from(raw);
+    if path.is_absolute() {
+        path
+    } else {
+        project_root.join(path)
+    }
+}
+
+/// Parse a VS Code JSON file that is about to be rewritten
+///
+/// Rewriting would drop comments and trailing commas, so files that are only
+/// valid as JSONC are refused with a hint to set the path by hand.
+fn parse_json_for_update(contents: &str, file_name: &str) -> Result<Value> {
+    match serde_json::from_str(contents) {
+        Ok(value) => Ok(value),
+        Err(e) if parse_jsonc(contents).is_ok() => {
+            debug!("{} is JSONC ({}), not rewriting it", file_name, e);
+            anyhow::bail!(
+                "{} contains comments or trailing commas, which rewriting it would remove. \
+                 Set \"dart.flutterSdkPath\": \".fvm/flutter_sdk\" in it manually",
+                file_name
+            )
+        }
+        Err(e) => Err(e).with_context(|| format!("Failed to parse {}", file_name)),
+    }
+}
+
+/// Parse a VS Code JSON file, which may contain comments and trailing commas
+fn parse_jsonc(contents: &str) -> serde_json::Result<Value> {
+    match serde_json::from_str(contents) {
+        Ok(value) => Ok(value),
+        Err(e) => {
+            debug!("Not plain JSON ({}), retrying as JSONC", e);
+            serde_json::from_str(&strip_jsonc(contents))
+        }
+    }
+}
+
+/// Remove comments and trailing commas outside of string literals
+fn strip_jsonc(contents: &str) -> String {
+    let mut out = String::with_capacity(contents.len());
+    let mut chars = contents.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                out.push(c);
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.next_if(|&c| c != '\n').is_some() {}
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut prev = '\0';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            '}' | ']' => {
+                // Drop a trailing comma before the closing bracket
+                let trimmed = out.trim_end().len();
+                if out[..trimmed].ends_with(',') {
+                    out.truncate(trimmed - 1);
+                }
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strip_jsonc_keeps_comment_markers_inside_strings() {
+        let contents = r#"{
+    // The SDK link
+    "dart.flutterSdkPath": ".fvm/flutter_sdk", // relative to the project
+    "url": "https://example.com/*not-a-comment*/",
+    "glob": "**/*.g.dart"
+}"#;
+
+        let value: Value = serde_json::from_str(&strip_jsonc(contents)).unwrap();
+        assert_eq!(value["dart.flutterSdkPath"], ".fvm/flutter_sdk");
+        assert_eq!(value["url"], "https://example.com/*not-a-comment*/");
+        assert_eq!(value["glob"], "**/*.g.dart");
+    }
+
+    #[test]
+    fn strip_jsonc_handles_escaped_quotes() {
+        let contents = r#"{ "title": "say \"hi\" // not a comment", "path": "C:\\sdk\\" /* trailing */ }"#;
+
+        let value: Value = serde_json::from_str(&strip_jsonc(contents)).unwrap();
+        assert_eq!(value["title"], r#"say "hi" // not a comment"#);
+        assert_eq!(value["path"], r"C:\sdk\");
+    }
+
+    #[test]
+    fn strip_jsonc_removes_block_comments_across_lines() {
+        let contents = "{\n  /* Managed by fvm-rs.\n     Don't edit by hand. */\n  \"a\": 1,\n  \"b\": /* inline */ 2\n}";
+
+        let value: Value = serde_json::from_str(&strip_jsonc(contents)).unwrap();
+        assert_eq!(value, json!({ "a": 1, "b": 2 }));
+    }
+
+    #[test]
+    fn strip_jsonc_drops_trailing_commas() {
+        let contents = "{\n  \"folders\": [\n    { \"path\": \".\" },\n  ],\n  \"settings\": { \"a\": \",\", },\n}";
+
+        let value: Value = serde_json::from_str(&strip_jsonc(contents)).unwrap();
+        assert_eq!(value["folders"], json!([{ "path": "." }]));
+        assert_eq!(value["settings"]["a"], ",");
+    }
+}
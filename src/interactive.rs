@@ -0,0 +1,128 @@
+//! Shared interactive pickers for commands that can prompt for a version
+//!
+//! All prompts use the same theme and refuse to block when stdin is not a
+//! terminal, so scripts and CI get an actionable error instead of a hang.
+
+use anyhow::{Context, Result};
+use dialoguer::{Select, theme::ColorfulTheme};
+use std::io::IsTerminal;
+use tracing::{debug, info};
+
+use crate::{config_manager, sdk_manager};
+
+/// Number of recent stable releases offered by the release picker
+const RECENT_RELEASES: usize = 10;
+
+/// Show a selection menu and return the index of the chosen item
+pub fn select(prompt: &str, items: &[String]) -> Result<usize> {
+    ensure_terminal(prompt)?;
+
+    Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .items(items)
+        .default(0)
+        .interact()
+        .context("Failed to get user selection")
+}
+
+fn ensure_terminal(prompt: &str) -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        anyhow::bail!(
+            "Cannot prompt \"{}\": stdin is not a terminal. Pass the value as an argument instead",
+            prompt
+        );
+    }
+    Ok(())
+}
+
+/// Pick one of the installed versions, marking the global and project versions
+pub async fn pick_installed_version(prompt: &str) -> Result<String> {
+    info!("Selecting installed Flutter version interactively");
+
+    let versions = sdk_manager::list_installed_versions().await?;
+    if versions.is_empty() {
+        anyhow::bail!(
+            "No Flutter versions installed.\nRun 'fvm-rs install <version>' to install one first."
+        );
+    }
+
+    let global_version = sdk_manager::get_global_version().await?;
+    let project_version = config_manager::get_project_flutter_version().await?;
+
+    let items: Vec<String> = versions
+        .iter()
+        .map(|version| {
+            let mut item = version.clone();
+            if global_version.as_deref() == Some(version) {
+                item.push_str(" (global)");
+            }
+            if project_version.as_deref() == Some(version) {
+                item.push_str(" ← current project");
+            }
+            item
+        })
+        .collect();
+
+    let selection = select(prompt, &items)?;
+    Ok(versions[selection].clone())
+}
+
+/// Pick a channel or one of the recent stable releases
+pub async fn pick_release(prompt: &str) -> Result<String> {
+    info!("Selecting Flutter release interactively");
+
+    // Fail before the network round-trip
+    ensure_terminal(prompt)?;
+    println!("Fetching available Flutter releases...");
+
+    // Channels don't need the releases feed, so offline users can still pick one
+    let releases = match sdk_manager::list_available_versions().await {
+        Ok(releases) => Some(releases),
+        Err(e) => {
+            debug!("Failed to fetch releases for the picker: {:#}", e);
+            println!("⚠ Could not fetch releases (offline?), showing channels only");
+            None
+        }
+    };
+    let installed = sdk_manager::list_installed_versions().await?;
+    let project_version = config_manager::get_project_flutter_version().await?;
+
+    // Channels first, then recent stable releases
+    let mut choices: Vec<(String, String)> = [
+        ("stable", "latest stable release"),
+        ("beta", "latest beta release"),
+        ("dev", "latest dev release"),
+        ("master", "bleeding edge"),
+    ]
+    .iter()
+    .map(|(channel, description)| (channel.to_string(), description.to_string()))
+    .collect();
+
+    if let Some(releases) = &releases {
+        choices.extend(
+            releases
+                .releases
+                .iter()
+                .filter(|r| r.channel == "stable")
+                .take(RECENT_RELEASES)
+                .map(|r| (r.version.clone(), "stable".to_string())),
+        );
+    }
+
+    let items: Vec<String> = choices
+        .iter()
+        .map(|(version, description)| {
+            let mut item = format!("{} ({})", version, description);
+            if installed.contains(version) {
+                item.push_str(" ✓");
+            }
+            if project_version.as_deref() == Some(version.as_str()) {
+                item.push_str(" ← current project");
+            }
+            item
+        })
+        .collect();
+
+    let selection = select(prompt, &items)?;
+    Ok(choices.swap_remove(selection).0)
+}
@@ -1,13 +1,19 @@
-use clap::{Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand};
+use std::io::IsTerminal;
 use tracing_subscriber::fmt::format::{self, FormatEvent, FormatFields};
 use tracing_subscriber::registry::LookupSpan;
 use tracing_subscriber::EnvFilter;
 
+mod archive_manager;
 mod commands;
+mod completion;
 mod config_manager;
 mod gitignore_manager;
 mod ide_manager;
+mod interactive;
+mod progress;
 mod sdk_manager;
+mod update_manager;
 mod utils;
 
 // Custom compact log format with short timestamp and single-letter levels
@@ -48,6 +54,9 @@ where
     }
 }
 
+/// How long a finished command waits for the background update check
+const UPDATE_CHECK_GRACE: std::time::Duration = std::time::Duration::from_millis(300);
+
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 struct FvmArgs {
@@ -56,7 +65,7 @@ struct FvmArgs {
     verbose: bool,
 
     #[command(subcommand)]
-    cmd: Commands,
+    cmd: Option<Commands>,
 }
 
 #[derive(Subcommand, Debug, Clone)]
@@ -67,7 +76,7 @@ enum Commands {
     Use(commands::r#use::UseArgs),
     /// Lists installed Flutter SDK versions
     #[command(alias = "ls")]
-    List,
+    List(commands::list::ListArgs),
     /// Shows available Flutter SDK releases
     Releases(commands::releases::ReleasesArgs),
     /// Removes a Flutter SDK version
@@ -93,32 +102,84 @@ enum Commands {
     Exec(commands::exec::ExecArgs),
     /// Executes Flutter commands with a specific SDK version
     Spawn(commands::spawn::SpawnArgs),
+    /// Prints shell commands that put the project's Flutter SDK on PATH
+    Env(commands::env::EnvArgs),
+    /// Prints the path of the flutter or dart executable that would run here
+    Which(commands::which::WhichArgs),
+    /// Updates a channel install (e.g. stable) to the latest commit of its channel
+    Upgrade(commands::upgrade::UpgradeArgs),
+    /// Updates fvm-rs itself to the latest (or a given) release
+    SelfUpdate(commands::self_update::SelfUpdateArgs),
+    /// Prints a shell completion script (bash, zsh, fish, powershell, elvish)
+    Completions(commands::completions::CompletionsArgs),
+    /// Shows cache disk usage and orphaned worktrees (`cache clean` prunes them)
+    Cache(commands::cache::CacheArgs),
     /// Completely removes the FVM cache directory and all cached versions
     Destroy(commands::destroy::DestroyArgs),
+    /// Bundles an installed version and its engine into a portable archive
+    Export(commands::export::ExportArgs),
+    /// Installs a version from an archive created by export
+    Import(commands::import::ImportArgs),
 }
 
 #[tokio::main]
 async fn main() -> Result<(), anyhow::Error> {
+    // Answer completion requests from scripts made by `completions --dynamic`
+    clap_complete::CompleteEnv::with_factory(FvmArgs::command)
+        .var(commands::completions::COMPLETE_VAR)
+        .complete();
+
     let args = FvmArgs::parse();
 
     // Initialize tracing subscriber based on verbose flag
     let log_level = if args.verbose { "debug" } else { "warn" };
+    // Logs go to stderr so machine-readable output on stdout stays clean
     tracing_subscriber::fmt()
         .event_format(CompactFormat)
         .with_env_filter(EnvFilter::new(log_level))
+        .with_writer(std::io::stderr)
         .init();
 
+    // Path helpers in utils resolve against the configured cache path
+    match config_manager::GlobalConfig::read()
+        .await
+        .and_then(|config| config.get_cache_path())
+    {
+        Ok(cache_path) => utils::set_cache_root(cache_path),
+        Err(e) => tracing::warn!("Could not resolve the configured cache path, using the default: {:#}", e),
+    }
+
     // cache::ensure_bare_cache(url, path)
 
-    match args.cmd {
+    let cmd = match args.cmd {
+        Some(cmd) => cmd,
+        // Without a subcommand, offer a menu on interactive terminals and keep
+        // clap's usage error everywhere else (scripts, CI, pipes)
+        None if std::io::stdin().is_terminal() => select_command_interactively()?,
+        None => FvmArgs::command()
+            .error(
+                clap::error::ErrorKind::MissingSubcommand,
+                "'fvm-rs' requires a subcommand but one was not provided",
+            )
+            .exit(),
+    };
+
+    // Check for a newer fvm-rs in the background while the command runs
+    let update_check = (std::io::stdout().is_terminal() && !matches!(cmd, Commands::SelfUpdate(_)))
+        .then(|| tokio::spawn(update_manager::check_for_update()));
+
+    let result = match cmd {
         Commands::Install(args) => commands::install::run(args).await,
         Commands::Use(args) => commands::r#use::run(args).await,
-        Commands::List => commands::list::run().await,
+        Commands::List(args) => commands::list::run(args).await,
         Commands::Releases(args) => commands::releases::run(args).await,
         Commands::Remove(args) => commands::remove::run(args).await,
         Commands::Config(args) => commands::config::run(args).await,
         Commands::Global(args) => commands::global::run(args).await,
-        Commands::Doctor(args) => commands::doctor::run(args).await,
+        Commands::Doctor(args) => {
+            let exit_code = commands::doctor::run(args).await?;
+            std::process::exit(exit_code);
+        }
         Commands::Flavor(args) => commands::flavor::run(args).await,
         Commands::Fork(args) => commands::fork::run(args).await,
         Commands::Api(args) => commands::api::run(args).await,
@@ -138,6 +199,59 @@ async fn main() -> Result<(), anyhow::Error> {
             let exit_code = commands::spawn::run(args).await?;
             std::process::exit(exit_code);
         }
+        Commands::Env(args) => commands::env::run(args).await,
+        Commands::Which(args) => commands::which::run(args).await,
+        Commands::Upgrade(args) => commands::upgrade::run(args).await,
+        Commands::SelfUpdate(args) => commands::self_update::run(args).await,
+        Commands::Completions(args) => commands::completions::run(args, FvmArgs::command()),
+        Commands::Cache(args) => commands::cache::run(args).await,
         Commands::Destroy(args) => commands::destroy::run(args).await,
+        Commands::Export(args) => commands::export::run(args).await,
+        Commands::Import(args) => commands::import::run(args).await,
+    };
+
+    // A check answered from the saved result finishes right away; a slow network
+    // check is abandoned after a short grace period rather than delaying the exit
+    if let Some(update_check) = update_check
+        && let Ok(Ok(Ok(Some(latest)))) =
+            tokio::time::timeout(UPDATE_CHECK_GRACE, update_check).await
+    {
+        eprintln!(
+            "\n⚠ fvm-rs {} is available (you have {})",
+            latest,
+            update_manager::CURRENT_VERSION
+        );
+        eprintln!("  Run: fvm-rs self-update");
+        eprintln!("  Run 'fvm-rs config --update-check false' to stop these notices");
     }
+
+    result
+}
+
+/// Show a menu of the main actions and return the chosen command
+///
+/// Only commands that work without further arguments are offered; the ones that
+/// need a version fall back to their own interactive selectors.
+fn select_command_interactively() -> Result<Commands, anyhow::Error> {
+    const ACTIONS: &[(&str, &str)] = &[
+        ("list", "List installed Flutter SDK versions"),
+        ("install", "Install a Flutter SDK version"),
+        ("use", "Set the Flutter SDK version for this project"),
+        ("global", "Set the global Flutter SDK version"),
+        ("releases", "Show available Flutter SDK releases"),
+        ("doctor", "Show FVM environment and project diagnostics"),
+        ("config", "Show global configuration"),
+    ];
+
+    let items: Vec<String> = ACTIONS
+        .iter()
+        .map(|(name, description)| format!("{:<10} {}", name, description))
+        .collect();
+
+    let selection = interactive::select("What would you like to do?", &items)?;
+
+    // Reuse clap parsing so each command gets its normal defaults
+    let args = FvmArgs::try_parse_from(["fvm-rs", ACTIONS[selection].0])?;
+    args.cmd
+        .ok_or_else(|| anyhow::anyhow!("Failed to resolve selected command"))
 }
@@ -0,0 +1,247 @@
+//! Terminal rendering of install progress events
+//!
+//! On a terminal, installs draw live bars for the repository fetch and the
+//! engine download. When stdout is piped (e.g. CI logs) or `--quiet` is given,
+//! a plain line is printed every 10% instead, so logs aren't flooded with
+//! redraws.
+
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::io::IsTerminal;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::sdk_manager::{InstallEvent, ProgressCallback};
+use crate::utils;
+
+/// Percentage step between progress lines when bars aren't drawn
+const LINE_STEP_PERCENT: u64 = 10;
+
+/// Spinner redraw interval while waiting on blocking work
+const SPINNER_TICK: Duration = Duration::from_millis(100);
+
+/// Renders install progress events as bars or periodic lines
+pub struct InstallProgress {
+    state: Arc<Mutex<State>>,
+}
+
+enum State {
+    Bars {
+        multi: MultiProgress,
+        repository: Option<ProgressBar>,
+        engine: Option<ProgressBar>,
+        waiting: Option<ProgressBar>,
+    },
+    Lines {
+        repository_percent: Option<u64>,
+        engine_percent: Option<u64>,
+    },
+}
+
+impl InstallProgress {
+    /// Draw bars when stdout is a terminal and `quiet` is off, print lines otherwise
+    pub fn new(quiet: bool) -> Self {
+        let state = if std::io::stdout().is_terminal() && !quiet {
+            State::Bars {
+                multi: MultiProgress::new(),
+                repository: None,
+                engine: None,
+                waiting: None,
+            }
+        } else {
+            State::Lines {
+                repository_percent: None,
+                engine_percent: None,
+            }
+        };
+
+        Self {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Get a callback to pass as `InstallOptions::progress`
+    pub fn callback(&self) -> ProgressCallback {
+        let state = self.state.clone();
+        Arc::new(move |event| {
+            if let Ok(mut state) = state.lock() {
+                state.handle(event);
+            }
+        })
+    }
+
+    /// Clear any bars still drawn, e.g. after a failed install
+    pub fn finish(&self) {
+        if let Ok(mut state) = self.state.lock()
+            && let State::Bars {
+                repository,
+                engine,
+                waiting,
+                ..
+            } = &mut *state
+        {
+            for bar in [repository.take(), engine.take(), waiting.take()].into_iter().flatten() {
+                bar.finish_and_clear();
+            }
+        }
+    }
+}
+
+impl State {
+    fn handle(&mut self, event: InstallEvent) {
+        match self {
+            State::Bars {
+                multi,
+                repository,
+                engine,
+                waiting,
+            } => {
+                // Any other event means the lock being waited for was acquired
+                if !matches!(event, InstallEvent::WaitingForLock { .. })
+                    && let Some(bar) = waiting.take()
+                {
+                    bar.finish_and_clear();
+                }
+                match event {
+                    InstallEvent::Cloning { .. } => {
+                        // Clones and fetches block without events until objects arrive
+                        repository.get_or_insert_with(|| repository_spinner(multi));
+                    }
+                    InstallEvent::FetchingRepository {
+                        received_objects,
+                        total_objects,
+                    } => {
+                        let bar = repository.get_or_insert_with(|| repository_spinner(multi));
+                        if bar.length() != Some(total_objects as u64) {
+                            bar.disable_steady_tick();
+                            bar.set_length(total_objects as u64);
+                            bar.set_style(bar_style("{pos}/{len} objects"));
+                            bar.set_message("Receiving objects");
+                        }
+                        bar.set_position(received_objects as u64);
+                    }
+                    InstallEvent::Checkout { .. } => {
+                        if let Some(bar) = repository.take() {
+                            bar.finish_and_clear();
+                        }
+                    }
+                    InstallEvent::DownloadingEngine {
+                        downloaded_bytes,
+                        total_bytes,
+                        ..
+                    } => {
+                        let bar = engine.get_or_insert_with(|| {
+                            let bar = match total_bytes {
+                                Some(total) => ProgressBar::new(total)
+                                    .with_style(bar_style("{bytes}/{total_bytes} ({eta})")),
+                                None => ProgressBar::new_spinner().with_style(spinner_style()),
+                            };
+                            let bar = multi.add(bar);
+                            bar.set_message("Downloading engine");
+                            bar
+                        });
+                        bar.set_position(downloaded_bytes);
+                    }
+                    InstallEvent::Extracting { .. } => {
+                        if let Some(bar) = engine {
+                            bar.set_style(spinner_style());
+                            bar.set_message("Extracting engine...");
+                            bar.enable_steady_tick(SPINNER_TICK);
+                        }
+                    }
+                    InstallEvent::Linking { .. } => {
+                        if let Some(bar) = engine.take() {
+                            bar.finish_and_clear();
+                        }
+                    }
+                    InstallEvent::WaitingForLock { path } => {
+                        waiting.get_or_insert_with(|| {
+                            let bar = multi.add(ProgressBar::new_spinner().with_style(spinner_style()));
+                            bar.set_message(format!("Waiting for another fvm-rs process working on {}...", path));
+                            bar.enable_steady_tick(SPINNER_TICK);
+                            bar
+                        });
+                    }
+                    InstallEvent::Resolving { .. } => {}
+                }
+            }
+            State::Lines {
+                repository_percent,
+                engine_percent,
+            } => match event {
+                InstallEvent::Cloning { url } => {
+                    println!("  Fetching {}...", url);
+                }
+                InstallEvent::FetchingRepository {
+                    received_objects,
+                    total_objects,
+                } => {
+                    if let Some(percent) = next_step(
+                        repository_percent,
+                        received_objects as u64,
+                        total_objects as u64,
+                    ) {
+                        println!(
+                            "  Receiving objects: {}% ({}/{})",
+                            percent, received_objects, total_objects
+                        );
+                    }
+                }
+                InstallEvent::DownloadingEngine {
+                    downloaded_bytes,
+                    total_bytes: Some(total_bytes),
+                    ..
+                } => {
+                    if let Some(percent) = next_step(engine_percent, downloaded_bytes, total_bytes)
+                    {
+                        println!(
+                            "  Downloading engine: {}% ({} of {})",
+                            percent,
+                            utils::format_size(downloaded_bytes),
+                            utils::format_size(total_bytes)
+                        );
+                    }
+                }
+                InstallEvent::Extracting { .. } => {
+                    println!("  Extracting engine...");
+                }
+                InstallEvent::WaitingForLock { path } => {
+                    println!("  Waiting for another fvm-rs process working on {}...", path);
+                }
+                _ => {}
+            },
+        }
+    }
+}
+
+fn repository_spinner(multi: &MultiProgress) -> ProgressBar {
+    let bar = multi.add(ProgressBar::new_spinner().with_style(spinner_style()));
+    bar.set_message("Fetching Flutter repository...");
+    bar.enable_steady_tick(SPINNER_TICK);
+    bar
+}
+
+fn spinner_style() -> ProgressStyle {
+    ProgressStyle::with_template("  {spinner} {msg}")
+        .unwrap_or_else(|_| ProgressStyle::default_spinner())
+}
+
+fn bar_style(counts: &str) -> ProgressStyle {
+    ProgressStyle::with_template(&format!("  {{msg:<20}} [{{bar:30}}] {}", counts))
+        .unwrap_or_else(|_| ProgressStyle::default_bar())
+        .progress_chars("=> ")
+}
+
+/// Get the percentage to report once progress reaches the next step
+fn next_step(last: &mut Option<u64>, done: u64, total: u64) -> Option<u64> {
+    if total == 0 {
+        return None;
+    }
+
+    let step = done * 100 / total / LINE_STEP_PERCENT * LINE_STEP_PERCENT;
+    if last.is_some_and(|last| step <= last) {
+        return None;
+    }
+
+    *last = Some(step);
+    Some(step)
+}
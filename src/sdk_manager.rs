@@ -1,9 +1,9 @@
 use crate::{utils, config_manager};
 use anyhow::{Context, Result, anyhow};
-use chrono::{DateTime, Utc};
-use git2::{FetchOptions, Repository, build::RepoBuilder};
+use chrono::{DateTime, NaiveDate, Utc};
+use git2::{Cred, CredentialType, FetchOptions, ProxyOptions, RemoteCallbacks, Repository, build::RepoBuilder};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashSet, io::Cursor, path::PathBuf, sync::OnceLock};
+use std::{collections::{HashMap, HashSet}, io::Cursor, path::{Path, PathBuf}, sync::{Arc, OnceLock}, time::Duration};
 use tokio::{fs, task};
 use tracing::{debug, warn};
 use zip::ZipArchive;
@@ -21,7 +21,8 @@ pub struct FlutterRelease {
 pub struct CurrentReleases {
     pub stable: FlutterRelease,
     pub beta: FlutterRelease,
-    pub dev: FlutterRelease,
+    /// The dev channel is discontinued and often missing from the feed
+    pub dev: Option<FlutterRelease>,
 }
 
 pub struct FlutterReleases {
@@ -29,11 +30,52 @@ pub struct FlutterReleases {
     pub releases: Vec<FlutterRelease>,
 }
 
+/// Aliases for the current release of a channel, e.g. `latest` for the newest stable
+const LATEST_ALIASES: &[(&str, &str)] = &[("latest", "stable"), ("latest-beta", "beta"), ("latest-dev", "dev")];
+
+impl FlutterReleases {
+    /// The current release of the stable, beta or dev channel
+    pub fn current_release(&self, channel: &str) -> Result<&FlutterRelease> {
+        match channel {
+            "stable" => Ok(&self.current_releases.stable),
+            "beta" => Ok(&self.current_releases.beta),
+            "dev" => self.current_releases.dev.as_ref().context(
+                "The releases feed has no current dev release. The dev channel is discontinued; use beta instead",
+            ),
+            _ => Err(anyhow!("The {} channel has no current release", channel)),
+        }
+    }
+
+    /// Releases on `channel` (every channel if None), newest first, at most `limit` of them
+    pub fn filtered(&self, channel: Option<&str>, limit: Option<usize>) -> Vec<&FlutterRelease> {
+        self.releases
+            .iter()
+            .filter(|release| channel.is_none_or(|channel| release.channel == channel))
+            .take(limit.unwrap_or(usize::MAX))
+            .collect()
+    }
+
+    /// The current channel versions and the filtered releases as JSON, as `api releases` prints them
+    pub fn to_json(&self, channel: Option<&str>, limit: Option<usize>) -> serde_json::Value {
+        let releases = self.filtered(channel, limit);
+        serde_json::json!({
+            "current": {
+                "stable": self.current_releases.stable.version,
+                "beta": self.current_releases.beta.version,
+                "dev": self.current_releases.dev.as_ref().map(|dev| &dev.version),
+            },
+            "total": releases.len(),
+            "releases": releases,
+        })
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct CurrentReleasesResponse {
     stable: String,
     beta: String,
-    dev: String,
+    #[serde(default)]
+    dev: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -71,7 +113,7 @@ async fn get_flutter_repo_url(version: &str) -> Result<String> {
 
         if let Some(url) = config.get_fork_url(&alias) {
             debug!("Found fork URL for '{}': {}", alias, url);
-            Ok(url)
+            utils::expand_repo_url(&url).with_context(|| format!("Invalid URL for fork '{}'", alias))
         } else {
             anyhow::bail!(
                 "Fork '{}' not found. Add it with: fvm-rs fork add {} <git-url>",
@@ -82,8 +124,430 @@ async fn get_flutter_repo_url(version: &str) -> Result<String> {
     } else {
         // Use default URL from config or fallback
         let config = config_manager::GlobalConfig::read().await?;
-        Ok(config.get_flutter_url())
+        utils::expand_repo_url(&config.get_flutter_url()).context("Invalid Flutter repository URL")
+    }
+}
+
+/// Host of the Flutter releases feed and engine archives
+const DEFAULT_STORAGE_BASE_URL: &str = "https://storage.googleapis.com";
+
+/// Host serving files from the Flutter repository, used for engine.version lookups
+const DEFAULT_RAW_CONTENT_URL: &str = "https://raw.githubusercontent.com";
+
+/// Get the base URL for the releases feed and engine archives (a mirror, if configured)
+async fn storage_base_url() -> Result<String> {
+    Ok(config_manager::GlobalConfig::read()
+        .await?
+        .get_storage_base_url()
+        .unwrap_or_else(|| DEFAULT_STORAGE_BASE_URL.to_string()))
+}
+
+/// Build an HTTP client honoring the configured proxy and custom CA certificates
+///
+/// reqwest already picks up HTTP(S)_PROXY and NO_PROXY from the environment, so
+/// only an explicit `proxy` from the global config needs to be applied here
+/// (still honoring NO_PROXY). Certificates from the PEM file in `FVM_CA_CERT`
+/// are trusted in addition to the system roots, for proxies that intercept TLS.
+pub async fn http_client() -> Result<reqwest::Client> {
+    let config = config_manager::GlobalConfig::read().await?;
+    let mut builder = reqwest::Client::builder();
+
+    if let Some(proxy) = &config.proxy {
+        debug!("Using configured proxy for HTTP requests: {}", proxy);
+        let proxy = reqwest::Proxy::all(proxy)
+            .context("Invalid proxy URL")?
+            .no_proxy(reqwest::NoProxy::from_env());
+        builder = builder.proxy(proxy);
+    }
+
+    if let Ok(path) = std::env::var("FVM_CA_CERT")
+        && !path.is_empty()
+    {
+        let path = utils::expand_path(&path)?;
+        debug!("Trusting CA certificates from: {}", path.display());
+        let pem = fs::read(&path)
+            .await
+            .with_context(|| format!("Failed to read FVM_CA_CERT file {}", path.display()))?;
+        let certificates = reqwest::Certificate::from_pem_bundle(&pem)
+            .with_context(|| format!("Invalid PEM certificate in FVM_CA_CERT file {}", path.display()))?;
+        if certificates.is_empty() {
+            anyhow::bail!("No certificates found in FVM_CA_CERT file {}", path.display());
+        }
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    builder.build().context("Failed to build HTTP client")
+}
+
+/// GET a URL with the configured proxy, retrying transient failures
+///
+/// See `utils::fetch_with_retry`. The caller checks the response status.
+async fn http_get(url: &str) -> Result<reqwest::Response> {
+    let client = http_client().await?;
+    let attempts = config_manager::GlobalConfig::read().await?.get_http_retries();
+    utils::fetch_with_retry(&client, url, attempts).await
+}
+
+/// Build git proxy options
+///
+/// git2 does not detect proxies from the environment, so the resolved proxy is
+/// set explicitly. Without one, libgit2 falls back to git's own http.proxy config.
+fn git_proxy_options<'a>(proxy: Option<&str>) -> ProxyOptions<'a> {
+    let mut proxy_options = ProxyOptions::new();
+    match proxy {
+        Some(url) => {
+            debug!("Using proxy for git operations: {}", url);
+            proxy_options.url(url);
+        }
+        None => {
+            proxy_options.auto();
+        }
+    }
+    proxy_options
+}
+
+/// Build git fetch options with proxy settings and credential callbacks
+fn git_fetch_options<'a>(proxy: Option<&str>) -> FetchOptions<'a> {
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.proxy_options(git_proxy_options(proxy));
+    fetch_options.remote_callbacks(git_remote_callbacks());
+    fetch_options
+}
+
+/// Build git fetch options that report transfer progress as install events
+///
+/// Events are only emitted when the received percentage changes, since libgit2
+/// calls back for every object.
+fn git_fetch_options_with_progress<'a>(
+    proxy: Option<&str>,
+    options: &InstallOptions,
+) -> FetchOptions<'a> {
+    let mut fetch_options = git_fetch_options(proxy);
+
+    let mut callbacks = git_remote_callbacks();
+    if let Some(progress) = options.progress.clone() {
+        let mut last_percent = None;
+        callbacks.transfer_progress(move |stats| {
+            let total_objects = stats.total_objects();
+            let percent = (total_objects > 0).then(|| stats.received_objects() * 100 / total_objects);
+            if percent != last_percent {
+                last_percent = percent;
+                progress(InstallEvent::FetchingRepository {
+                    received_objects: stats.received_objects(),
+                    total_objects,
+                });
+            }
+            true
+        });
+    }
+    fetch_options.remote_callbacks(callbacks);
+
+    fetch_options
+}
+
+/// Private keys tried in order for SSH remotes when ssh-agent has none that work
+const SSH_KEY_NAMES: &[&str] = &["id_ed25519", "id_ecdsa", "id_rsa"];
+
+/// Username sent with `FVM_GIT_TOKEN` when the URL doesn't name one
+///
+/// GitHub and GitLab accept any username alongside a personal access token.
+const GIT_TOKEN_USERNAME: &str = "x-access-token";
+
+/// Build git remote callbacks that answer credential requests
+///
+/// SSH URLs authenticate through ssh-agent, then the unencrypted keys in
+/// ~/.ssh (`SSH_KEY_NAMES`). HTTPS URLs use the token in `FVM_GIT_TOKEN`, then
+/// git's configured credential helpers. libgit2 calls back after every rejected
+/// credential, so each method is tried once and the last failure is surfaced
+/// instead of looping.
+fn git_remote_callbacks<'a>() -> RemoteCallbacks<'a> {
+    let mut tried_agent = false;
+    let mut ssh_keys = ssh_key_paths().into_iter();
+    let mut tried_token = false;
+    let mut tried_helper = false;
+
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(move |url, username_from_url, allowed_types| {
+        if allowed_types.contains(CredentialType::USERNAME) {
+            return Cred::username(username_from_url.unwrap_or("git"));
+        }
+
+        if allowed_types.contains(CredentialType::SSH_KEY) {
+            let username = username_from_url.unwrap_or("git");
+            if !tried_agent {
+                tried_agent = true;
+                debug!("Trying ssh-agent for {}", url);
+                if let Ok(cred) = Cred::ssh_key_from_agent(username) {
+                    return Ok(cred);
+                }
+            }
+            if let Some(key) = ssh_keys.next() {
+                debug!("Trying SSH key {} for {}", key.display(), url);
+                let public_key = key.with_extension("pub");
+                return Cred::ssh_key(username, public_key.exists().then_some(public_key.as_path()), &key, None);
+            }
+            return Err(git2::Error::from_str(
+                "SSH authentication failed: no ssh-agent identity or key in ~/.ssh was accepted",
+            ));
+        }
+
+        if allowed_types.contains(CredentialType::USER_PASS_PLAINTEXT) {
+            if !tried_token {
+                tried_token = true;
+                if let Ok(token) = std::env::var("FVM_GIT_TOKEN")
+                    && !token.is_empty()
+                {
+                    debug!("Using FVM_GIT_TOKEN for {}", url);
+                    return Cred::userpass_plaintext(username_from_url.unwrap_or(GIT_TOKEN_USERNAME), &token);
+                }
+            }
+            if !tried_helper {
+                tried_helper = true;
+                debug!("Asking git credential helpers for {}", url);
+                let config = git2::Config::open_default()?;
+                if let Ok(cred) = Cred::credential_helper(&config, url, username_from_url) {
+                    return Ok(cred);
+                }
+            }
+            return Err(git2::Error::from_str(
+                "HTTPS authentication failed: set FVM_GIT_TOKEN or configure a git credential helper",
+            ));
+        }
+
+        Cred::default()
+    });
+    callbacks
+}
+
+/// Get the private keys in ~/.ssh to offer, in `SSH_KEY_NAMES` order
+fn ssh_key_paths() -> Vec<PathBuf> {
+    let Some(ssh_dir) = dirs::home_dir().map(|home| home.join(".ssh")) else {
+        return vec![];
+    };
+
+    SSH_KEY_NAMES
+        .iter()
+        .map(|name| ssh_dir.join(name))
+        .filter(|path| path.exists())
+        .collect()
+}
+
+/// Remote repository details discovered by [`probe_remote`]
+pub struct RemoteProbe {
+    pub default_branch: Option<String>,
+    pub channel_branches: Vec<String>,
+}
+
+/// Check that a git remote is reachable without cloning it
+///
+/// Equivalent to `git ls-remote`: connects, lists the advertised refs and
+/// reports the default branch plus which Flutter channel branches exist.
+pub async fn probe_remote(url: &str) -> Result<RemoteProbe> {
+    let proxy = config_manager::GlobalConfig::read().await?.get_proxy();
+    let url = url.to_string();
+
+    task::spawn_blocking(move || -> Result<RemoteProbe> {
+        debug!("Probing remote: {}", url);
+        let mut remote = git2::Remote::create_detached(url.as_str())
+            .context("Invalid repository URL")?;
+        let connection = remote
+            .connect_auth(
+                git2::Direction::Fetch,
+                Some(git_remote_callbacks()),
+                Some(git_proxy_options(proxy.as_deref())),
+            )
+            .with_context(|| format!("Failed to connect to {}", url))?;
+
+        let default_branch = connection
+            .default_branch()
+            .ok()
+            .and_then(|b| b.as_str().map(|s| s.trim_start_matches("refs/heads/").to_string()));
+
+        let heads: HashSet<String> = connection
+            .list()
+            .context("Failed to list remote refs")?
+            .iter()
+            .filter_map(|head| head.name().strip_prefix("refs/heads/").map(str::to_string))
+            .collect();
+
+        let channel_branches = ["stable", "beta", "master"]
+            .iter()
+            .filter(|branch| heads.contains(**branch))
+            .map(|branch| branch.to_string())
+            .collect();
+
+        Ok(RemoteProbe {
+            default_branch,
+            channel_branches,
+        })
+    })
+    .await?
+}
+
+/// The current tip of the master branch
+pub struct MasterTip {
+    pub commit: String,
+    /// Read from the local shared repository because the remote was unreachable
+    pub from_cache: bool,
+}
+
+/// Resolve the master tip commit of the configured Flutter repository
+///
+/// Asks the remote first (like `git ls-remote`) and falls back to the shared
+/// repository's last fetched master when offline.
+pub async fn master_tip() -> Result<MasterTip> {
+    let config = config_manager::GlobalConfig::read().await?;
+    let url = utils::expand_repo_url(&config.get_flutter_url())
+        .context("Invalid Flutter repository URL")?;
+    let proxy = config.get_proxy();
+
+    let remote_result = task::spawn_blocking(move || -> Result<String> {
+        let mut remote = git2::Remote::create_detached(url.as_str())
+            .context("Invalid repository URL")?;
+        let connection = remote
+            .connect_auth(
+                git2::Direction::Fetch,
+                Some(git_remote_callbacks()),
+                Some(git_proxy_options(proxy.as_deref())),
+            )
+            .with_context(|| format!("Failed to connect to {}", url))?;
+
+        connection
+            .list()?
+            .iter()
+            .find(|head| head.name() == "refs/heads/master")
+            .map(|head| head.oid().to_string())
+            .with_context(|| format!("{} has no master branch", url))
+    })
+    .await?;
+
+    let remote_error = match remote_result {
+        Ok(commit) => {
+            return Ok(MasterTip {
+                commit,
+                from_cache: false,
+            });
+        }
+        Err(e) => e,
+    };
+
+    debug!("Could not query remote master, trying shared repository: {}", remote_error);
+    let shared_dir = utils::shared_flutter_dir()?;
+    let cached = Repository::open_bare(&shared_dir)
+        .ok()
+        .and_then(|repo| repo.refname_to_id("refs/heads/master").ok());
+
+    match cached {
+        Some(oid) => Ok(MasterTip {
+            commit: oid.to_string(),
+            from_cache: true,
+        }),
+        None => Err(remote_error),
+    }
+}
+
+/// Get the shared repository a version's worktree belongs to
+///
+/// Upstream versions share one repository; each fork alias has its own.
+fn shared_repo_dir(version: &str) -> Result<PathBuf> {
+    match parse_fork_syntax(version).0 {
+        Some(alias) => utils::shared_fork_dir(&alias),
+        None => utils::shared_flutter_dir(),
+    }
+}
+
+/// Get the git worktree name for a version (a single path component)
+fn worktree_name(version: &str) -> String {
+    format!("fvm-{}", version.replace(['@', '/'], "-"))
+}
+
+/// Clone (or update) a fork's shared repository ahead of its first install
+///
+/// Surfaces reachability and authentication problems early and makes the first
+/// `install <alias>/<version>` fast. A failed first clone leaves nothing behind.
+pub async fn prefetch_fork(alias: &str, url: &str, options: &InstallOptions) -> Result<PathBuf> {
+    ensure_cache_writable()?;
+    let repo_dir = utils::shared_fork_dir(alias)?;
+    let _repo_lock = acquire_install_lock(&repo_dir, options).await?;
+    let existed = repo_dir.exists();
+
+    options.emit(InstallEvent::Cloning {
+        url: url.to_string(),
+    });
+
+    if let Err(e) = ensure_shared_repo(url, &repo_dir, &FetchScope::All, &[], options).await {
+        if !existed && repo_dir.exists() {
+            debug!("Removing partial clone at {}", repo_dir.display());
+            let _ = fs::remove_dir_all(&repo_dir).await;
+        }
+        return Err(e);
+    }
+
+    Ok(repo_dir)
+}
+
+/// Point an existing fork repository's origin at a new URL
+///
+/// Returns false if the fork hasn't been cloned yet; its first install will
+/// clone from the configured URL.
+pub async fn set_fork_remote_url(alias: &str, url: &str) -> Result<bool> {
+    let repo_dir = utils::shared_fork_dir(alias)?;
+    if !repo_dir.exists() {
+        return Ok(false);
+    }
+
+    let _repo_lock = acquire_install_lock(&repo_dir, &InstallOptions::default()).await?;
+    let url = url.to_string();
+    task::spawn_blocking(move || {
+        let repo = Repository::open_bare(&repo_dir)
+            .with_context(|| format!("Failed to open fork repository at {}", repo_dir.display()))?;
+        repo.remote_set_url("origin", &url)
+            .context("Failed to update the fork repository's origin URL")?;
+        debug!("Set origin of {} to {}", repo_dir.display(), url);
+        Ok(true)
+    })
+    .await?
+}
+
+/// Move a fork's cloned repository to a new alias
+///
+/// Only safe while no versions are installed from it, since their worktrees
+/// reference the repository by path. Returns false if there is nothing to move
+/// or the new alias already has a repository.
+pub async fn move_fork_repo(alias: &str, new_alias: &str) -> Result<bool> {
+    let (repo_dir, new_repo_dir) = (utils::shared_fork_dir(alias)?, utils::shared_fork_dir(new_alias)?);
+    if !repo_dir.exists() || new_repo_dir.exists() {
+        return Ok(false);
+    }
+
+    let _repo_lock = acquire_install_lock(&repo_dir, &InstallOptions::default()).await?;
+    fs::rename(&repo_dir, &new_repo_dir)
+        .await
+        .with_context(|| format!("Failed to move {} to {}", repo_dir.display(), new_repo_dir.display()))?;
+    debug!("Moved fork repository {} to {}", repo_dir.display(), new_repo_dir.display());
+
+    Ok(true)
+}
+
+/// Delete a fork's cloned repository
+///
+/// Only safe while no versions are installed from it. Returns false if the
+/// fork was never cloned.
+pub async fn remove_fork_repo(alias: &str) -> Result<bool> {
+    let repo_dir = utils::shared_fork_dir(alias)?;
+    if !repo_dir.exists() {
+        return Ok(false);
     }
+
+    let _repo_lock = acquire_install_lock(&repo_dir, &InstallOptions::default()).await?;
+    fs::remove_dir_all(&repo_dir)
+        .await
+        .with_context(|| format!("Failed to remove {}", repo_dir.display()))?;
+    debug!("Removed fork repository {}", repo_dir.display());
+
+    Ok(true)
 }
 
 /// Get the actual version string without fork alias
@@ -94,6 +558,53 @@ fn strip_fork_alias(version: &str) -> String {
     parse_fork_syntax(version).1
 }
 
+/// Split an explicit channel suffix off a version (e.g. "3.10.0@beta")
+///
+/// Returns (version, Some(channel)) when the part after '@' is a channel name.
+/// Commit-pinned master versions ("master@<sha>") are left intact.
+fn parse_channel_suffix(version: &str) -> (String, Option<String>) {
+    if let Some((release, channel)) = version.rsplit_once('@')
+        && config_manager::is_channel(channel)
+    {
+        debug!("Parsed channel suffix: version='{}', channel='{}'", release, channel);
+        return (release.to_string(), Some(channel.to_string()));
+    }
+
+    (version.to_string(), None)
+}
+
+/// Get the release name used for tag and engine lookups
+///
+/// Strips the fork alias and any channel suffix:
+/// For "mycompany/3.10.0@beta" returns "3.10.0"
+pub fn release_name(version: &str) -> String {
+    parse_channel_suffix(&strip_fork_alias(version)).0
+}
+
+/// Separator between "master" and the commit in a commit-pinned master version
+const PINNED_MASTER_PREFIX: &str = "master@";
+
+/// Build the version name for master pinned to a commit (e.g. "master@3f2c1a9b0d")
+///
+/// The short SHA in the name keeps it apart from a floating `master` install.
+pub fn pinned_master_version(commit: &str) -> Result<String> {
+    let commit = commit.to_lowercase();
+    if commit.len() < 7 || !commit.chars().all(|c| c.is_ascii_hexdigit()) {
+        anyhow::bail!("Invalid commit SHA '{}': use at least 7 hex characters", commit);
+    }
+
+    Ok(format!("{}{}", PINNED_MASTER_PREFIX, &commit[..commit.len().min(10)]))
+}
+
+/// Get the commit prefix of a commit-pinned master version
+///
+/// For "master@3f2c1a9b0d" (optionally fork-prefixed) returns "3f2c1a9b0d".
+fn pinned_master_commit(version: &str) -> Option<String> {
+    strip_fork_alias(version)
+        .strip_prefix(PINNED_MASTER_PREFIX)
+        .map(str::to_string)
+}
+
 /// Get the channel for a given Flutter version
 /// Returns the channel name (stable, beta, dev, master) or defaults to "master" if not found
 pub async fn get_channel_for_version(version: &str) -> Result<String> {
@@ -103,6 +614,28 @@ pub async fn get_channel_for_version(version: &str) -> Result<String> {
     let actual_version = strip_fork_alias(version);
     debug!("Actual version (without fork alias): {}", actual_version);
 
+    if pinned_master_commit(version).is_some() {
+        return Ok("master".to_string());
+    }
+
+    // An explicit channel suffix overrides the releases lookup
+    let (actual_version, channel) = parse_channel_suffix(&actual_version);
+    if let Some(channel) = channel {
+        debug!("Using explicit channel for version {}: {}", version, channel);
+        return Ok(channel);
+    }
+
+    // Channels are their own branch; no need for the releases feed (works offline)
+    if config_manager::is_channel(&actual_version) {
+        return Ok(actual_version);
+    }
+
+    // An installed version's worktree is checked out on its channel branch
+    if let Some(channel) = installed_channel(version) {
+        debug!("Using channel of installed version {}: {}", version, channel);
+        return Ok(channel);
+    }
+
     // Get or fetch releases
     let releases = match RELEASES_CACHE.get() {
         Some(cached) => {
@@ -130,13 +663,230 @@ pub async fn get_channel_for_version(version: &str) -> Result<String> {
     Ok("master".to_string())
 }
 
+/// Progress events emitted while installing a Flutter version
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "kebab-case")]
+pub enum InstallEvent {
+    /// Resolving the repository URL, engine hash and channel
+    Resolving { version: String },
+    /// Cloning or fetching the shared Flutter repository
+    Cloning { url: String },
+    /// Objects received while cloning or fetching the shared repository
+    FetchingRepository {
+        received_objects: usize,
+        total_objects: usize,
+    },
+    /// Downloading the engine archive
+    DownloadingEngine {
+        hash: String,
+        downloaded_bytes: u64,
+        total_bytes: Option<u64>,
+    },
+    /// Extracting the engine archive into the shared engine cache
+    Extracting { hash: String },
+    /// Checking out the commit the version tag points to
+    Checkout { commit: String },
+    /// Linking the engine into the Flutter installation
+    Linking { hash: String },
+    /// Waiting for another fvm-rs process working on the same version, repository or engine
+    WaitingForLock { path: String },
+}
+
+/// Callback receiving installation progress events
+pub type ProgressCallback = Arc<dyn Fn(InstallEvent) + Send + Sync>;
+
+/// Options controlling how a Flutter version is installed
+#[derive(Clone, Default)]
+pub struct InstallOptions {
+    /// Skip downloading the engine and linking it into bin/cache
+    /// (for engine developers who provide their own local engine)
+    pub skip_engine_link: bool,
+
+    /// Called with progress events as the installation advances
+    pub progress: Option<ProgressCallback>,
+
+    /// Commit SHA (full or at least 7 characters) the version tag must point to
+    pub expected_commit: Option<String>,
+
+    /// Skip integrity checks of downloaded engine archives (for mirrors without checksums)
+    pub skip_verify: bool,
+
+    /// Fetch the shared repository even when the release tag is already cached
+    pub refresh: bool,
+}
+
+impl InstallOptions {
+    fn emit(&self, event: InstallEvent) {
+        if let Some(progress) = &self.progress {
+            progress(event);
+        }
+    }
+}
+
 pub async fn ensure_installed(version: &str) -> Result<()> {
-    if !verify_installed(version)? {
-        install(version).await?;
+    ensure_installed_with(version, &InstallOptions::default()).await
+}
+
+/// How long a successful verification is trusted by `ensure_installed_cached`
+const VERIFIED_MARKER_TTL: Duration = Duration::from_secs(30);
+
+/// Like `ensure_installed`, but trusts a verification made in the last few seconds
+///
+/// Meant for hot paths such as `spawn` called repeatedly from scripts. A marker
+/// file's modification time records the last successful check; once it is older
+/// than `VERIFIED_MARKER_TTL` the full check runs again.
+pub async fn ensure_installed_cached(version: &str) -> Result<()> {
+    let marker = utils::verified_marker_path(version)?;
+
+    let recently_verified = fs::metadata(&marker)
+        .await
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age < VERIFIED_MARKER_TTL);
+
+    if recently_verified {
+        debug!("Version {} was verified recently, skipping check", version);
+        return Ok(());
+    }
+
+    ensure_installed(version).await?;
+
+    // The marker is only an optimization, so failing to write it is not an error
+    if let Some(parent) = marker.parent() {
+        let _ = fs::create_dir_all(parent).await;
+    }
+    if let Err(e) = fs::write(&marker, "").await {
+        debug!("Failed to write verification marker {}: {}", marker.display(), e);
+    }
+
+    Ok(())
+}
+
+/// Shared outcome of one install; errors are kept as text since anyhow::Error isn't Clone
+type InstallOutcome = Arc<tokio::sync::OnceCell<Result<(), String>>>;
+
+/// Serializes git operations on each shared repository within this process
+///
+/// Concurrent installs of different versions would otherwise race on the
+/// shared repo's fetch refs and config locks.
+static SHARED_REPO_LOCKS: OnceLock<std::sync::Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>>> =
+    OnceLock::new();
+
+fn shared_repo_lock(path: &Path) -> Arc<tokio::sync::Mutex<()>> {
+    SHARED_REPO_LOCKS
+        .get_or_init(Default::default)
+        .lock()
+        .expect("shared repo lock registry poisoned")
+        .entry(path.to_path_buf())
+        .or_default()
+        .clone()
+}
+
+/// How long to wait for another process to release an install lock
+const INSTALL_LOCK_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// How often a held install lock is retried
+const INSTALL_LOCK_POLL: Duration = Duration::from_millis(250);
+
+/// Take the cross-process lock for a version, shared repository or engine directory
+///
+/// Processes sharing a cache (e.g. CI matrix jobs) would otherwise race on the
+/// same fetch, worktree or extraction. A held lock is retried until
+/// `INSTALL_LOCK_TIMEOUT`, emitting `WaitingForLock` once. The lock is released
+/// when the returned file is dropped, or by the OS if the process dies.
+pub async fn acquire_install_lock(target: &Path, options: &InstallOptions) -> Result<std::fs::File> {
+    let root = utils::cache_root_dir()?;
+    // File name: <target relative to the cache root, with separators as '+'>.lock
+    let name = target
+        .strip_prefix(&root)
+        .unwrap_or(target)
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("+");
+
+    let dir = utils::install_lock_dir()?;
+    fs::create_dir_all(&dir).await.context("Failed to create locks directory")?;
+    let path = dir.join(format!("{}.lock", name));
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+
+    let started = std::time::Instant::now();
+    let mut waiting = false;
+    loop {
+        match file.try_lock() {
+            Ok(()) => {
+                debug!("Acquired install lock: {}", path.display());
+                return Ok(file);
+            }
+            Err(std::fs::TryLockError::WouldBlock) => {}
+            Err(std::fs::TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("Failed to lock {}", path.display()));
+            }
+        }
+
+        if !waiting {
+            waiting = true;
+            debug!("Waiting for another fvm-rs process holding {}", path.display());
+            options.emit(InstallEvent::WaitingForLock {
+                path: target.display().to_string(),
+            });
+        }
+        if started.elapsed() >= INSTALL_LOCK_TIMEOUT {
+            anyhow::bail!(
+                "Timed out after {} minutes waiting for another fvm-rs process working on {} (lock: {})",
+                INSTALL_LOCK_TIMEOUT.as_secs() / 60,
+                target.display(),
+                path.display()
+            );
+        }
+        tokio::time::sleep(INSTALL_LOCK_POLL).await;
+    }
+}
+
+/// Installs started in this process, keyed by (version, repository URL)
+///
+/// Deliberately process-scoped: it only prevents duplicate work within one
+/// invocation and never outlives it, so it cannot go stale.
+static INSTALLS: OnceLock<std::sync::Mutex<HashMap<(String, String), InstallOutcome>>> = OnceLock::new();
+
+/// Install a Flutter version with custom options if it is not installed yet
+///
+/// Identical targets requested concurrently within one process install once;
+/// later callers wait for and share the first result (and its progress events).
+pub async fn ensure_installed_with(version: &str, options: &InstallOptions) -> Result<()> {
+    if verify_installed(version)? {
+        // An existing installation must match the expected commit too
+        if let Some(expected) = &options.expected_commit {
+            let commit = installed_commit(version)?
+                .with_context(|| format!("Could not read the commit of Flutter {}", version))?;
+            check_expected_commit(version, &commit, expected)?;
+        }
+        return Ok(());
     }
-    return Ok(());
+
+    let repo_url = get_flutter_repo_url(version).await?;
+    let outcome = INSTALLS
+        .get_or_init(Default::default)
+        .lock()
+        .expect("install registry poisoned")
+        .entry((version.to_string(), repo_url))
+        .or_default()
+        .clone();
+
+    outcome
+        .get_or_init(|| async { install(version, options).await.map_err(|e| format!("{:#}", e)) })
+        .await
+        .clone()
+        .map_err(|e| anyhow!(e))
 }
 
+/// Get the installed versions, sorted by version number
 pub async fn list_installed_versions() -> Result<Vec<String>> {
     let flutter_root = utils::flutter_dir()?;
     debug!("Listing installed versions from: {}", flutter_root.display());
@@ -151,37 +901,144 @@ pub async fn list_installed_versions() -> Result<Vec<String>> {
 
     while let Some(entry) = entries.next_entry().await? {
         let path = entry.path();
-        if fs::metadata(&path).await?.is_dir() {
-            if let Some(name) = path.file_name().and_then(|s| s.to_str()) {
-                debug!("Found installed version: {}", name);
-                versions.push(name.to_string());
-            }
+        if fs::metadata(&path).await?.is_dir()
+            && let Some(name) = path.file_name().and_then(|s| s.to_str())
+        {
+            debug!("Found installed version: {}", name);
+            versions.push(name.to_string());
         }
     }
 
+    // Oldest release first, then channels, then other names alphabetically
+    versions.sort_by(|a, b| utils::compare_versions(a, b));
+
     debug!("Found {} installed version(s)", versions.len());
-    return Ok(versions);
+    Ok(versions)
+}
+
+/// How the releases feed cached on disk may be used
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReleasesSource {
+    /// Use the cache while it is fresher than the TTL; fall back to it when offline
+    #[default]
+    Cached,
+    /// Always fetch the feed, updating the cache
+    Refresh,
+    /// Only read the cache, however old it is
+    Offline,
+}
+
+/// The releases feed as stored in `{cache}/releases_{platform}.json`
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CachedReleasesFeed {
+    /// Feed URL, so a changed storage mirror doesn't reuse another mirror's feed
+    url: String,
+    fetched_at: DateTime<Utc>,
+    feed: serde_json::Value,
 }
 
 pub async fn list_available_versions() -> Result<FlutterReleases> {
-    let platform = std::env::consts::OS;
+    list_available_versions_from(ReleasesSource::Cached).await
+}
 
-    // Check for FLUTTER_STORAGE_BASE_URL override
-    let base_url = std::env::var("FLUTTER_STORAGE_BASE_URL")
-        .unwrap_or_else(|_| "https://storage.googleapis.com".to_string());
+/// Get the available releases, using the on-disk cache as allowed by `source`
+pub async fn list_available_versions_from(source: ReleasesSource) -> Result<FlutterReleases> {
+    let platform = std::env::consts::OS;
 
+    let base_url = storage_base_url().await?;
     let url = format!(
         "{}/flutter_infra_release/releases/releases_{}.json",
         base_url, platform
     );
+    let cache_path = utils::releases_cache_path(platform)?;
+    let cached = read_cached_releases(&cache_path, &url).await;
+
+    match (source, &cached) {
+        (ReleasesSource::Offline, None) => anyhow::bail!(
+            "No cached releases feed for {}. Run 'fvm-rs releases' once while online",
+            url
+        ),
+        (ReleasesSource::Offline, Some(cached)) => {
+            debug!("Using cached releases feed from {} (offline)", cached.fetched_at);
+            return parse_releases(cached.feed.clone());
+        }
+        (ReleasesSource::Cached, Some(cached)) => {
+            let ttl = config_manager::GlobalConfig::read().await?.get_releases_cache_ttl();
+            if releases_cache_age(cached) < ttl {
+                debug!("Using cached releases feed from {}", cached.fetched_at);
+                return parse_releases(cached.feed.clone());
+            }
+        }
+        _ => {}
+    }
+
+    let feed = match fetch_releases_feed(&url).await {
+        Ok(feed) => feed,
+        Err(e) => match cached {
+            Some(cached) if source == ReleasesSource::Cached => {
+                warn!(
+                    "Could not fetch the releases feed, using the copy cached {} minute(s) ago: {:#}",
+                    releases_cache_age(&cached).as_secs() / 60,
+                    e
+                );
+                return parse_releases(cached.feed);
+            }
+            _ => return Err(e),
+        },
+    };
+
+    let releases = parse_releases(feed.clone())?;
+
+    // The cache is only an optimization, so failing to write it is not an error
+    let cached = CachedReleasesFeed {
+        url,
+        fetched_at: Utc::now(),
+        feed,
+    };
+    if let Err(e) = write_cached_releases(&cache_path, &cached).await {
+        debug!("Failed to write releases cache {}: {}", cache_path.display(), e);
+    }
+
+    Ok(releases)
+}
+
+async fn fetch_releases_feed(url: &str) -> Result<serde_json::Value> {
     debug!("Fetching available Flutter releases from: {}", url);
-    let response = reqwest::get(&url)
+    let response = http_get(url)
         .await
         .context("Failed to fetch list of available versions")?
         .error_for_status()?;
 
+    response.json().await.context("Invalid JSON")
+}
+
+/// Read the cached feed if it exists and was fetched from `url`
+async fn read_cached_releases(path: &Path, url: &str) -> Option<CachedReleasesFeed> {
+    let content = fs::read_to_string(path).await.ok()?;
+    let cached: CachedReleasesFeed = serde_json::from_str(&content)
+        .inspect_err(|e| debug!("Ignoring unreadable releases cache {}: {}", path.display(), e))
+        .ok()?;
+
+    (cached.url == url).then_some(cached)
+}
+
+async fn write_cached_releases(path: &Path, cached: &CachedReleasesFeed) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).await?;
+    }
+
+    // Concurrent readers never see a partial file, and each writer has its own temp file
+    utils::write_file_atomic(path, serde_json::to_vec(cached)?)
+}
+
+fn releases_cache_age(cached: &CachedReleasesFeed) -> Duration {
+    (Utc::now() - cached.fetched_at).to_std().unwrap_or_default()
+}
+
+fn parse_releases(feed: serde_json::Value) -> Result<FlutterReleases> {
     debug!("Parsing releases JSON response");
-    let parsed: FlutterReleasesResponse = response.json().await.context("Invalid JSON")?;
+    let parsed: FlutterReleasesResponse = serde_json::from_value(feed).context("Invalid JSON")?;
 
     let mut seen = HashSet::new();
     let mut versions = vec![];
@@ -192,31 +1049,173 @@ pub async fn list_available_versions() -> Result<FlutterReleases> {
         }
     }
 
-    return Ok(FlutterReleases {
-        current_releases: CurrentReleases {
-            stable: versions
-                .iter()
-                .find(|r| r.hash == parsed.current_release.stable)
-                .unwrap()
-                .clone(),
-            beta: versions
-                .iter()
-                .find(|r| r.hash == parsed.current_release.beta)
-                .unwrap()
-                .clone(),
-            dev: versions
-                .iter()
-                .find(|r| r.hash == parsed.current_release.dev)
-                .unwrap()
-                .clone(),
-        },
+    // The feed can briefly reference a current hash missing from its releases
+    // during channel transitions
+    let find_current = |channel: &str, hash: &str| {
+        versions.iter().find(|r| r.hash == hash).cloned().with_context(|| {
+            format!(
+                "The releases feed lists {} as the current {} release, but has no release with that hash. \
+                 Try again later",
+                hash, channel
+            )
+        })
+    };
+
+    let current_releases = CurrentReleases {
+        stable: find_current("stable", &parsed.current_release.stable)?,
+        beta: find_current("beta", &parsed.current_release.beta)?,
+        dev: parsed
+            .current_release
+            .dev
+            .as_deref()
+            .and_then(|hash| versions.iter().find(|r| r.hash == hash).cloned()),
+    };
+
+    Ok(FlutterReleases {
+        current_releases,
         releases: versions,
-    });
+    })
 }
 
-/// Get the engine hash used by a specific Flutter version
-/// Returns None if the version is not installed or the engine.stamp file is missing
-pub async fn get_engine_hash_for_version(version: &str) -> Result<Option<String>> {
+/// Resolve a partial version like "3.24" (or "3") to its newest release
+///
+/// Prefers stable releases, falling back to pre-releases when no stable
+/// release matches. `latest`, `latest-beta` and `latest-dev` resolve to the
+/// current release of their channel. Channels, exact versions, fork versions
+/// and other input pass through unchanged.
+pub async fn resolve_version(input: &str) -> Result<String> {
+    if let Some((_, channel)) = LATEST_ALIASES.iter().find(|(alias, _)| *alias == input) {
+        let releases = list_available_versions().await?;
+        let release = releases.current_release(channel)?;
+        debug!("Resolved {} to the current {} release {}", input, channel, release.version);
+        return Ok(release.version.clone());
+    }
+
+    let is_partial = (1..=2).contains(&input.split('.').count())
+        && input.split('.').all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()));
+    if !is_partial {
+        return Ok(input.to_string());
+    }
+
+    let releases = list_available_versions().await?;
+    let release = newest_matching_release(&releases.releases, input).with_context(|| {
+        format!(
+            "No release matches {}. Run 'fvm-rs releases --channel all' to list available versions",
+            input
+        )
+    })?;
+
+    debug!("Resolved partial version {} to {}", input, release.version);
+    Ok(release.version.clone())
+}
+
+/// The newest release whose version starts with `partial` (e.g. "3.24" matches
+/// "3.24.x" but not "3.240.0"), preferring stable releases
+fn newest_matching_release<'a>(releases: &'a [FlutterRelease], partial: &str) -> Option<&'a FlutterRelease> {
+    let prefix = format!("{}.", partial);
+    let newest = |stable_only: bool| {
+        releases
+            .iter()
+            .filter(|r| r.version.starts_with(&prefix) && (!stable_only || r.channel == "stable"))
+            .max_by(|a, b| utils::compare_versions(&a.version, &b.version))
+    };
+
+    newest(true).or_else(|| newest(false))
+}
+
+/// Find the newest stable release whose bundled Dart SDK satisfies a pub constraint
+///
+/// `constraint` uses pubspec.yaml syntax, e.g. ">=3.2.0 <4.0.0", "^3.2.0" or "any".
+/// Releases without a Dart SDK version in the feed are skipped.
+pub async fn resolve_dart_constraint(constraint: &str) -> Result<FlutterRelease> {
+    let requirement = parse_dart_constraint(constraint)?;
+    let releases = list_available_versions().await?;
+
+    let stable_with_dart: Vec<(&FlutterRelease, semver::Version)> = releases
+        .releases
+        .iter()
+        .filter(|r| r.channel == "stable")
+        .filter_map(|r| Some((r, release_dart_version(r)?)))
+        .collect();
+
+    let matching = stable_with_dart
+        .iter()
+        .filter(|(_, dart)| requirement.matches(dart))
+        .max_by(|(a, _), (b, _)| utils::compare_versions(&a.version, &b.version));
+    if let Some((release, dart)) = matching {
+        debug!("Dart SDK constraint {} resolved to Flutter {} (Dart {})", constraint, release.version, dart);
+        return Ok((*release).clone());
+    }
+
+    // Explain against the range of Dart SDKs stable releases ship
+    let newest = stable_with_dart
+        .iter()
+        .max_by(|(a, _), (b, _)| utils::compare_versions(&a.version, &b.version));
+    let oldest = stable_with_dart
+        .iter()
+        .min_by(|(a, _), (b, _)| utils::compare_versions(&a.version, &b.version));
+    match (oldest, newest) {
+        (Some((oldest, oldest_dart)), Some((newest, newest_dart))) => Err(anyhow!(
+            "No stable Flutter release bundles a Dart SDK matching {}. \
+             Stable releases bundle Dart {} (Flutter {}) through Dart {} (Flutter {})",
+            constraint,
+            oldest_dart,
+            oldest.version,
+            newest_dart,
+            newest.version
+        )),
+        _ => Err(anyhow!(
+            "No stable Flutter release bundles a Dart SDK matching {}: the releases feed lists no Dart SDK versions",
+            constraint
+        )),
+    }
+}
+
+/// Convert a pub version constraint into a semver requirement
+///
+/// Pub separates comparators with spaces and treats a bare version as exact,
+/// while semver separates them with commas and treats a bare version as a caret.
+fn parse_dart_constraint(constraint: &str) -> Result<semver::VersionReq> {
+    let comparators: Vec<String> = constraint
+        .split_whitespace()
+        .map(|part| match part {
+            "any" => "*".to_string(),
+            _ if part.starts_with(|c: char| c.is_ascii_digit()) => format!("={}", part),
+            _ => part.to_string(),
+        })
+        .collect();
+
+    if comparators.is_empty() {
+        anyhow::bail!("The Dart SDK constraint is empty");
+    }
+
+    semver::VersionReq::parse(&comparators.join(", "))
+        .with_context(|| format!("Invalid Dart SDK constraint: {}", constraint))
+}
+
+/// Parse a release's Dart SDK version, e.g. "3.6.0" or "3.6.0 (build 3.6.0-334.4.beta)"
+fn release_dart_version(release: &FlutterRelease) -> Option<semver::Version> {
+    let version = release.dart_sdk_version.as_deref()?.split_whitespace().next()?;
+    semver::Version::parse(version).ok()
+}
+
+/// Find the release of a channel that was current on a given date
+///
+/// Picks the latest release of `channel` whose release date is on or before `date`.
+pub async fn resolve_release_as_of(date: NaiveDate, channel: &str) -> Result<FlutterRelease> {
+    let releases = list_available_versions().await?;
+
+    releases
+        .releases
+        .into_iter()
+        .filter(|r| r.channel == channel && r.release_date.date_naive() <= date)
+        .max_by_key(|r| r.release_date)
+        .ok_or_else(|| anyhow!("No {} release found on or before {}", channel, date))
+}
+
+/// Get the engine hash used by a specific Flutter version
+/// Returns None if the version is not installed or the engine.stamp file is missing
+pub async fn get_engine_hash_for_version(version: &str) -> Result<Option<String>> {
     let flutter_dir = utils::flutter_version_dir(version)?;
     let stamp_file = flutter_dir.join("bin").join("cache").join("engine.stamp");
 
@@ -232,13 +1231,23 @@ pub async fn get_engine_hash_for_version(version: &str) -> Result<Option<String>
 
 /// Result of cleaning up unused engines
 pub struct EngineCleanupResult {
-    pub removed_engines: Vec<String>,
+    pub removed_engines: Vec<(String, u64)>, // (hash, size_in_bytes)
     pub failed_removals: Vec<(String, String)>, // (hash, error_message)
 }
 
 /// Clean up engine caches that are no longer used by any installed Flutter version
 /// Returns details about removed and failed engines
-pub async fn cleanup_unused_engines() -> Result<EngineCleanupResult> {
+///
+/// Versions in `removed_versions` are treated as already uninstalled, so a dry
+/// run can preview what removing them would free. With `dry_run`, the result
+/// lists the engines that would be removed but nothing is deleted.
+pub async fn cleanup_unused_engines(
+    removed_versions: &[String],
+    dry_run: bool,
+) -> Result<EngineCleanupResult> {
+    if !dry_run {
+        ensure_cache_writable()?;
+    }
     let engine_dir = utils::shared_engine_dir()?;
     debug!("Checking for unused engines in: {}", engine_dir.display());
 
@@ -256,6 +1265,9 @@ pub async fn cleanup_unused_engines() -> Result<EngineCleanupResult> {
     let mut used_engines = HashSet::new();
 
     for version in installed_versions {
+        if removed_versions.contains(&version) {
+            continue;
+        }
         if let Some(hash) = get_engine_hash_for_version(&version).await? {
             debug!("Version {} uses engine hash: {}", version, hash);
             used_engines.insert(hash);
@@ -279,11 +1291,26 @@ pub async fn cleanup_unused_engines() -> Result<EngineCleanupResult> {
         if let Some(hash) = path.file_name().and_then(|s| s.to_str()) {
             if !used_engines.contains(hash) {
                 // This engine is not used by any Flutter version, delete it
-                debug!("Removing unused engine: {}", hash);
+                // Measure before removal so the caller can report reclaimed space
+                let size_path = path.clone();
+                let size = task::spawn_blocking(move || utils::dir_size(&size_path))
+                    .await?
+                    .unwrap_or_else(|e| {
+                        warn!("Failed to calculate size of engine {}: {}", hash, e);
+                        0
+                    });
+
+                if dry_run {
+                    debug!("Would remove unused engine: {} ({} bytes)", hash, size);
+                    removed_engines.push((hash.to_string(), size));
+                    continue;
+                }
+
+                debug!("Removing unused engine: {} ({} bytes)", hash, size);
                 match fs::remove_dir_all(&path).await {
                     Ok(_) => {
                         debug!("Successfully removed engine: {}", hash);
-                        removed_engines.push(hash.to_string());
+                        removed_engines.push((hash.to_string(), size));
                     }
                     Err(e) => {
                         warn!("Failed to remove engine {}: {}", hash, e);
@@ -296,13 +1323,21 @@ pub async fn cleanup_unused_engines() -> Result<EngineCleanupResult> {
         }
     }
 
-    return Ok(EngineCleanupResult {
+    Ok(EngineCleanupResult {
         removed_engines,
         failed_removals,
-    });
+    })
+}
+
+impl EngineCleanupResult {
+    /// Total disk space reclaimed by removed engines, in bytes
+    pub fn reclaimed_bytes(&self) -> u64 {
+        self.removed_engines.iter().map(|(_, size)| size).sum()
+    }
 }
 
 pub async fn uninstall(version: &str) -> Result<Option<String>> {
+    ensure_cache_writable()?;
     let flutter_dir = utils::flutter_version_dir(version)?;
     debug!("Uninstalling Flutter version: {}", version);
 
@@ -321,9 +1356,12 @@ pub async fn uninstall(version: &str) -> Result<Option<String>> {
     debug!("Removing directory: {}", flutter_dir.display());
     fs::remove_dir_all(&flutter_dir).await?;
 
+    // Drop any verification marker so cached checks don't trust a removed version
+    let _ = fs::remove_file(utils::verified_marker_path(version)?).await;
+
     // Remove the worktree from git
-    let shared_repo_path = utils::shared_flutter_dir()?;
-    let worktree_name = format!("fvm-{}", version);
+    let shared_repo_path = shared_repo_dir(version)?;
+    let worktree_name = worktree_name(version);
     debug!("Pruning git worktree: {}", worktree_name);
 
     // Spawn blocking task for git operations
@@ -345,10 +1383,11 @@ pub async fn uninstall(version: &str) -> Result<Option<String>> {
     .await??;
 
     debug!("Successfully uninstalled Flutter version: {}", version);
-    return Ok(engine_hash);
+    Ok(engine_hash)
 }
 
-fn verify_installed(version: &str) -> Result<bool> {
+/// Check whether a version is installed with a runnable `flutter` entry point
+pub fn verify_installed(version: &str) -> Result<bool> {
     let flutter_root = utils::flutter_version_dir(version)?;
 
     if !flutter_root.exists() {
@@ -365,53 +1404,662 @@ fn verify_installed(version: &str) -> Result<bool> {
         return Ok(false);
     }
 
-    return Ok(true);
+    // A commit-pinned master must still be checked out at its commit
+    if let Some(prefix) = pinned_master_commit(version) {
+        return Ok(installed_commit(version)?.is_some_and(|commit| commit.starts_with(&prefix)));
+    }
+
+    Ok(true)
+}
+
+/// Check that an installed version is ready to build with
+///
+/// Confirms the SDK is installed and that its engine link resolves to the
+/// engine the SDK expects. Returns the problems found (empty when ready).
+pub async fn validate_installation(version: &str) -> Result<Vec<String>> {
+    if !verify_installed(version)? {
+        return Ok(vec![format!("not installed (run: fvm-rs install {})", version)]);
+    }
+
+    let flutter_dir = utils::flutter_version_dir(version)?;
+    let mut problems = Vec::new();
+
+    match get_engine_hash_for_version(version).await? {
+        None => problems.push("no engine linked (bin/cache/engine.stamp missing)".to_string()),
+        Some(linked) => {
+            if let Ok(expected) = read_checkout_engine_hash(&flutter_dir).await
+                && expected != linked
+            {
+                problems.push(format!(
+                    "linked engine {} does not match bin/internal/engine.version ({})",
+                    linked, expected
+                ));
+            }
+        }
+    }
+
+    // exists() follows the symlink, so a dangling link into a removed engine fails
+    let dart_sdk = flutter_dir.join("bin").join("cache").join("dart-sdk");
+    if !dart_sdk.exists() {
+        problems.push(format!("{} does not resolve to an engine", dart_sdk.display()));
+    }
+
+    Ok(problems)
+}
+
+/// Check whether an installed version's engine link is broken
+///
+/// True when bin/cache/dart-sdk doesn't resolve although the version was
+/// installed with an engine (the link or engine.stamp exists), e.g. after the
+/// engine cache was deleted or the cache directory moved. Versions installed
+/// without an engine link are not broken.
+pub async fn has_broken_engine_link(version: &str) -> Result<bool> {
+    let dart_sdk = utils::flutter_version_dir(version)?.join("bin").join("cache").join("dart-sdk");
+    if dart_sdk.exists() {
+        return Ok(false);
+    }
+
+    Ok(dart_sdk.symlink_metadata().is_ok() || get_engine_hash_for_version(version).await?.is_some())
+}
+
+/// Relink the engine of an installed version whose engine link is broken
+///
+/// Links the engine named by the checkout's bin/internal/engine.version,
+/// downloading it first if it is no longer cached. Returns the linked hash, or
+/// None if the link wasn't broken.
+pub async fn repair_engine_link(version: &str) -> Result<Option<String>> {
+    if !has_broken_engine_link(version).await? {
+        return Ok(None);
+    }
+    ensure_cache_writable()?;
+
+    let flutter_dir = utils::flutter_version_dir(version)?;
+    let engine_hash = read_checkout_engine_hash(&flutter_dir).await?;
+    let engine_dir = utils::shared_engine_hash_dir(&engine_hash)?;
+    debug!("Repairing engine link of {} to {}", version, engine_hash);
+
+    install_engine(&engine_dir, &InstallOptions::default()).await?;
+
+    let dart_sdk_link = flutter_dir.join("bin").join("cache").join("dart-sdk");
+    if dart_sdk_link.symlink_metadata().is_ok() {
+        fs::remove_file(&dart_sdk_link)
+            .await
+            .context("Failed to remove the broken engine link")?;
+    }
+    link_engine_to_flutter(&engine_dir, &flutter_dir).await?;
+
+    let metadata = InstallMetadata {
+        engine_hash: Some(engine_hash.clone()),
+        ..install_metadata(version).await?
+    };
+    write_install_metadata(version, &metadata).await;
+
+    Ok(Some(engine_hash))
+}
+
+/// Get the binaries of an installed version that must be executable to run it
+///
+/// Covers the flutter/dart entry points and the linked engine's Dart binaries.
+/// Paths that don't exist are skipped; they are reported by other checks.
+#[cfg(unix)]
+fn version_binaries(flutter_dir: &Path) -> Vec<PathBuf> {
+    let bin = flutter_dir.join("bin");
+    let dart_sdk_bin = bin.join("cache").join("dart-sdk").join("bin");
+
+    [
+        bin.join("flutter"),
+        bin.join("dart"),
+        dart_sdk_bin.join("dart"),
+        dart_sdk_bin.join("dartaotruntime"),
+    ]
+    .into_iter()
+    .filter(|path| path.exists())
+    .collect()
+}
+
+/// Find binaries of an installed version that are missing the executable bit
+///
+/// Extraction with a bad umask or restores from permission-stripping backups
+/// leave them non-executable, so running flutter fails with "Permission denied".
+/// Always empty on Windows, which has no executable bit.
+pub fn non_executable_binaries(version: &str) -> Result<Vec<PathBuf>> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let flutter_dir = utils::flutter_version_dir(version)?;
+        let mut non_executable = vec![];
+        for path in version_binaries(&flutter_dir) {
+            // Follows the dart-sdk symlink into the shared engine
+            let mode = std::fs::metadata(&path)
+                .with_context(|| format!("Failed to read permissions of {}", path.display()))?
+                .permissions()
+                .mode();
+            if mode & 0o111 == 0 {
+                debug!("{} is not executable (mode {:o})", path.display(), mode);
+                non_executable.push(path);
+            }
+        }
+        Ok(non_executable)
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = version;
+        Ok(vec![])
+    }
+}
+
+/// Set the executable bit on a file for everyone who can read it (like `chmod +x`)
+#[cfg(unix)]
+pub fn make_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = std::fs::metadata(path)
+        .with_context(|| format!("Failed to read permissions of {}", path.display()))?
+        .permissions();
+    let mode = permissions.mode();
+    permissions.set_mode(mode | ((mode & 0o444) >> 2));
+
+    std::fs::set_permissions(path, permissions)
+        .with_context(|| format!("Failed to make {} executable", path.display()))
+}
+
+/// Windows has no executable bit, so there is nothing to fix
+#[cfg(not(unix))]
+pub fn make_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
+
+/// Get the commit SHA an installed version is checked out at
+///
+/// Returns None if the version is not installed or is not a git checkout.
+pub fn installed_commit(version: &str) -> Result<Option<String>> {
+    let flutter_root = utils::flutter_version_dir(version)?;
+    let Ok(repo) = Repository::open(&flutter_root) else {
+        return Ok(None);
+    };
+
+    let commit = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .context("Failed to read HEAD commit")?;
+    Ok(Some(commit.id().to_string()))
 }
 
-async fn install(version: &str) -> Result<()> {
+/// File in a version's directory recording how it was installed
+const INSTALL_METADATA_FILE: &str = ".fvm-rs-meta.json";
+
+/// How an installed version was installed
+///
+/// Recorded by `install` in `{version_dir}/.fvm-rs-meta.json`. For versions
+/// installed before the file existed, [`install_metadata`] derives what it can
+/// and leaves `recorded` false.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub channel: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installed_at: Option<DateTime<Utc>>,
+    /// None for versions installed without an engine link
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub engine_hash: Option<String>,
+    /// Flutter or fork repository the version was checked out from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo_url: Option<String>,
+    /// Whether this was read from the metadata file rather than derived
+    #[serde(skip)]
+    pub recorded: bool,
+}
+
+impl InstallMetadata {
+    /// Metadata for a version being installed now
+    fn new(channel: &str, engine_hash: Option<&str>, repo_url: &str) -> Self {
+        Self {
+            channel: Some(channel.to_string()),
+            installed_at: Some(Utc::now()),
+            engine_hash: engine_hash.map(str::to_string),
+            repo_url: Some(repo_url.to_string()),
+            recorded: true,
+        }
+    }
+}
+
+/// Write the install metadata file of an installed (or upgraded) version
+///
+/// Best effort: the installation itself already succeeded, so failures are
+/// only logged. The file is excluded from git so the checkout stays clean for
+/// `flutter upgrade`.
+async fn write_install_metadata(version: &str, metadata: &InstallMetadata) {
+    let result = async {
+        let version_dir = utils::flutter_version_dir(version)?;
+        let json = serde_json::to_string_pretty(metadata)?;
+        task::spawn_blocking(move || {
+            exclude_from_git(&version_dir, INSTALL_METADATA_FILE)?;
+            utils::write_file_atomic(&version_dir.join(INSTALL_METADATA_FILE), json)
+        })
+        .await?
+    };
+
+    match result.await {
+        Ok(()) => debug!("Recorded install metadata for {}", version),
+        Err(e) => warn!("Failed to record install metadata for {}: {:#}", version, e),
+    }
+}
+
+/// Add `name` to the info/exclude file shared by a checkout's repository
+fn exclude_from_git(checkout: &Path, name: &str) -> Result<()> {
+    let repo = Repository::open(checkout).context("Failed to open worktree repository")?;
+    let exclude_path = repo.commondir().join("info").join("exclude");
+    let pattern = format!("/{}", name);
+
+    let existing = std::fs::read_to_string(&exclude_path).unwrap_or_default();
+    if existing.lines().any(|line| line.trim() == pattern) {
+        return Ok(());
+    }
+
+    if let Some(parent) = exclude_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let separator = if existing.is_empty() || existing.ends_with('\n') { "" } else { "\n" };
+    std::fs::write(&exclude_path, format!("{}{}{}\n", existing, separator, pattern))
+        .with_context(|| format!("Failed to update {}", exclude_path.display()))?;
+    Ok(())
+}
+
+/// Get how an installed version was installed
+///
+/// Reads the recorded metadata file. Older installs without one (or with an
+/// unreadable one) fall back to the worktree's channel branch, origin URL and
+/// creation time, and the linked engine stamp.
+pub async fn install_metadata(version: &str) -> Result<InstallMetadata> {
+    let version_dir = utils::flutter_version_dir(version)?;
+    let metadata_path = version_dir.join(INSTALL_METADATA_FILE);
+
+    match fs::read_to_string(&metadata_path).await {
+        Ok(json) => match serde_json::from_str::<InstallMetadata>(&json) {
+            Ok(metadata) => {
+                return Ok(InstallMetadata {
+                    recorded: true,
+                    ..metadata
+                });
+            }
+            Err(e) => debug!("Ignoring invalid {}: {}", metadata_path.display(), e),
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            debug!("No install metadata for {}, deriving it", version);
+        }
+        Err(e) => debug!("Failed to read {}: {}", metadata_path.display(), e),
+    }
+
+    let repo_url = Repository::open(&version_dir).ok().and_then(|repo| {
+        let remote = repo.find_remote("origin").ok()?;
+        remote.url().map(str::to_string)
+    });
+    // A worktree's .git file is written once, when the worktree is created
+    let installed_at = std::fs::metadata(version_dir.join(".git"))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .map(DateTime::<Utc>::from);
+
+    Ok(InstallMetadata {
+        channel: installed_channel(version),
+        installed_at,
+        engine_hash: get_engine_hash_for_version(version).await?,
+        repo_url,
+        recorded: false,
+    })
+}
+
+/// Get the channel branch an installed version's worktree is checked out on
+///
+/// Returns None if the version isn't installed or HEAD isn't a known channel branch.
+fn installed_channel(version: &str) -> Option<String> {
+    let repo = Repository::open(utils::flutter_version_dir(version).ok()?).ok()?;
+    let head = repo.head().ok()?;
+    let branch = head.shorthand().filter(|_| head.is_branch())?;
+
+    config_manager::is_channel(branch).then(|| branch.to_string())
+}
+
+/// Fail unless `commit` matches the expected SHA (full or abbreviated to 7+ characters)
+fn check_expected_commit(version: &str, commit: &str, expected: &str) -> Result<()> {
+    let expected = expected.to_lowercase();
+    if expected.len() < 7 {
+        anyhow::bail!("Expected commit '{}' is too short; use at least 7 characters", expected);
+    }
+
+    if !commit.starts_with(&expected) {
+        anyhow::bail!(
+            "Flutter {} resolves to commit {}, but {} was expected. The tag may have been moved",
+            version,
+            commit,
+            expected
+        );
+    }
+
+    Ok(())
+}
+
+/// Result of running a post-install smoke test against a Flutter version
+pub struct SmokeTestResult {
+    pub exit_code: i32,
+    /// Version reported by `flutter --version --machine`, if it could be parsed
+    pub reported_version: Option<String>,
+    /// Version we expect to see reported (None for channels, which move over time)
+    pub expected_version: Option<String>,
+}
+
+impl SmokeTestResult {
+    /// Whether the SDK ran successfully and reported the expected version
+    pub fn passed(&self) -> bool {
+        if self.exit_code != 0 {
+            return false;
+        }
+
+        match &self.expected_version {
+            Some(expected) => self.reported_version.as_deref() == Some(expected.as_str()),
+            None => true,
+        }
+    }
+}
+
+/// Platforms `flutter precache` can download artifacts for (each maps to a `--<platform>` flag)
+pub const PRECACHE_PLATFORMS: &[&str] = &["android", "ios", "web", "linux", "macos", "windows", "fuchsia", "universal"];
+
+/// Run `flutter precache` for an installed version, streaming its output
+///
+/// With no platforms, Flutter downloads the artifacts for its default platforms.
+/// Returns the exit code of `flutter precache`.
+pub async fn precache(version: &str, platforms: &[String]) -> Result<i32> {
+    let flutter_path = utils::flutter_version_dir(version)?;
+    let mut args = vec!["precache".to_string()];
+    args.extend(platforms.iter().map(|platform| format!("--{}", platform)));
+    debug!("Precaching Flutter {} artifacts: flutter {}", version, args.join(" "));
+
+    task::spawn_blocking(move || utils::execute_with_flutter_path("flutter", &args, &flutter_path)).await?
+}
+
+/// Run `flutter --version --machine` with an installed version and check the result
+///
+/// Confirms the SDK actually starts and, for release versions, that it reports
+/// the version it was installed as.
+pub async fn smoke_test(version: &str) -> Result<SmokeTestResult> {
+    let flutter_path = utils::flutter_version_dir(version)?;
+    debug!("Smoke testing Flutter {} at: {}", version, flutter_path.display());
+
+    let actual_version = release_name(version);
+    let expected_version = if config_manager::is_channel(&actual_version) {
+        None
+    } else {
+        Some(actual_version)
+    };
+
+    let output = task::spawn_blocking(move || {
+        utils::output_with_flutter_path(
+            "flutter",
+            &["--version".to_string(), "--machine".to_string()],
+            &flutter_path,
+        )
+    })
+    .await??;
+
+    let exit_code = output.status.code().unwrap_or(1);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    // The first run may print tool build messages before the JSON payload
+    let reported_version = stdout
+        .find('{')
+        .and_then(|start| serde_json::from_str::<serde_json::Value>(&stdout[start..]).ok())
+        .and_then(|json| json.get("frameworkVersion")?.as_str().map(|s| s.to_string()));
+
+    debug!(
+        "Smoke test exit code: {}, reported version: {:?}, expected: {:?}",
+        exit_code, reported_version, expected_version
+    );
+
+    Ok(SmokeTestResult {
+        exit_code,
+        reported_version,
+        expected_version,
+    })
+}
+
+/// Fail with a clear message if the FVM cache can't be modified
+///
+/// Baked container images often ship a shared, read-only SDK cache. Installed
+/// versions can still be listed and run from it; only changes are refused.
+pub fn ensure_cache_writable() -> Result<()> {
+    let root = utils::cache_root_dir()?;
+    if !utils::is_writable(&root) {
+        anyhow::bail!(
+            "The FVM cache at {} is read-only. Installing, removing and switching global versions \
+             is unavailable; installed versions can still be listed and run",
+            root.display()
+        );
+    }
+    Ok(())
+}
+
+async fn install(version: &str, options: &InstallOptions) -> Result<()> {
+    ensure_cache_writable()?;
+
+    // Another process may be installing the same version; wait and reuse its result
+    let _version_lock = acquire_install_lock(&utils::flutter_version_dir(version)?, options).await?;
+    if verify_installed(version)? {
+        debug!("Flutter {} was installed by another process", version);
+        return Ok(());
+    }
+
     debug!("Starting installation of Flutter version: {}", version);
+    options.emit(InstallEvent::Resolving {
+        version: version.to_string(),
+    });
 
     // Get the repository URL (may be a fork)
     let repo_url = get_flutter_repo_url(version).await?;
     debug!("Using Flutter repository: {}", repo_url);
 
-    let engine_hash = fetch_engine_hash(version).await?;
-    debug!("Engine hash for version {}: {}", version, engine_hash);
+    if options.skip_engine_link {
+        let flutter_dir = utils::flutter_version_dir(version)?;
+        let channel = get_channel_for_version(version).await?;
+        debug!("Skipping engine download and linking for version {}", version);
+
+        install_flutter(&flutter_dir, version, &channel, &repo_url, options).await?;
+        write_install_metadata(version, &InstallMetadata::new(&channel, None, &repo_url)).await;
+
+        debug!("Successfully completed installation of Flutter {} without engine", version);
+        return Ok(());
+    }
 
-    let engine_dir = utils::shared_engine_hash_dir(&engine_hash)?;
     let flutter_dir = utils::flutter_version_dir(version)?;
-    debug!("Engine directory: {}", engine_dir.display());
     debug!("Flutter directory: {}", flutter_dir.display());
 
     // Get the channel for this version before installation
     let channel = get_channel_for_version(version).await?;
     debug!("Version {} belongs to channel: {}", version, channel);
 
-    debug!("Installing engine and Flutter in parallel");
-    let (engine_result, flutter_result) =
-        tokio::join!(install_engine(&engine_dir), install_flutter(&flutter_dir, version, &channel, &repo_url),);
+    let engine_hash = if pinned_master_commit(version).is_some() {
+        // The engine for a pinned commit is only known once that commit is checked out
+        install_flutter(&flutter_dir, version, &channel, &repo_url, options).await?;
+        let engine_hash = read_checkout_engine_hash(&flutter_dir).await?;
+        debug!("Engine hash for version {}: {}", version, engine_hash);
 
-    engine_result?;
-    flutter_result?;
+        install_engine(&utils::shared_engine_hash_dir(&engine_hash)?, options).await?;
+        engine_hash
+    } else {
+        let engine_hash = fetch_engine_hash(version).await?;
+        debug!("Engine hash for version {}: {}", version, engine_hash);
+
+        let engine_dir = utils::shared_engine_hash_dir(&engine_hash)?;
+        debug!("Installing engine and Flutter in parallel");
+        let (engine_result, flutter_result) = tokio::join!(
+            install_engine(&engine_dir, options),
+            install_flutter(&flutter_dir, version, &channel, &repo_url, options),
+        );
+
+        engine_result?;
+        flutter_result?;
+        engine_hash
+    };
+
+    let engine_dir = utils::shared_engine_hash_dir(&engine_hash)?;
+    debug!("Engine directory: {}", engine_dir.display());
 
     debug!("Linking engine to Flutter installation");
+    options.emit(InstallEvent::Linking {
+        hash: engine_hash.clone(),
+    });
     link_engine_to_flutter(&engine_dir, &flutter_dir).await?;
+    write_install_metadata(version, &InstallMetadata::new(&channel, Some(&engine_hash), &repo_url)).await;
 
     debug!("Successfully completed installation of Flutter {}", version);
-    return Ok(());
+    Ok(())
+}
+
+/// Result of upgrading a channel install
+pub enum UpgradeOutcome {
+    /// The worktree already matched the channel tip
+    UpToDate { commit: String },
+    /// The worktree was reset to the new channel tip
+    Upgraded {
+        commit: String,
+        /// New engine hash, when it changed and was re-linked
+        new_engine: Option<String>,
+    },
+}
+
+/// Fast-forward an installed channel version (e.g. "stable") to the channel tip
+///
+/// Fetches the shared repository, resets the worktree's channel branch to the
+/// fetched tip and re-links the engine if its hash changed. Release versions
+/// are refused, since they are pinned by definition.
+pub async fn upgrade_channel(version: &str, options: &InstallOptions) -> Result<UpgradeOutcome> {
+    let channel = strip_fork_alias(version);
+    if !config_manager::is_channel(&channel) {
+        anyhow::bail!(config_manager::RELEASE_UPGRADE_ERROR);
+    }
+    if !verify_installed(version)? {
+        anyhow::bail!("Flutter {} is not installed. Run 'fvm-rs install {}' first", version, version);
+    }
+    ensure_cache_writable()?;
+
+    let repo_url = get_flutter_repo_url(version).await?;
+    let shared_dir = shared_repo_dir(version)?;
+    let flutter_dir = utils::flutter_version_dir(version)?;
+    let _version_lock = acquire_install_lock(&flutter_dir, options).await?;
+
+    let outcome = {
+        let repo_lock = shared_repo_lock(&shared_dir);
+        let _repo_guard = repo_lock.lock().await;
+        let _repo_file_lock = acquire_install_lock(&shared_dir, options).await?;
+
+        options.emit(InstallEvent::Cloning { url: repo_url.clone() });
+        let scope = FetchScope::for_version(version, &channel).await?;
+        ensure_shared_repo(&repo_url, &shared_dir, &scope, &[], options).await?;
+
+        let flutter_dir = flutter_dir.clone();
+        task::spawn_blocking(move || -> Result<UpgradeOutcome> {
+            let repo = Repository::open(&flutter_dir).context("Failed to open worktree repository")?;
+            let tip = repo
+                .find_reference(&format!("refs/heads/{}", channel))
+                .and_then(|reference| reference.peel_to_commit())
+                .map_err(|e| git_error(e, format!("Failed to find channel branch '{}'", channel), None))?;
+
+            // Fetches move the shared channel branch without touching the worktree's
+            // files, so compare what is checked out (the index) with the tip
+            let checked_out = repo.index()?.write_tree()?;
+            if checked_out == tip.tree_id() {
+                return Ok(UpgradeOutcome::UpToDate {
+                    commit: tip.id().to_string(),
+                });
+            }
+
+            debug!("Resetting {} worktree to {}", channel, tip.id());
+            repo.set_head(&format!("refs/heads/{}", channel))?;
+            repo.reset(tip.as_object(), git2::ResetType::Hard, None)?;
+
+            Ok(UpgradeOutcome::Upgraded {
+                commit: tip.id().to_string(),
+                new_engine: None,
+            })
+        })
+        .await??
+    };
+
+    let UpgradeOutcome::Upgraded { commit, .. } = outcome else {
+        return Ok(outcome);
+    };
+
+    // Versions installed without an engine stay that way
+    let old_engine = match get_engine_hash_for_version(version).await? {
+        Some(hash) if !options.skip_engine_link => hash,
+        _ => return Ok(UpgradeOutcome::Upgraded { commit, new_engine: None }),
+    };
+    let new_engine = read_checkout_engine_hash(&flutter_dir).await?;
+    if new_engine == old_engine {
+        return Ok(UpgradeOutcome::Upgraded { commit, new_engine: None });
+    }
+
+    debug!("Engine changed from {} to {}", old_engine, new_engine);
+    let engine_dir = utils::shared_engine_hash_dir(&new_engine)?;
+    install_engine(&engine_dir, options).await?;
+
+    let dart_sdk_link = flutter_dir.join("bin").join("cache").join("dart-sdk");
+    if dart_sdk_link.symlink_metadata().is_ok() {
+        fs::remove_file(&dart_sdk_link)
+            .await
+            .context("Failed to remove the old engine link")?;
+    }
+    options.emit(InstallEvent::Linking { hash: new_engine.clone() });
+    link_engine_to_flutter(&engine_dir, &flutter_dir).await?;
+    // The install date and source stay; only the engine changed
+    let metadata = InstallMetadata {
+        engine_hash: Some(new_engine.clone()),
+        ..install_metadata(version).await?
+    };
+    write_install_metadata(version, &metadata).await;
+
+    Ok(UpgradeOutcome::Upgraded {
+        commit,
+        new_engine: Some(new_engine),
+    })
+}
+
+/// Read the engine hash from a checked-out Flutter SDK's bin/internal/engine.version
+async fn read_checkout_engine_hash(flutter_dir: &Path) -> Result<String> {
+    let path = flutter_dir.join("bin").join("internal").join("engine.version");
+    let hash = fs::read_to_string(&path)
+        .await
+        .with_context(|| format!("Could not read {}", path.display()))?;
+
+    Ok(hash.trim().to_string())
+}
+
+/// Get a release's engine hash, from the installed checkout when available
+pub async fn release_engine_hash(version: &str) -> Result<String> {
+    if verify_installed(version)?
+        && let Ok(hash) = read_checkout_engine_hash(&utils::flutter_version_dir(version)?).await
+    {
+        return Ok(hash);
+    }
+
+    fetch_engine_hash(version).await
 }
 
 async fn fetch_engine_hash(version: &str) -> Result<String> {
-    // Strip fork alias if present
-    let actual_version = strip_fork_alias(version);
+    // Strip fork alias and channel suffix if present
+    let actual_version = release_name(version);
 
+    // Storage mirrors only serve flutter_infra_release/, so this always goes to GitHub
     let url = format!(
-        "https://raw.githubusercontent.com/flutter/flutter/{}/bin/internal/engine.version",
-        actual_version
+        "{}/flutter/flutter/{}/bin/internal/engine.version",
+        DEFAULT_RAW_CONTENT_URL, actual_version
     );
     debug!("Fetching engine hash from: {}", url);
 
-    let response = reqwest::get(&url)
+    let response = http_get(&url)
         .await
         .context("Failed to fetch engine hash")?
         .error_for_status()?;
@@ -424,51 +2072,197 @@ async fn fetch_engine_hash(version: &str) -> Result<String> {
         .to_string();
 
     debug!("Fetched engine hash: {}", hash);
-    return Ok(hash);
+    Ok(hash)
 }
 
-async fn install_engine(engine_dir: &PathBuf) -> Result<()> {
-    if engine_dir.exists() {
-        debug!("Engine already cached at: {}", engine_dir.display());
+/// Check a downloaded engine archive is complete and matches its published SHA-256
+///
+/// The checksum is read from a `<archive>.sha256` file next to the archive.
+/// Google's storage doesn't publish one, so there only the size is checked;
+/// mirrors that do publish checksums get full verification.
+async fn verify_engine_archive(url: &str, bytes: &[u8], expected_size: Option<u64>) -> Result<()> {
+    if let Some(expected_size) = expected_size
+        && expected_size != bytes.len() as u64
+    {
+        anyhow::bail!(
+            "Engine archive download is incomplete: got {} of {} bytes from {}. Retry the install",
+            bytes.len(),
+            expected_size,
+            url
+        );
+    }
+
+    let Some(expected) = fetch_engine_checksum(url).await? else {
+        debug!("No checksum published for {}, skipping SHA-256 verification", url);
         return Ok(());
+    };
+
+    let actual: String = ring::digest::digest(&ring::digest::SHA256, bytes)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+
+    if actual != expected {
+        anyhow::bail!(
+            "Engine archive checksum mismatch for {}: expected SHA-256 {}, got {}. \
+             The download may be corrupted; retry the install (or pass --no-verify for mirrors with stale checksums)",
+            url,
+            expected,
+            actual
+        );
+    }
+
+    debug!("Engine archive SHA-256 verified: {}", actual);
+    Ok(())
+}
+
+/// Fetch the SHA-256 published next to an engine archive, if any
+///
+/// Accepts both a bare hash and `sha256sum` output ("<hash>  <file>").
+async fn fetch_engine_checksum(url: &str) -> Result<Option<String>> {
+    let checksum_url = format!("{}.sha256", url);
+    debug!("Fetching engine checksum from: {}", checksum_url);
+
+    let response = http_get(&checksum_url)
+        .await
+        .context("Failed to fetch engine checksum")?;
+
+    // Missing checksums are expected: most storage doesn't publish them
+    if matches!(response.status().as_u16(), 403 | 404) {
+        return Ok(None);
+    }
+
+    let text = response
+        .error_for_status()
+        .context("Failed to fetch engine checksum")?
+        .text()
+        .await
+        .context("Could not read engine checksum")?;
+
+    let hash = text.split_whitespace().next().unwrap_or("").to_lowercase();
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        anyhow::bail!("Invalid SHA-256 checksum at {}: {:?}", checksum_url, text.trim());
     }
 
-    let platform = match std::env::consts::OS {
+    Ok(Some(hash))
+}
+
+/// OS/arch combinations Flutter publishes Dart SDK engine archives for
+const SUPPORTED_ENGINE_PLATFORMS: &[&str] = &[
+    "darwin-x64",
+    "darwin-arm64",
+    "linux-x64",
+    "linux-arm64",
+    "windows-x64",
+    "windows-arm64",
+];
+
+/// Resolve the engine platform string (e.g. "linux-arm64") used in engine archive names
+///
+/// An explicit override (from `enginePlatform` config or FVM_ENGINE_PLATFORM) is used
+/// as-is, which allows testing or exotic setups. Otherwise the current OS and arch are
+/// mapped to Flutter's naming and checked against the published combinations.
+fn engine_platform(override_platform: Option<String>) -> Result<String> {
+    if let Some(platform) = override_platform {
+        debug!("Using engine platform override: {}", platform);
+        return Ok(platform);
+    }
+
+    let os = std::env::consts::OS;
+    let arch = std::env::consts::ARCH;
+
+    let flutter_os = match os {
         "macos" => "darwin", // match Flutter conventions
         other => other,
     };
-    let arch = match std::env::consts::ARCH {
-        "x86_64" => Ok("x64"),
-        "aarch64" | "arm64" | "armv8" => Ok("arm64"),
-        other => Err(anyhow!("Unsupported platform {}", other)),
-    }?;
+    let flutter_arch = match arch {
+        "x86_64" => "x64",
+        "aarch64" | "arm64" | "armv8" => "arm64",
+        other => other,
+    };
+
+    let platform = format!("{}-{}", flutter_os, flutter_arch);
+    if !SUPPORTED_ENGINE_PLATFORMS.contains(&platform.as_str()) {
+        return Err(anyhow!(
+            "Unsupported platform: Flutter does not publish Dart SDK engines for {} on {}. \
+            Supported platforms: {}. \
+            Set 'enginePlatform' in the global config to override.",
+            os,
+            arch,
+            SUPPORTED_ENGINE_PLATFORMS.join(", ")
+        ));
+    }
+
+    debug!("Detected engine platform: {}", platform);
+    Ok(platform)
+}
+
+async fn install_engine(engine_dir: &Path, options: &InstallOptions) -> Result<()> {
+    // Checked under the lock, so a concurrent extraction is waited for rather than reused half done
+    let _engine_lock = acquire_install_lock(engine_dir, options).await?;
+    if engine_dir.exists() {
+        debug!("Engine already cached at: {}", engine_dir.display());
+        return Ok(());
+    }
 
-    let engine_hash = engine_dir.file_name().unwrap().to_str().unwrap();
-    debug!("Installing engine {} for {}-{}", engine_hash, platform, arch);
+    let config = config_manager::GlobalConfig::read().await?;
+    let platform = engine_platform(config.get_engine_platform())?;
 
-    // Check for FLUTTER_STORAGE_BASE_URL override
-    let base_url = std::env::var("FLUTTER_STORAGE_BASE_URL")
-        .unwrap_or_else(|_| "https://storage.googleapis.com".to_string());
+    let engine_hash = engine_dir.file_name().unwrap().to_str().unwrap();
+    debug!("Installing engine {} for {}", engine_hash, platform);
 
+    let base_url = storage_base_url().await?;
     let url = format!(
-        "{}/flutter_infra_release/flutter/{}/dart-sdk-{}-{}.zip",
-        base_url, engine_hash, platform, arch
+        "{}/flutter_infra_release/flutter/{}/dart-sdk-{}.zip",
+        base_url, engine_hash, platform
     );
     debug!("Downloading engine from: {}", url);
 
-    let response = reqwest::get(&url)
+    let mut response = http_get(&url)
         .await
         .context("Failed to fetch engine zip")?
         .error_for_status()
         .context("Failed to fetch engine zip")?;
 
     debug!("Downloading engine zip archive");
-    let bytes = response
-        .bytes()
-        .await
-        .context("Failed to read engine zip")?;
+    let total_bytes = response.content_length();
+    let mut bytes = Vec::with_capacity(total_bytes.unwrap_or(0) as usize);
+    let mut last_reported = 0;
+
+    while let Some(chunk) = response.chunk().await.context("Failed to read engine zip")? {
+        bytes.extend_from_slice(&chunk);
+
+        // Report roughly every megabyte to avoid flooding progress consumers
+        let downloaded = bytes.len() as u64;
+        if downloaded - last_reported >= 1024 * 1024 || Some(downloaded) == total_bytes {
+            last_reported = downloaded;
+            options.emit(InstallEvent::DownloadingEngine {
+                hash: engine_hash.to_string(),
+                downloaded_bytes: downloaded,
+                total_bytes,
+            });
+        }
+    }
+
+    if last_reported != bytes.len() as u64 {
+        options.emit(InstallEvent::DownloadingEngine {
+            hash: engine_hash.to_string(),
+            downloaded_bytes: bytes.len() as u64,
+            total_bytes,
+        });
+    }
+
+    if options.skip_verify {
+        debug!("Skipping engine archive verification");
+    } else {
+        verify_engine_archive(&url, &bytes, total_bytes).await?;
+    }
 
     debug!("Extracting engine archive ({} bytes)", bytes.len());
+    options.emit(InstallEvent::Extracting {
+        hash: engine_hash.to_string(),
+    });
     let cursor = Cursor::new(bytes);
     let mut archive = ZipArchive::new(cursor)?;
 
@@ -491,10 +2285,10 @@ async fn install_engine(engine_dir: &PathBuf) -> Result<()> {
             if file.is_dir() {
                 fs::create_dir_all(&outpath).await?;
             } else {
-                if let Some(p) = outpath.parent() {
-                    if !p.exists() {
-                        fs::create_dir_all(&p).await?;
-                    }
+                if let Some(p) = outpath.parent()
+                    && !p.exists()
+                {
+                    fs::create_dir_all(&p).await?;
                 }
                 let mut outfile = std::fs::File::create(&outpath)?;
                 std::io::copy(&mut file, &mut outfile)?;
@@ -511,14 +2305,29 @@ async fn install_engine(engine_dir: &PathBuf) -> Result<()> {
     }
 
     debug!("Successfully installed engine to: {}", engine_dir.display());
-    return Ok(());
+    Ok(())
 }
 
-async fn install_flutter(version_dir: &PathBuf, version: &str, channel: &str, repo_url: &str) -> Result<()> {
-    let shared_dir = utils::shared_flutter_dir()?;
+async fn install_flutter(
+    version_dir: &Path,
+    version: &str,
+    channel: &str,
+    repo_url: &str,
+    options: &InstallOptions,
+) -> Result<()> {
+    let shared_dir = shared_repo_dir(version)?;
+    let repo_lock = shared_repo_lock(&shared_dir);
+    let _repo_guard = repo_lock.lock().await;
+    let _repo_file_lock = acquire_install_lock(&shared_dir, options).await?;
+
     debug!("Setting up Flutter repository from: {}", repo_url);
+    options.emit(InstallEvent::Cloning {
+        url: repo_url.to_string(),
+    });
 
-    let repo = ensure_shared_repo(repo_url, &shared_dir).await?;
+    let scope = FetchScope::for_version(version, channel).await?;
+    let cached_refs = cached_release_refs(version, channel);
+    let repo = ensure_shared_repo(repo_url, &shared_dir, &scope, &cached_refs, options).await?;
 
     let parent_dir = version_dir.parent().unwrap();
     debug!("Creating parent directory: {}", parent_dir.display());
@@ -527,40 +2336,80 @@ async fn install_flutter(version_dir: &PathBuf, version: &str, channel: &str, re
     debug!("Creating git worktree for version: {} (channel: {})", version, channel);
     debug!("Worktree will be created at: {}", version_dir.display());
 
-    let version_dir_clone = version_dir.clone();
+    // Resolve the commit in the shared repository first, so a mismatch aborts
+    // before anything is checked out
+    let commit_id = match pinned_master_commit(version) {
+        Some(prefix) => resolve_master_commit(&repo, &prefix)?,
+        // Channel installs track their branch tip; there is no tag to find
+        None if config_manager::is_channel(&strip_fork_alias(version)) => repo
+            .refname_to_id(&format!("refs/heads/{}", channel))
+            .map_err(|e| git_error(e, format!("Failed to find channel branch '{}'", channel), None))?,
+        None => {
+            let tag = release_name(version);
+            let commit_ref = format!("refs/tags/{}", tag);
+            debug!("Finding version tag: {}", commit_ref);
+            repo.find_reference(&commit_ref)
+                .and_then(|reference| reference.peel_to_commit())
+                .map_err(|e| {
+                    git_error(
+                        e,
+                        format!("Failed to find tag {}", tag),
+                        Some(format!(
+                            "The repository has no {} tag. Check the version with 'fvm-rs releases --channel all'; \
+                             for a fork, make sure the tag was pushed",
+                            tag
+                        )),
+                    )
+                })?
+                .id()
+        }
+    };
+
+    if let Some(expected) = &options.expected_commit {
+        check_expected_commit(version, &commit_id.to_string(), expected)?;
+    }
+    options.emit(InstallEvent::Checkout {
+        commit: commit_id.to_string(),
+    });
+
+    let version_dir_clone = version_dir.to_path_buf();
     let version_string = version.to_string();
     let channel_string = channel.to_string();
 
     task::spawn_blocking(move || {
-        let worktree_name = format!("fvm-{}", version_string);
+        let worktree_name = worktree_name(&version_string);
         debug!("Creating worktree '{}' using channel branch '{}'", worktree_name, channel_string);
 
         // Find the channel branch reference (e.g., "refs/heads/stable")
         let branch_ref_name = format!("refs/heads/{}", channel_string);
         debug!("Finding channel branch reference: {}", branch_ref_name);
-        let branch_ref = repo.find_reference(&branch_ref_name)
-            .context("Failed to find channel branch")?;
+        let branch_ref = repo.find_reference(&branch_ref_name).map_err(|e| {
+            git_error(
+                e,
+                format!("Failed to find channel branch '{}'", channel_string),
+                Some(format!(
+                    "The repository has no '{}' branch. Forks often only have some channels; \
+                     check which with 'fvm-rs fork verify <alias>'",
+                    channel_string
+                )),
+            )
+        })?;
 
         // Create the worktree using the channel branch
         // This makes Flutter doctor recognize the correct channel
         let mut opts = git2::WorktreeAddOptions::new();
         opts.reference(Some(&branch_ref));
 
-        let worktree = repo
-            .worktree(&worktree_name, &version_dir_clone, Some(&opts))
-            .context("Failed to create worktree")?;
+        let worktree = with_stale_lock_retry(&repo, || {
+            repo.worktree(&worktree_name, &version_dir_clone, Some(&opts))
+        })
+        .map_err(|e| git_error(e, "Failed to create worktree", None))?;
 
         debug!("Opening worktree repository at: {}", worktree.path().display());
         let worktree_repo =
             Repository::open(worktree.path()).context("Failed to open worktree repository")?;
 
-        // Find the specific version tag
-        let commit_ref = format!("refs/tags/{}", version_string);
-        debug!("Finding version tag: {}", commit_ref);
-
-        let commit = worktree_repo
-            .find_reference(&commit_ref)?
-            .peel_to_commit()?;
+        let commit = worktree_repo.find_commit(commit_id)?;
 
         // Reset to the specific version while staying on the channel branch
         debug!("Resetting {} branch to commit {} (version {})", channel_string, commit.id(), version_string);
@@ -578,15 +2427,372 @@ async fn install_flutter(version_dir: &PathBuf, version: &str, channel: &str, re
             .context("Failed to set branch merge")?;
 
         debug!("Successfully set up Flutter version {} on channel {} with upstream tracking", version_string, channel_string);
-        return Ok::<_, anyhow::Error>(());
+        Ok::<_, anyhow::Error>(())
     })
     .await??;
 
     debug!("Successfully set up Flutter at: {}", version_dir.display());
-    return Ok(());
+    Ok(())
+}
+
+const NETWORK_HINT: &str = "Could not reach the repository. Check the URL, your network connection \
+                            and proxy (fvm-rs config --proxy)";
+
+const AUTH_HINT: &str = "Authentication failed. For SSH URLs, add your key to ssh-agent (ssh-add) or ~/.ssh; \
+                         for HTTPS, set FVM_GIT_TOKEN or configure a git credential helper";
+
+/// Wrap a git error with context and, for common failures, an actionable hint
+///
+/// With a hint, the message reads "<context>: <git error with class and code>"
+/// followed by the hint on its own line, so it survives being flattened into a
+/// single string. `not_found_hint` replaces the generic hint for missing references.
+fn git_error(
+    error: impl Into<anyhow::Error>,
+    context: impl std::fmt::Display,
+    not_found_hint: Option<String>,
+) -> anyhow::Error {
+    let error = error.into();
+    let hint = error.downcast_ref::<git2::Error>().and_then(|git_error| {
+        use git2::{ErrorClass, ErrorCode};
+
+        let message = git_error.message().to_lowercase();
+        match (git_error.class(), git_error.code()) {
+            (_, ErrorCode::Auth) => Some(AUTH_HINT.to_string()),
+            (ErrorClass::Ssh | ErrorClass::Http, _) if message.contains("auth") => {
+                Some(AUTH_HINT.to_string())
+            }
+            (ErrorClass::Net | ErrorClass::Ssl | ErrorClass::Http | ErrorClass::Ssh, _) => {
+                Some(NETWORK_HINT.to_string())
+            }
+            (ErrorClass::Os, _) if message.contains("connect") => Some(NETWORK_HINT.to_string()),
+            (_, ErrorCode::NotFound) => not_found_hint.or_else(|| {
+                Some("The reference does not exist in the repository".to_string())
+            }),
+            _ if is_git_lock_error(git_error) => Some(
+                "A git lock is blocking the repository. Another fvm-rs or git process may be \
+                 running; if none is, delete the lock file named in the error"
+                    .to_string(),
+            ),
+            _ => None,
+        }
+    });
+
+    match hint {
+        Some(hint) => anyhow!("{}: {}\n  Hint: {}", context, error, hint),
+        None => error.context(context.to_string()),
+    }
+}
+
+/// Git lock files younger than this may still belong to a running git operation
+const STALE_GIT_LOCK_AGE: Duration = Duration::from_secs(10 * 60);
+
+/// Run a git operation, retrying once after clearing stale locks if it was blocked
+///
+/// Interrupted installs leave `*.lock` files and half-created worktree entries
+/// behind, which make every later install fail until they are removed.
+fn with_stale_lock_retry<T>(
+    repo: &Repository,
+    mut operation: impl FnMut() -> Result<T, git2::Error>,
+) -> Result<T> {
+    match operation() {
+        Err(e) if is_git_lock_error(&e) => {
+            debug!("Git operation blocked: {}", e.message());
+            remove_stale_git_locks(repo.path())?;
+            prune_missing_worktrees(repo)?;
+
+            debug!("Retrying git operation after removing stale locks");
+            Ok(operation()?)
+        }
+        result => Ok(result?),
+    }
+}
+
+/// Check whether a git error may be caused by a leftover lock or worktree entry
+fn is_git_lock_error(error: &git2::Error) -> bool {
+    matches!(error.code(), git2::ErrorCode::Locked | git2::ErrorCode::Exists)
+        || error.message().contains(".lock")
+        || error.message().contains("locked")
+}
+
+/// Remove lock files left in a git directory by interrupted operations
+///
+/// Git lock files don't record their owner, so age tells them apart: a recent
+/// lock is treated as a concurrent operation and reported instead of removed.
+fn remove_stale_git_locks(git_dir: &Path) -> Result<()> {
+    let mut locks = Vec::new();
+    collect_git_locks(git_dir, &mut locks);
+
+    for lock in &locks {
+        let age = std::fs::metadata(lock)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .and_then(|modified| modified.elapsed().ok())
+            .unwrap_or_default();
+
+        if age < STALE_GIT_LOCK_AGE {
+            anyhow::bail!(
+                "Another git operation is running on {} (lock {} is {}s old). \
+                 Wait for it to finish and retry; if nothing is running, delete the lock file",
+                git_dir.display(),
+                lock.display(),
+                age.as_secs()
+            );
+        }
+    }
+
+    for lock in locks {
+        warn!("Removing stale git lock left by an interrupted operation: {}", lock.display());
+        std::fs::remove_file(&lock)
+            .with_context(|| format!("Failed to remove stale lock {}", lock.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Collect `*.lock` files and worktree `locked` markers, skipping the object store
+fn collect_git_locks(dir: &Path, locks: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+
+        if path.is_dir() {
+            if name != "objects" {
+                collect_git_locks(&path, locks);
+            }
+        } else {
+            let in_worktree_entry =
+                dir.parent().and_then(|p| p.file_name()) == Some("worktrees".as_ref());
+            if name.ends_with(".lock") || (name == "locked" && in_worktree_entry) {
+                locks.push(path);
+            }
+        }
+    }
+}
+
+/// Get the worktree entries whose directory no longer exists, as (name, path)
+fn missing_worktrees(repo: &Repository) -> Result<Vec<(String, PathBuf)>> {
+    let mut missing = Vec::new();
+    for name in repo.worktrees()?.iter().flatten() {
+        let worktree = repo.find_worktree(name)?;
+        if !worktree.path().exists() {
+            missing.push((name.to_string(), worktree.path().to_path_buf()));
+        }
+    }
+    Ok(missing)
+}
+
+/// Remove worktree entries whose directory no longer exists
+///
+/// Returns the pruned entries as (name, path).
+fn prune_missing_worktrees(repo: &Repository) -> Result<Vec<(String, PathBuf)>> {
+    let missing = missing_worktrees(repo)?;
+    for (name, path) in &missing {
+        debug!("Pruning worktree '{}' with missing directory {}", name, path.display());
+        repo.find_worktree(name)?.prune(Some(
+            git2::WorktreePruneOptions::new()
+                .valid(true)
+                .locked(true)
+                .working_tree(false),
+        ))?;
+    }
+
+    Ok(missing)
+}
+
+/// A worktree entry in a shared repository whose version directory is gone
+///
+/// Left behind when a version directory is deleted by hand or an install is
+/// interrupted; git still considers the worktree's branch checked out.
+pub struct OrphanedWorktree {
+    /// Shared repository holding the entry
+    pub repo: PathBuf,
+    pub name: String,
+    /// Directory the worktree was checked out to
+    pub path: PathBuf,
+}
+
+/// Get the shared bare repositories in the cache (upstream Flutter and forks)
+pub fn shared_repo_dirs() -> Result<Vec<PathBuf>> {
+    let mut repos = vec![utils::shared_flutter_dir()?];
+    if let Ok(entries) = std::fs::read_dir(utils::shared_dir()?.join("forks")) {
+        let mut forks: Vec<_> = entries.flatten().map(|entry| entry.path()).filter(|path| path.is_dir()).collect();
+        forks.sort();
+        repos.extend(forks);
+    }
+    repos.retain(|path| path.exists());
+    Ok(repos)
+}
+
+/// Find (or with `prune`, remove) orphaned worktree entries in all shared repositories
+///
+/// Pruning takes each repository's install lock, so it waits for installs
+/// running in other processes.
+pub async fn orphaned_worktrees(prune: bool) -> Result<Vec<OrphanedWorktree>> {
+    let mut orphaned = Vec::new();
+
+    for repo_dir in shared_repo_dirs()? {
+        let repo_lock = shared_repo_lock(&repo_dir);
+        let _repo_guard = repo_lock.lock().await;
+        let _repo_file_lock = if prune {
+            Some(acquire_install_lock(&repo_dir, &InstallOptions::default()).await?)
+        } else {
+            None
+        };
+
+        let dir = repo_dir.clone();
+        let entries = task::spawn_blocking(move || -> Result<_> {
+            let repo = Repository::open_bare(&dir)
+                .with_context(|| format!("Failed to open shared repository {}", dir.display()))?;
+            if prune {
+                prune_missing_worktrees(&repo)
+            } else {
+                missing_worktrees(&repo)
+            }
+        })
+        .await??;
+
+        orphaned.extend(entries.into_iter().map(|(name, path)| OrphanedWorktree {
+            repo: repo_dir.clone(),
+            name,
+            path,
+        }));
+    }
+
+    Ok(orphaned)
+}
+
+/// Resolve a (possibly abbreviated) commit that must be on the master branch
+fn resolve_master_commit(repo: &Repository, prefix: &str) -> Result<git2::Oid> {
+    debug!("Resolving master commit: {}", prefix);
+    let commit = repo
+        .revparse_single(prefix)
+        .and_then(|object| object.peel_to_commit())
+        .with_context(|| format!("Commit {} not found in the Flutter repository", prefix))?;
+
+    let master_tip = repo
+        .find_reference("refs/heads/master")
+        .and_then(|reference| reference.peel_to_commit())
+        .context("Failed to find master branch")?
+        .id();
+
+    if commit.id() != master_tip && !repo.graph_descendant_of(master_tip, commit.id())? {
+        anyhow::bail!("Commit {} is not on the master branch", commit.id());
+    }
+
+    Ok(commit.id())
+}
+
+/// Refs fetched into a shared repository
+enum FetchScope {
+    /// Every branch and tag, with full history
+    All,
+    /// One channel branch and, for releases, its tag, without history (`shallowClone`)
+    Shallow { branch: String, tag: Option<String> },
+}
+
+impl FetchScope {
+    /// Pick the refs an install or upgrade of `version` needs
+    ///
+    /// Commit-pinned master versions always need the full history, since the
+    /// commit is looked up in master's ancestry.
+    async fn for_version(version: &str, channel: &str) -> Result<Self> {
+        let shallow = config_manager::GlobalConfig::read().await?.get_shallow_clone();
+        if !shallow || pinned_master_commit(version).is_some() {
+            return Ok(FetchScope::All);
+        }
+
+        let tag = (!config_manager::is_channel(&strip_fork_alias(version))).then(|| release_name(version));
+        Ok(FetchScope::Shallow {
+            branch: channel.to_string(),
+            tag,
+        })
+    }
+
+    fn refspecs(&self) -> Vec<String> {
+        match self {
+            FetchScope::All => vec!["refs/heads/*:refs/heads/*".to_string(), "refs/tags/*:refs/tags/*".to_string()],
+            FetchScope::Shallow { branch, tag } => {
+                // Forced, since a fetch without history can't prove a fast-forward
+                let mut refspecs = vec![format!("+refs/heads/{0}:refs/heads/{0}", branch)];
+                if let Some(tag) = tag {
+                    refspecs.push(format!("refs/tags/{0}:refs/tags/{0}", tag));
+                }
+                refspecs
+            }
+        }
+    }
+}
+
+/// Fetch `scope` from the repository's origin remote
+///
+/// Shallow scopes only limit the depth of repositories that are already
+/// shallow, so enabling `shallowClone` never truncates an existing full clone.
+/// A full fetch into a shallow repository restores its history.
+fn fetch_scope(
+    repo: &Repository,
+    url: &str,
+    scope: &FetchScope,
+    proxy: Option<&str>,
+    options: &InstallOptions,
+) -> Result<()> {
+    let mut remote = repo.find_remote("origin").context("Failed to get remote")?;
+
+    let mut fetch_options = git_fetch_options_with_progress(proxy, options);
+    match scope {
+        FetchScope::All => {
+            fetch_options.download_tags(git2::AutotagOption::All);
+            if repo.is_shallow() {
+                debug!("Fetching the full history into shallow repository");
+                fetch_options.depth(i32::MAX); // GIT_FETCH_DEPTH_UNSHALLOW
+            }
+        }
+        FetchScope::Shallow { .. } => {
+            fetch_options.download_tags(git2::AutotagOption::None);
+            if repo.is_shallow() {
+                fetch_options.depth(1);
+            }
+        }
+    }
+
+    let refspecs = scope.refspecs();
+    debug!("Fetching {} from {}", refspecs.join(" "), url);
+    with_stale_lock_retry(repo, || remote.fetch(&refspecs, Some(&mut fetch_options), None))
+        .map_err(|e| git_error(e, format!("Failed to fetch {}", url), None))?;
+
+    Ok(())
+}
+
+/// Get the refs that make fetching unnecessary for installing `version`
+///
+/// Release tags never move, so a shared repository that already has the tag
+/// and its channel branch can be used as is. Channels and commit-pinned master
+/// versions always fetch to see the latest commits.
+fn cached_release_refs(version: &str, channel: &str) -> Vec<String> {
+    if pinned_master_commit(version).is_some() || config_manager::is_channel(&strip_fork_alias(version)) {
+        return vec![];
+    }
+
+    vec![format!("refs/tags/{}", release_name(version)), format!("refs/heads/{}", channel)]
 }
 
-async fn ensure_shared_repo(url: &str, path: &PathBuf) -> Result<git2::Repository> {
+/// Open (fetching updates) or clone the shared repository at `path`
+///
+/// The fetch is skipped when every ref in `cached_refs` already exists, unless
+/// `options.refresh` is set. An empty `cached_refs` always fetches.
+async fn ensure_shared_repo(
+    url: &str,
+    path: &PathBuf,
+    scope: &FetchScope,
+    cached_refs: &[String],
+    options: &InstallOptions,
+) -> Result<git2::Repository> {
+    let proxy = config_manager::GlobalConfig::read().await?.get_proxy();
+
     if path.exists() {
         debug!("Shared repository already exists at: {}", path.display());
         let repo_result = Repository::open_bare(path.clone());
@@ -597,21 +2803,17 @@ async fn ensure_shared_repo(url: &str, path: &PathBuf) -> Result<git2::Repositor
                 let mut config = repo.config()?;
                 config.set_bool("advice.detachedHead", false)?;
 
-                debug!("Fetching updates from remote: {}", url);
-                let mut remote = repo.find_remote("origin").context("Failed to get remote")?;
-
-                let mut fetch_options = FetchOptions::new();
-                fetch_options.download_tags(git2::AutotagOption::All);
+                let cached = !options.refresh
+                    && !cached_refs.is_empty()
+                    && cached_refs.iter().all(|name| repo.refname_to_id(name).is_ok());
+                if cached {
+                    debug!("{} already present, skipping fetch", cached_refs.join(" and "));
+                } else {
+                    debug!("Fetching updates from remote: {}", url);
+                    fetch_scope(&repo, url, scope, proxy.as_deref(), options)?;
 
-                remote
-                    .fetch(
-                        &["refs/heads/*:refs/heads/*", "refs/tags/*:refs/tags/*"],
-                        Some(&mut fetch_options),
-                        None,
-                    )
-                    .context("Failed to fetch remote")?;
-
-                debug!("Successfully fetched updates from remote");
+                    debug!("Successfully fetched updates from remote");
+                }
             }
 
             return Ok(repo);
@@ -628,12 +2830,41 @@ async fn ensure_shared_repo(url: &str, path: &PathBuf) -> Result<git2::Repositor
 
     let url = url.to_string();
     let path_clone = path.clone();
+    let options = options.clone();
+    let shallow_refspecs = match scope {
+        FetchScope::All => None,
+        FetchScope::Shallow { .. } => Some(scope.refspecs()),
+    };
 
     let repo = tokio::task::spawn_blocking(move || {
-        let repo = RepoBuilder::new()
-            .bare(true)
-            .clone(&url, &path_clone)
-            .context("Failed to clone repository")?;
+        let repo = match shallow_refspecs {
+            // A clone fetches every ref, so a shallow one is an init plus a narrow fetch
+            Some(refspecs) => {
+                debug!("Shallow-cloning {}", refspecs.join(" "));
+                let repo = Repository::init_bare(&path_clone)?;
+                let mut remote = repo.remote("origin", &url)?;
+                let mut fetch_options = git_fetch_options_with_progress(proxy.as_deref(), &options);
+                fetch_options.download_tags(git2::AutotagOption::None);
+                fetch_options.depth(1);
+                remote
+                    .fetch(&refspecs, Some(&mut fetch_options), None)
+                    .map_err(|e| git_error(e, format!("Failed to clone {}", url), None))?;
+                drop(remote);
+                repo
+            }
+            None => {
+                let repo = RepoBuilder::new()
+                    .bare(true)
+                    .fetch_options(git_fetch_options_with_progress(proxy.as_deref(), &options))
+                    .clone(&url, &path_clone)
+                    .map_err(|e| git_error(e, format!("Failed to clone {}", url), None))?;
+
+                // A clone only creates the default branch locally, but worktrees are
+                // created from the channel branches (later fetches map them directly)
+                create_local_channel_branches(&repo)?;
+                repo
+            }
+        };
 
         // Configure advice.detachedHead=false to suppress warnings
         debug!("Configuring git advice.detachedHead=false");
@@ -645,10 +2876,31 @@ async fn ensure_shared_repo(url: &str, path: &PathBuf) -> Result<git2::Repositor
     .await??;
 
     debug!("Successfully cloned shared repository to: {}", path.display());
-    return Ok(repo);
+    Ok(repo)
+}
+
+/// Create a local branch for every remote-tracking branch that doesn't have one
+fn create_local_channel_branches(repo: &Repository) -> Result<()> {
+    for branch in repo.branches(Some(git2::BranchType::Remote))? {
+        let (branch, _) = branch?;
+        let Some(name) = branch.name()?.and_then(|name| name.strip_prefix("origin/")) else {
+            continue;
+        };
+        if name == "HEAD" || repo.find_branch(name, git2::BranchType::Local).is_ok() {
+            continue;
+        }
+
+        debug!("Creating local branch {} from origin/{}", name, name);
+        let commit = branch.get().peel_to_commit()?;
+        repo.branch(name, &commit, false)?;
+    }
+    Ok(())
 }
 
-async fn link_engine_to_flutter(engine_dir: &PathBuf, flutter_dir: &PathBuf) -> Result<()> {
+/// Link a shared engine into a Flutter checkout's bin/cache
+///
+/// Writes the engine stamp files Flutter checks and symlinks dart-sdk to the engine.
+pub async fn link_engine_to_flutter(engine_dir: &Path, flutter_dir: &Path) -> Result<()> {
     let cache_dir = flutter_dir.join("bin").join("cache");
     debug!("Creating cache directory: {}", cache_dir.display());
     fs::create_dir_all(&cache_dir).await?;
@@ -686,11 +2938,101 @@ async fn link_engine_to_flutter(engine_dir: &PathBuf, flutter_dir: &PathBuf) ->
     Ok(())
 }
 
+/// Point a project's .fvm/flutter_sdk symlink at an installed Flutter version
+///
+/// Replaces any existing link. Returns the path the symlink points to.
+pub async fn link_project_sdk(project_root: &Path, version: &str) -> Result<PathBuf> {
+    let flutter_version_dir = utils::flutter_version_dir(version)?;
+
+    if !flutter_version_dir.exists() {
+        anyhow::bail!(
+            "Flutter version {} is not installed. Run 'fvm-rs install {}' first.",
+            version,
+            version
+        );
+    }
+
+    let fvm_dir = project_root.join(".fvm");
+    fs::create_dir_all(&fvm_dir)
+        .await
+        .context("Failed to create .fvm directory")?;
+
+    let sdk_link = fvm_dir.join("flutter_sdk");
+    if let Ok(metadata) = sdk_link.symlink_metadata() {
+        // A real directory here is usually an SDK copied in by hand; don't delete it
+        if metadata.is_dir() && !metadata.file_type().is_symlink() {
+            anyhow::bail!(
+                "{} is a directory, not a link fvm-rs manages. Move or delete it, then run 'fvm-rs use {}' again",
+                sdk_link.display(),
+                version
+            );
+        }
+
+        debug!("Removing existing project SDK link: {}", sdk_link.display());
+        // Windows directory links and junctions are removed as directories
+        let removed = match fs::remove_file(&sdk_link).await {
+            Err(_) if cfg!(windows) => fs::remove_dir(&sdk_link).await,
+            result => result,
+        };
+        removed.context("Failed to remove existing .fvm/flutter_sdk")?;
+    }
+
+    debug!("Creating project SDK symlink: {} -> {}",
+           sdk_link.display(),
+           flutter_version_dir.display());
+
+    let target = flutter_version_dir.clone();
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::symlink;
+        tokio::task::spawn_blocking(move || symlink(&target, &sdk_link))
+            .await?
+            .context("Failed to create .fvm/flutter_sdk symlink")?;
+    }
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::symlink_dir;
+        tokio::task::spawn_blocking(move || {
+            // Symlinks need Developer Mode or admin rights; junctions don't
+            symlink_dir(&target, &sdk_link).or_else(|e| {
+                debug!("Symlink failed ({}), creating a directory junction instead", e);
+                create_junction(&target, &sdk_link)
+            })
+        })
+        .await?
+        .context("Failed to create .fvm/flutter_sdk link")?;
+    }
+
+    Ok(flutter_version_dir)
+}
+
+/// Create a directory junction with `mklink /J`
+#[cfg(windows)]
+fn create_junction(target: &Path, link: &Path) -> std::io::Result<()> {
+    let status = std::process::Command::new("cmd")
+        .arg("/C")
+        .arg("mklink")
+        .arg("/J")
+        .arg(link)
+        .arg(target)
+        .stdout(std::process::Stdio::null())
+        .status()?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!("mklink /J exited with {}", status)))
+    }
+}
+
 /// Set a Flutter version as the global default
 ///
 /// Creates a symlink at ~/.fvm-rs/default pointing to the specified version.
 /// The version must be installed first.
 pub async fn set_global_version(version: &str) -> Result<()> {
+    ensure_cache_writable()?;
     let flutter_version_dir = utils::flutter_version_dir(version)?;
 
     // Verify the version is installed
@@ -745,6 +3087,7 @@ pub async fn set_global_version(version: &str) -> Result<()> {
 /// Removes the symlink at ~/.fvm-rs/default.
 /// Returns Ok(false) if no global version was set, Ok(true) if it was removed.
 pub async fn unset_global_version() -> Result<bool> {
+    ensure_cache_writable()?;
     let global_link = utils::get_global_link_path()?;
 
     // Check if symlink exists (using symlink_metadata to avoid following the link)
@@ -761,6 +3104,68 @@ pub async fn unset_global_version() -> Result<bool> {
     }
 }
 
+/// Get the target of the global version link if it points to a missing directory
+///
+/// The link dangles when its version was deleted by hand or the cache moved.
+pub async fn dangling_global_link() -> Result<Option<PathBuf>> {
+    let global_link = utils::get_global_link_path()?;
+    match fs::read_link(&global_link).await {
+        Ok(target) if !global_link.exists() => Ok(Some(target)),
+        _ => Ok(None),
+    }
+}
+
+/// Remove the global version link if it dangles
+///
+/// Returns the missing target of the removed link.
+pub async fn remove_dangling_global_link() -> Result<Option<PathBuf>> {
+    let Some(target) = dangling_global_link().await? else {
+        return Ok(None);
+    };
+
+    let global_link = utils::get_global_link_path()?;
+    debug!("Removing dangling global link: {} -> {}", global_link.display(), target.display());
+    // Windows directory links and junctions are removed as directories
+    let removed = match fs::remove_file(&global_link).await {
+        Err(_) if cfg!(windows) => fs::remove_dir(&global_link).await,
+        result => result,
+    };
+    removed.context("Failed to remove the global link")?;
+
+    Ok(Some(target))
+}
+
+/// Get the global link left in ~/.fvm-rs after `cachePath` moved the cache elsewhere
+///
+/// `global` only maintains the link in the configured cache, so a PATH entry
+/// pointing at the old link silently stops following the global version.
+pub fn orphaned_global_link() -> Result<Option<PathBuf>> {
+    let old_link = utils::fvm_rs_root_dir()?.join("default");
+    if old_link == utils::get_global_link_path()? || old_link.symlink_metadata().is_err() {
+        return Ok(None);
+    }
+    Ok(Some(old_link))
+}
+
+/// Remove the global link orphaned in ~/.fvm-rs by a custom cache path
+///
+/// Returns the path of the removed link.
+pub async fn remove_orphaned_global_link() -> Result<Option<PathBuf>> {
+    let Some(old_link) = orphaned_global_link()? else {
+        return Ok(None);
+    };
+
+    debug!("Removing orphaned global link: {}", old_link.display());
+    // Windows directory links and junctions are removed as directories
+    let removed = match fs::remove_file(&old_link).await {
+        Err(_) if cfg!(windows) => fs::remove_dir(&old_link).await,
+        result => result,
+    };
+    removed.context("Failed to remove the old global link")?;
+
+    Ok(Some(old_link))
+}
+
 /// Get the currently set global version
 ///
 /// Returns the version name if a global version is set, or None.
@@ -785,3 +3190,221 @@ pub async fn get_global_version() -> Result<Option<String>> {
     debug!("No global version configured");
     Ok(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn install_lock_waits_for_first_holder() {
+        let cache = tempfile::tempdir().unwrap();
+        utils::set_cache_root(cache.path().to_path_buf());
+        let version_dir = utils::flutter_version_dir("3.24.0").unwrap();
+
+        let first = acquire_install_lock(&version_dir, &InstallOptions::default())
+            .await
+            .unwrap();
+
+        let waited = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let options = InstallOptions {
+            progress: Some(Arc::new({
+                let waited = waited.clone();
+                move |event| {
+                    if matches!(event, InstallEvent::WaitingForLock { .. }) {
+                        waited.store(true, std::sync::atomic::Ordering::SeqCst);
+                    }
+                }
+            })),
+            ..Default::default()
+        };
+        let second = tokio::spawn({
+            let version_dir = version_dir.clone();
+            async move {
+                let _lock = acquire_install_lock(&version_dir, &options).await.unwrap();
+                version_dir.join("bin/flutter").exists()
+            }
+        });
+
+        // The first holder finishes the install while the second one waits
+        tokio::time::sleep(INSTALL_LOCK_POLL * 2).await;
+        std::fs::create_dir_all(version_dir.join("bin")).unwrap();
+        std::fs::write(version_dir.join("bin/flutter"), "").unwrap();
+        drop(first);
+
+        assert!(second.await.unwrap(), "second holder did not see the finished install");
+        assert!(waited.load(std::sync::atomic::Ordering::SeqCst));
+    }
+
+    fn dart(version: &str) -> semver::Version {
+        semver::Version::parse(version).unwrap()
+    }
+
+    #[test]
+    fn dart_constraint_caret() {
+        let requirement = parse_dart_constraint("^3.4.0").unwrap();
+
+        assert!(requirement.matches(&dart("3.4.0")));
+        assert!(requirement.matches(&dart("3.9.2")));
+        assert!(!requirement.matches(&dart("3.3.4")));
+        assert!(!requirement.matches(&dart("4.0.0")));
+    }
+
+    #[test]
+    fn dart_constraint_range_and_exact() {
+        let range = parse_dart_constraint(">=2.17.0 <4.0.0").unwrap();
+        assert!(range.matches(&dart("2.17.0")));
+        assert!(range.matches(&dart("3.5.0")));
+        assert!(!range.matches(&dart("2.16.2")));
+        assert!(!range.matches(&dart("4.0.0")));
+
+        // Pub treats a bare version as exact, not as a caret
+        let exact = parse_dart_constraint("3.4.0").unwrap();
+        assert!(exact.matches(&dart("3.4.0")));
+        assert!(!exact.matches(&dart("3.4.1")));
+    }
+
+    #[test]
+    fn dart_constraint_any() {
+        let requirement = parse_dart_constraint("any").unwrap();
+
+        assert!(requirement.matches(&dart("2.12.0")));
+        assert!(requirement.matches(&dart("3.6.0")));
+    }
+
+    #[test]
+    fn dart_constraint_rejects_malformed_input() {
+        assert!(parse_dart_constraint("").is_err());
+        assert!(parse_dart_constraint(">=three <4.0.0").is_err());
+        assert!(parse_dart_constraint("^3.4.0 || ^2.0.0").is_err());
+    }
+
+    fn release(version: &str, channel: &str) -> FlutterRelease {
+        FlutterRelease {
+            hash: String::new(),
+            channel: channel.to_string(),
+            version: version.to_string(),
+            dart_sdk_version: None,
+            release_date: DateTime::<Utc>::default(),
+        }
+    }
+
+    #[test]
+    fn partial_version_resolves_to_newest_patch() {
+        let releases = [
+            release("3.22.0", "stable"),
+            release("3.22.3", "stable"),
+            release("3.22.10", "stable"),
+            release("3.24.0", "stable"),
+            release("3.22.11-0.1.pre", "beta"),
+        ];
+
+        let resolved = newest_matching_release(&releases, "3.22").unwrap();
+        assert_eq!(resolved.version, "3.22.10");
+        assert_eq!(newest_matching_release(&releases, "3").unwrap().version, "3.24.0");
+    }
+
+    #[test]
+    fn partial_version_matches_whole_components_only() {
+        // "3.2" is a prefix of "3.22.0" and "3.24.0" as strings, but not as versions
+        let releases = [
+            release("3.2.5", "stable"),
+            release("3.22.0", "stable"),
+            release("3.24.0", "stable"),
+        ];
+        assert_eq!(newest_matching_release(&releases, "3.2").unwrap().version, "3.2.5");
+
+        // Only pre-releases match, so the newest of those wins
+        let releases = [
+            release("3.27.0-0.1.pre", "beta"),
+            release("3.27.0-0.2.pre", "beta"),
+            release("3.24.5", "stable"),
+        ];
+        assert_eq!(newest_matching_release(&releases, "3.27").unwrap().version, "3.27.0-0.2.pre");
+    }
+
+    #[test]
+    fn partial_version_without_match() {
+        let releases = [release("3.22.0", "stable"), release("3.24.0", "stable")];
+
+        assert!(newest_matching_release(&releases, "3.23").is_none());
+        assert!(newest_matching_release(&releases, "4").is_none());
+        assert!(newest_matching_release(&[], "3.22").is_none());
+    }
+
+    #[test]
+    fn channel_suffix_is_split_off() {
+        assert_eq!(parse_channel_suffix("3.22.0@beta"), ("3.22.0".to_string(), Some("beta".to_string())));
+        assert_eq!(parse_channel_suffix("3.22.0@stable"), ("3.22.0".to_string(), Some("stable".to_string())));
+    }
+
+    #[test]
+    fn channel_suffix_leaves_other_versions_alone() {
+        // A bare channel has no suffix to split
+        assert_eq!(parse_channel_suffix("beta"), ("beta".to_string(), None));
+        assert_eq!(parse_channel_suffix("3.22.0"), ("3.22.0".to_string(), None));
+        // Unknown channels and empty suffixes stay part of the version
+        assert_eq!(parse_channel_suffix("3.22.0@nightly"), ("3.22.0@nightly".to_string(), None));
+        assert_eq!(parse_channel_suffix("3.22.0@"), ("3.22.0@".to_string(), None));
+        // Commit-pinned master versions keep their commit
+        assert_eq!(parse_channel_suffix("master@3f2c1a9b0d"), ("master@3f2c1a9b0d".to_string(), None));
+    }
+
+    /// Commit a file on the `stable` branch of a local upstream repository
+    fn commit_upstream(repo: &Repository, contents: &str) -> git2::Oid {
+        std::fs::write(repo.workdir().unwrap().join("VERSION"), contents).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("VERSION")).unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::now("test", "test@localhost").unwrap();
+        let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+        repo.commit(
+            Some("refs/heads/stable"),
+            &signature,
+            &signature,
+            contents,
+            &tree,
+            &parent.iter().collect::<Vec<_>>(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn shared_repo_skips_fetch_when_release_tag_is_cached() {
+        let dir = tempfile::tempdir().unwrap();
+        let upstream = Repository::init_opts(
+            dir.path().join("upstream"),
+            git2::RepositoryInitOptions::new().initial_head("stable"),
+        )
+        .unwrap();
+        let released = commit_upstream(&upstream, "3.24.0");
+        upstream
+            .tag_lightweight("3.24.0", &upstream.find_object(released, None).unwrap(), false)
+            .unwrap();
+
+        let url = dir.path().join("upstream").to_string_lossy().to_string();
+        let shared = dir.path().join("shared");
+        let options = InstallOptions::default();
+        ensure_shared_repo(&url, &shared, &FetchScope::All, &[], &options).await.unwrap();
+
+        // Upstream moves on after the clone
+        let newer = commit_upstream(&upstream, "3.24.1");
+        let stable_tip = |repo: &Repository| repo.refname_to_id("refs/heads/stable").unwrap();
+
+        // The release tag and its channel branch are already there, so nothing is fetched
+        let cached = cached_release_refs("3.24.0", "stable");
+        let repo = ensure_shared_repo(&url, &shared, &FetchScope::All, &cached, &options)
+            .await
+            .unwrap();
+        assert_eq!(stable_tip(&repo), released);
+
+        // A release whose tag is missing fetches and sees the new commit
+        let missing = cached_release_refs("3.24.1", "stable");
+        let repo = ensure_shared_repo(&url, &shared, &FetchScope::All, &missing, &options)
+            .await
+            .unwrap();
+        assert_eq!(stable_tip(&repo), newer);
+
+        // Channels always fetch
+        assert!(cached_release_refs("stable", "stable").is_empty());
+    }
+}
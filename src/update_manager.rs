@@ -0,0 +1,307 @@
+//! Checking GitHub for newer fvm-rs releases and updating the binary in place
+//!
+//! The latest release is looked up at most once per `CHECK_INTERVAL`; the
+//! result is kept in `{cache}/update_check.json` so other runs in between
+//! can report it without going online.
+//!
+//! `self-update` downloads the release asset for this platform
+//! (`fvm-rs-{os}-{arch}`, `.exe` on Windows), checks it against the
+//! `<asset>.sha256` published with it and replaces the running executable.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tracing::debug;
+
+use crate::{config_manager, sdk_manager, utils};
+
+/// GitHub API endpoint for fvm-rs releases
+const RELEASES_API_URL: &str = "https://api.github.com/repos/JakubBatel/fvm-rs/releases";
+
+/// How often GitHub is asked for the latest release
+const CHECK_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Give up on a slow GitHub API instead of hanging around
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The running fvm-rs version
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Result of the last update check, persisted between runs
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UpdateCheckState {
+    checked_at: DateTime<Utc>,
+    /// None when no check has succeeded yet
+    latest_version: Option<String>,
+}
+
+/// A published fvm-rs release
+#[derive(Debug, Clone, Deserialize)]
+pub struct FvmRsRelease {
+    pub tag_name: String,
+    #[serde(default)]
+    assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ReleaseAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+impl FvmRsRelease {
+    /// Release version without the "v" tag prefix
+    pub fn version(&self) -> &str {
+        self.tag_name.trim_start_matches('v')
+    }
+
+    fn asset(&self, name: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+/// Get the releases API URL (`FVM_RS_RELEASES_URL` overrides it, e.g. for mirrors)
+fn releases_api_url() -> String {
+    std::env::var("FVM_RS_RELEASES_URL")
+        .ok()
+        .filter(|url| !url.is_empty())
+        .map(|url| url.trim_end_matches('/').to_string())
+        .unwrap_or_else(|| RELEASES_API_URL.to_string())
+}
+
+/// Get the name of the release asset built for this platform
+pub fn platform_asset_name() -> String {
+    let extension = if cfg!(windows) { ".exe" } else { "" };
+    format!(
+        "fvm-rs-{}-{}{}",
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        extension
+    )
+}
+
+/// Ask GitHub for the latest fvm-rs release version (without the "v" prefix)
+pub async fn fetch_latest_version() -> Result<String> {
+    Ok(fetch_release(None).await?.version().to_string())
+}
+
+/// Fetch a release by tag ("0.2.0" and "v0.2.0" both work), or the latest one
+pub async fn fetch_release(tag: Option<&str>) -> Result<FvmRsRelease> {
+    let url = match tag {
+        Some(tag) => format!(
+            "{}/tags/v{}",
+            releases_api_url(),
+            tag.trim_start_matches('v')
+        ),
+        None => format!("{}/latest", releases_api_url()),
+    };
+    debug!("Fetching fvm-rs release from: {}", url);
+
+    let response = sdk_manager::http_client()
+        .await?
+        .get(&url)
+        .header(
+            reqwest::header::USER_AGENT,
+            format!("fvm-rs/{}", CURRENT_VERSION),
+        )
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+        .timeout(CHECK_TIMEOUT)
+        .send()
+        .await
+        .context("Failed to reach the fvm-rs releases API")?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        match tag {
+            Some(tag) => anyhow::bail!("fvm-rs release {} not found", tag),
+            None => anyhow::bail!("No fvm-rs release has been published"),
+        }
+    }
+
+    response
+        .error_for_status()
+        .context("The fvm-rs releases API returned an error")?
+        .json()
+        .await
+        .context("Failed to parse the fvm-rs release")
+}
+
+/// Check the running executable can be replaced, returning its path
+///
+/// Fails when fvm-rs lives in a directory this user can't write to, which
+/// usually means a package manager installed it.
+pub fn replaceable_executable() -> Result<PathBuf> {
+    let exe = std::env::current_exe()
+        .and_then(|exe| exe.canonicalize())
+        .context("Failed to locate the fvm-rs executable")?;
+    let dir = exe.parent().context("Invalid fvm-rs executable path")?;
+
+    let probe = dir.join(format!(".fvm-rs-update-check.{}", std::process::id()));
+    match std::fs::File::create(&probe) {
+        Ok(_) => {
+            let _ = std::fs::remove_file(&probe);
+            Ok(exe)
+        }
+        Err(e) => Err(anyhow::anyhow!(
+            "Cannot write to {} ({}). fvm-rs appears to be installed by a package manager or \
+             system installer; update it through that instead",
+            dir.display(),
+            e
+        )),
+    }
+}
+
+/// Download a release's binary for this platform, verify it and replace the running executable
+pub async fn install_release(release: &FvmRsRelease) -> Result<()> {
+    let exe = replaceable_executable()?;
+
+    let asset_name = platform_asset_name();
+    let asset = release.asset(&asset_name).with_context(|| {
+        format!(
+            "fvm-rs {} has no build for this platform ({})",
+            release.version(),
+            asset_name
+        )
+    })?;
+    let checksum_name = format!("{}.sha256", asset_name);
+    let checksum_asset = release.asset(&checksum_name).with_context(|| {
+        format!(
+            "fvm-rs {} doesn't publish {}, refusing to install an unverified binary",
+            release.version(),
+            checksum_name
+        )
+    })?;
+
+    let client = sdk_manager::http_client().await?;
+    let attempts = config_manager::GlobalConfig::read()
+        .await?
+        .get_http_retries();
+
+    debug!("Downloading {}", asset.browser_download_url);
+    let bytes = utils::fetch_with_retry(&client, &asset.browser_download_url, attempts)
+        .await?
+        .error_for_status()
+        .context("Failed to download the fvm-rs binary")?
+        .bytes()
+        .await
+        .context("Failed to download the fvm-rs binary")?;
+
+    let checksum_text =
+        utils::fetch_with_retry(&client, &checksum_asset.browser_download_url, attempts)
+            .await?
+            .error_for_status()
+            .context("Failed to download the fvm-rs checksum")?
+            .text()
+            .await
+            .context("Failed to download the fvm-rs checksum")?;
+
+    // Accepts both a bare hash and `sha256sum` output ("<hash>  <file>")
+    let expected = checksum_text
+        .split_whitespace()
+        .next()
+        .unwrap_or("")
+        .to_lowercase();
+    let actual: String = ring::digest::digest(&ring::digest::SHA256, &bytes)
+        .as_ref()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    if actual != expected {
+        anyhow::bail!(
+            "Checksum mismatch for {}: expected SHA-256 {}, got {}. The download may be corrupted; try again",
+            asset_name,
+            expected,
+            actual
+        );
+    }
+    debug!("fvm-rs binary SHA-256 verified: {}", actual);
+
+    // Stage the new binary next to the current one, then swap it in. self_replace
+    // handles Windows, where a running executable can't be overwritten.
+    let staged = exe.with_file_name(format!(".{}.new", asset_name));
+    let result = stage_and_replace(&staged, &bytes);
+    let _ = std::fs::remove_file(&staged);
+    result
+}
+
+fn stage_and_replace(staged: &Path, bytes: &[u8]) -> Result<()> {
+    std::fs::write(staged, bytes).context("Failed to write the downloaded fvm-rs binary")?;
+    sdk_manager::make_executable(staged)?;
+
+    // Make sure the download runs on this machine before replacing a working binary
+    let output = std::process::Command::new(staged)
+        .arg("--version")
+        .output()
+        .context("The downloaded fvm-rs binary does not run on this machine")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "The downloaded fvm-rs binary failed to run: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    debug!("Replacing the running executable");
+    self_replace::self_replace(staged).context("Failed to replace the fvm-rs executable")
+}
+
+/// Get the newer fvm-rs version available, if any
+///
+/// Returns None when update checks are disabled (`updateCheck` config), when
+/// this version is current, or when the latest version is unknown.
+pub async fn check_for_update() -> Result<Option<String>> {
+    let config = config_manager::GlobalConfig::read().await?;
+    if !config.get_update_check_enabled() {
+        debug!("Update check disabled in config");
+        return Ok(None);
+    }
+
+    let path = utils::update_check_path()?;
+    let state = read_state(&path).await;
+
+    let latest = match state {
+        Some(state) if check_age(&state) < CHECK_INTERVAL => {
+            debug!("Using update check from {}", state.checked_at);
+            state.latest_version
+        }
+        previous => {
+            let latest = fetch_latest_version()
+                .await
+                .inspect_err(|e| debug!("Update check failed: {:#}", e))
+                .ok();
+
+            // Record failed checks too, so an offline machine doesn't retry every run
+            let state = UpdateCheckState {
+                checked_at: Utc::now(),
+                latest_version: latest.or_else(|| previous.and_then(|p| p.latest_version)),
+            };
+            if let Err(e) = write_state(&path, &state) {
+                debug!("Failed to save update check: {:#}", e);
+            }
+            state.latest_version
+        }
+    };
+
+    Ok(latest.filter(|latest| {
+        utils::compare_versions(latest, CURRENT_VERSION) == std::cmp::Ordering::Greater
+    }))
+}
+
+async fn read_state(path: &Path) -> Option<UpdateCheckState> {
+    let contents = tokio::fs::read_to_string(path).await.ok()?;
+    serde_json::from_str(&contents)
+        .inspect_err(|e| debug!("Ignoring unreadable update check {}: {}", path.display(), e))
+        .ok()
+}
+
+fn write_state(path: &Path, state: &UpdateCheckState) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    utils::write_file_atomic(path, serde_json::to_vec_pretty(state)?)
+}
+
+fn check_age(state: &UpdateCheckState) -> Duration {
+    (Utc::now() - state.checked_at).to_std().unwrap_or_default()
+}
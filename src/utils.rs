@@ -1,13 +1,29 @@
 use anyhow::{Context, Result};
-use dirs;
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
-use tracing::debug;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::process::{Command, Output, Stdio};
+use std::time::Duration;
+use tracing::{debug, warn};
 
 pub fn fvm_rs_root_dir() -> Result<PathBuf> {
-    Ok(dirs::home_dir()
-        .context("Could not find home directory")?
-        .join(".fvm-rs"))
+    if let Some(home) = dirs::home_dir() {
+        return Ok(home.join(".fvm-rs"));
+    }
+
+    // Minimal containers may have no home directory; an explicit cache path still works
+    for name in ["FVM_CACHE_PATH", "FVM_HOME"] {
+        if let Ok(path) = std::env::var(name)
+            && !path.is_empty()
+        {
+            debug!("No home directory, using {} as fvm-rs root: {}", name, path);
+            return Ok(PathBuf::from(path));
+        }
+    }
+
+    anyhow::bail!("Could not find home directory. Set FVM_CACHE_PATH to choose where fvm-rs stores its data")
 }
 
 /// Alias for fvm_rs_root_dir() for consistency with config_manager
@@ -15,30 +31,60 @@ pub fn get_fvm_dir() -> Result<PathBuf> {
     fvm_rs_root_dir()
 }
 
+/// Cache root resolved from the global config at startup
+static CACHE_ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/// Set the cache root used by the path helpers below (called once at startup)
+///
+/// The global config itself always stays in `fvm_rs_root_dir()`, since the
+/// cache path is read from it.
+pub fn set_cache_root(path: PathBuf) {
+    debug!("Using cache root: {}", path.display());
+    let _ = CACHE_ROOT.set(path);
+}
+
+/// Get the directory holding installed versions, shared repositories and engines
+///
+/// This is the configured cache path (`cache_path`, `FVM_CACHE_PATH` or
+/// `FVM_HOME`), or ~/.fvm-rs when none is set.
+pub fn cache_root_dir() -> Result<PathBuf> {
+    match CACHE_ROOT.get() {
+        Some(path) => Ok(path.clone()),
+        None => fvm_rs_root_dir(),
+    }
+}
+
 /// Get the path to the global configuration file (~/.fvm-rs/.fvmrc)
 pub fn get_global_config_path() -> Result<PathBuf> {
     Ok(fvm_rs_root_dir()?.join(".fvmrc"))
 }
 
-/// Get the path to the global version symlink (~/.fvm-rs/default)
+/// Get the path to the global version symlink (<cache root>/default)
 pub fn get_global_link_path() -> Result<PathBuf> {
-    Ok(fvm_rs_root_dir()?.join("default"))
+    Ok(cache_root_dir()?.join("default"))
 }
 
 pub fn shared_dir() -> Result<PathBuf> {
-    Ok(fvm_rs_root_dir()?.join("shared"))
+    Ok(cache_root_dir()?.join("shared"))
 }
 
 pub fn shared_flutter_dir() -> Result<PathBuf> {
     Ok(shared_dir()?.join("flutter"))
 }
 
+/// Get the shared repository directory for a fork alias
+///
+/// Each fork gets its own repository, since its branches and tags differ from upstream.
+pub fn shared_fork_dir(alias: &str) -> Result<PathBuf> {
+    Ok(shared_dir()?.join("forks").join(alias))
+}
+
 pub fn shared_engine_dir() -> Result<PathBuf> {
     Ok(shared_dir()?.join("engine"))
 }
 
 pub fn flutter_dir() -> Result<PathBuf> {
-    Ok(fvm_rs_root_dir()?.join("flutter"))
+    Ok(cache_root_dir()?.join("flutter"))
 }
 
 pub fn flutter_version_dir(version: &str) -> Result<PathBuf> {
@@ -49,6 +95,500 @@ pub fn shared_engine_hash_dir(hash: &str) -> Result<PathBuf> {
     Ok(shared_dir()?.join("engine").join(hash))
 }
 
+/// Get the path of the marker recording when a version was last verified as installed
+pub fn verified_marker_path(version: &str) -> Result<PathBuf> {
+    Ok(cache_root_dir()?.join(".verified").join(version))
+}
+
+/// Get the path of the cached releases feed for a platform (e.g. "linux")
+pub fn releases_cache_path(platform: &str) -> Result<PathBuf> {
+    Ok(cache_root_dir()?.join(format!("releases_{}.json", platform)))
+}
+
+/// Get the path of the persisted fvm-rs update check result
+pub fn update_check_path() -> Result<PathBuf> {
+    Ok(cache_root_dir()?.join("update_check.json"))
+}
+
+/// Get the directory holding run locks of versions with a running flutter/dart process
+pub fn run_lock_dir() -> Result<PathBuf> {
+    Ok(cache_root_dir()?.join("run"))
+}
+
+/// Get the directory holding the cross-process locks taken while installing
+pub fn install_lock_dir() -> Result<PathBuf> {
+    Ok(cache_root_dir()?.join("locks"))
+}
+
+/// Marks an installed version as in use while a child process runs
+///
+/// The file stays exclusively locked for the lifetime of the guard. The OS
+/// releases the lock when the process dies, so files left behind by crashed
+/// processes are recognizable as stale.
+struct RunLock {
+    path: PathBuf,
+    file: Option<std::fs::File>,
+}
+
+impl Drop for RunLock {
+    fn drop(&mut self) {
+        // Close before removing; Windows can't delete a file that is still open
+        self.file.take();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Create a run lock for the installed version at `flutter_path`
+///
+/// Locking is best effort: SDKs outside the cache are not tracked, and a
+/// failure to write the lock never prevents the command from running.
+fn acquire_run_lock(flutter_path: &Path) -> Option<RunLock> {
+    static SEQUENCE: AtomicUsize = AtomicUsize::new(0);
+
+    let flutter_root = flutter_dir().ok()?.canonicalize().ok()?;
+    let sdk_path = flutter_path.canonicalize().ok()?;
+    let relative = sdk_path.strip_prefix(&flutter_root).ok()?;
+    let version = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    // File name: <version with '/' as '+'>.<pid>-<sequence>.lock
+    let file_name = format!(
+        "{}.{}-{}.lock",
+        version.replace('/', "+"),
+        std::process::id(),
+        SEQUENCE.fetch_add(1, Ordering::Relaxed)
+    );
+
+    let result = (|| -> Result<RunLock> {
+        let dir = run_lock_dir()?;
+        std::fs::create_dir_all(&dir)?;
+        // Lock under a temporary name and then rename, so every visible .lock
+        // file is already locked and `versions_in_use` never mistakes it for stale
+        let path = dir.join(&file_name);
+        let temp_path = dir.join(format!("{}.tmp", file_name));
+        let file = std::fs::File::create(&temp_path)?;
+        if let Err(e) = file.lock().and_then(|()| std::fs::rename(&temp_path, &path)) {
+            drop(file);
+            let _ = std::fs::remove_file(&temp_path);
+            return Err(e.into());
+        }
+        Ok(RunLock {
+            path,
+            file: Some(file),
+        })
+    })();
+
+    match result {
+        Ok(lock) => {
+            debug!("Created run lock for {}: {}", version, lock.path.display());
+            Some(lock)
+        }
+        Err(e) => {
+            debug!("Could not create run lock for {}: {}", version, e);
+            None
+        }
+    }
+}
+
+/// Get the versions that currently have a running flutter/dart process
+///
+/// Stale locks left by processes that crashed or were killed are removed.
+pub fn versions_in_use() -> Result<HashSet<String>> {
+    let mut in_use = HashSet::new();
+    let Ok(entries) = std::fs::read_dir(run_lock_dir()?) else {
+        return Ok(in_use);
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Some(version) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_suffix(".lock"))
+            .and_then(|name| name.rsplit_once('.'))
+            .map(|(version, _)| version.replace('+', "/"))
+        else {
+            continue;
+        };
+
+        // Never create or truncate: a lock that disappeared was released
+        let file = match std::fs::File::open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                debug!("Skipping run lock {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        match file.try_lock() {
+            Ok(()) => {
+                debug!("Removing stale run lock: {}", path.display());
+                drop(file);
+                let _ = std::fs::remove_file(&path);
+            }
+            Err(std::fs::TryLockError::WouldBlock) => {
+                in_use.insert(version);
+            }
+            Err(e) => {
+                debug!("Could not check run lock {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    Ok(in_use)
+}
+
+/// Calculate the total size in bytes of all files under a directory
+///
+/// Symlinks are not followed, so engines linked into a version's bin/cache
+/// are not counted towards that version.
+pub fn dir_size(path: &Path) -> std::io::Result<u64> {
+    let mut total = 0;
+
+    for entry in std::fs::read_dir(path)? {
+        let entry = entry?;
+        let metadata = entry.path().symlink_metadata()?;
+
+        if metadata.is_dir() {
+            total += dir_size(&entry.path())?;
+        } else {
+            total += metadata.len();
+        }
+    }
+
+    Ok(total)
+}
+
+/// Calculate the on-disk size of a version, including its linked engine
+///
+/// Unlike `dir_size`, symlinked directories (such as bin/cache/dart-sdk) are
+/// followed, but each target is counted once even when linked several times.
+pub fn dir_size_following_links(path: &Path) -> std::io::Result<u64> {
+    let mut visited = HashSet::new();
+    if let Ok(canonical) = path.canonicalize() {
+        visited.insert(canonical);
+    }
+    dir_size_visiting(path, &mut visited)
+}
+
+fn dir_size_visiting(path: &Path, visited: &mut HashSet<PathBuf>) -> std::io::Result<u64> {
+    let mut total = 0;
+
+    for entry in std::fs::read_dir(path)? {
+        let entry_path = entry?.path();
+        let metadata = entry_path.symlink_metadata()?;
+
+        if metadata.is_symlink() {
+            // Broken links and links to files count as the link itself
+            match entry_path.canonicalize() {
+                Ok(target) if target.is_dir() => {
+                    if visited.insert(target.clone()) {
+                        total += dir_size_visiting(&target, visited)?;
+                    }
+                }
+                _ => total += metadata.len(),
+            }
+        } else if metadata.is_dir() {
+            total += dir_size_visiting(&entry_path, visited)?;
+        } else {
+            total += metadata.len();
+        }
+    }
+
+    Ok(total)
+}
+
+/// Parse a Flutter version such as "3.24.0-0.1.pre" or "v1.12.13+hotfix.9" as semver
+///
+/// Returns None for names that aren't versions, such as channels.
+fn parse_version(version: &str) -> Option<semver::Version> {
+    semver::Version::parse(version.strip_prefix('v').unwrap_or(version)).ok()
+}
+
+/// Check whether a version is a numbered release rather than a channel or other name
+pub fn is_version_number(version: &str) -> bool {
+    parse_version(version).is_some()
+}
+
+/// Channels in the order they are listed after numbered versions
+const CHANNEL_ORDER: [&str; 4] = ["stable", "beta", "dev", "master"];
+
+/// Compare Flutter version strings by semver precedence (e.g. "3.9.0" < "3.10.0")
+///
+/// A pre-release ("3.24.0-0.1.pre") sorts before its release. Channels sort
+/// after all versions, then any other names alphabetically.
+pub fn compare_versions(a: &str, b: &str) -> std::cmp::Ordering {
+    let key = |name: &str| {
+        let version = parse_version(name);
+        let channel = CHANNEL_ORDER.iter().position(|channel| *channel == name);
+        (version.is_none(), version, channel.is_none(), channel)
+    };
+    key(a).cmp(&key(b)).then_with(|| a.cmp(b))
+}
+
+/// Distinguishes temp files of concurrent writers within this process
+static TEMP_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Replace a file's contents so readers see either the old or the new file, never a partial one
+///
+/// Writes a temp file next to `path`, flushes it to disk and renames it over
+/// the destination. The temp name includes the process id and a per-process
+/// counter, so concurrent writers (in other processes or on other threads)
+/// never share a temp file.
+pub fn write_file_atomic(path: &Path, contents: impl AsRef<[u8]>) -> Result<()> {
+    use std::io::Write;
+
+    let file_name = path
+        .file_name()
+        .with_context(|| format!("Invalid file path: {}", path.display()))?;
+    let temp_path = path.with_file_name(format!(
+        "{}.tmp.{}.{}",
+        file_name.to_string_lossy(),
+        std::process::id(),
+        TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let write = || -> Result<()> {
+        let mut file = std::fs::File::create(&temp_path)?;
+        file.write_all(contents.as_ref())?;
+        file.sync_all()?;
+        std::fs::rename(&temp_path, path)?;
+        Ok(())
+    };
+
+    write().inspect_err(|_| {
+        let _ = std::fs::remove_file(&temp_path);
+    })
+}
+
+/// Format a byte count for display (e.g. "1.4 GB")
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Check whether files can be created in a directory
+///
+/// A directory that doesn't exist yet is judged by its nearest existing
+/// ancestor, since that is where it would be created.
+pub fn is_writable(path: &Path) -> bool {
+    let Some(existing) = path.ancestors().find(|p| p.exists()) else {
+        return false;
+    };
+
+    // Permission bits don't account for read-only mounts, so actually try a write
+    let probe = existing.join(format!(".fvm-rs-write-test-{}", std::process::id()));
+    match std::fs::OpenOptions::new().write(true).create_new(true).open(&probe) {
+        Ok(_) => {
+            let _ = std::fs::remove_file(&probe);
+            true
+        }
+        Err(e) => {
+            debug!("{} is not writable: {}", existing.display(), e);
+            false
+        }
+    }
+}
+
+/// Expand a leading `~` and `$VAR`/`${VAR}` references in a configured path
+///
+/// Relative results are made absolute against the current directory. Fails if a
+/// referenced variable isn't set.
+pub fn expand_path(value: &str) -> Result<PathBuf> {
+    let expanded = shellexpand::full(value).with_context(|| format!("Could not expand '{}'", value))?;
+    std::path::absolute(expanded.as_ref()).with_context(|| format!("Invalid path '{}'", expanded))
+}
+
+/// Like `expand_path`, but leaves remote git URLs (`https://...`, `git@host:repo`) untouched
+pub fn expand_repo_url(url: &str) -> Result<String> {
+    let is_remote = url.contains("://")
+        || url
+            .split_once(':')
+            .is_some_and(|(host, _)| !host.contains('/') && host.len() > 1);
+    if is_remote {
+        return Ok(url.to_string());
+    }
+
+    Ok(expand_path(url)?.to_string_lossy().to_string())
+}
+
+/// Check whether a path contains characters known to break Flutter/Gradle tooling
+///
+/// Flutter and Gradle builds historically fail when the SDK path contains
+/// whitespace or non-ASCII characters.
+pub fn has_unsafe_path_chars(path: &Path) -> bool {
+    path.to_string_lossy()
+        .chars()
+        .any(|c| c.is_whitespace() || !c.is_ascii())
+}
+
+/// Get the path of a `flutter` or `dart` entry point in a Flutter SDK's bin directory
+///
+/// This is what `execute_with_flutter_path` runs, since that directory is
+/// prepended to PATH.
+pub fn flutter_bin_path(flutter_path: &Path, command: &str) -> PathBuf {
+    let name = if cfg!(windows) {
+        format!("{}.bat", command)
+    } else {
+        command.to_string()
+    };
+    flutter_path.join("bin").join(name)
+}
+
+/// Get the Java executable Gradle would most likely use: JAVA_HOME, then PATH
+pub fn java_executable() -> Option<PathBuf> {
+    if let Ok(java_home) = std::env::var("JAVA_HOME")
+        && !java_home.is_empty()
+    {
+        let java = Path::new(&java_home)
+            .join("bin")
+            .join(if cfg!(windows) { "java.exe" } else { "java" });
+        if java.exists() {
+            return Some(java);
+        }
+    }
+
+    which::which("java").ok()
+}
+
+/// Run `java -version` and return the quoted version string (e.g. "17.0.10")
+pub fn java_version(java: &Path) -> Option<String> {
+    // `java -version` prints to stderr, e.g.: openjdk version "17.0.10" 2024-01-16
+    let output = Command::new(java).arg("-version").output().ok()?;
+    let text = String::from_utf8_lossy(&output.stderr);
+    let version = text.lines().next()?.split('"').nth(1)?.to_string();
+
+    debug!("Detected Java version {} at {}", version, java.display());
+    Some(version)
+}
+
+/// Get the major Java release from a version string ("1.8.0_392" → 8, "17.0.10" → 17)
+pub fn java_major_version(version: &str) -> Option<u32> {
+    let mut parts = version.split(['.', '_', '-', '+']);
+    match parts.next()?.parse().ok()? {
+        1 => parts.next()?.parse().ok(),
+        major => Some(major),
+    }
+}
+
+/// An `fvm` or `fvm-rs` executable found on PATH
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FvmExecutable {
+    pub name: &'static str,
+    pub path: PathBuf,
+    /// First line of `--version` output, if it ran
+    pub version: Option<String>,
+    /// Whether this is the fvm-rs binary (directly, via symlink, or by its version output)
+    pub is_fvm_rs: bool,
+}
+
+/// Find every `fvm` and `fvm-rs` executable on PATH, in PATH order
+pub fn fvm_executables_in_path() -> Vec<FvmExecutable> {
+    let current_exe = std::env::current_exe().and_then(|p| p.canonicalize()).ok();
+    let mut found: Vec<FvmExecutable> = vec![];
+
+    for name in ["fvm", "fvm-rs"] {
+        let Ok(paths) = which::which_all(name) else {
+            continue;
+        };
+        for path in paths {
+            // PATH may list the same directory twice
+            if found.iter().any(|e| e.path == path) {
+                continue;
+            }
+
+            let version = tool_version(&path);
+            let is_fvm_rs = (current_exe.is_some() && path.canonicalize().ok() == current_exe)
+                || version.as_deref().is_some_and(|v| v.starts_with("fvm-rs"));
+
+            debug!("Found {} at {} (version: {:?})", name, path.display(), version);
+            found.push(FvmExecutable { name, path, version, is_fvm_rs });
+        }
+    }
+
+    found
+}
+
+/// Run `<tool> --version` and return the first non-empty line of its output
+fn tool_version(tool: &Path) -> Option<String> {
+    let output = Command::new(tool)
+        .arg("--version")
+        .stdin(std::process::Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+/// Delay before the first HTTP retry, doubled after each further attempt
+const HTTP_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// GET a URL, retrying connection errors and 5xx responses with exponential backoff
+///
+/// Makes up to `attempts` requests, waiting 500ms, 1s, 2s, ... in between.
+/// Other responses, including 4xx errors, are returned as-is for the caller
+/// to check, and so is the last 5xx response once attempts run out.
+pub async fn fetch_with_retry(
+    client: &reqwest::Client,
+    url: &str,
+    attempts: u32,
+) -> Result<reqwest::Response> {
+    let attempts = attempts.max(1);
+    let mut delay = HTTP_RETRY_DELAY;
+    let mut attempt = 1;
+
+    loop {
+        match client.get(url).send().await {
+            Ok(response) if response.status().is_server_error() && attempt < attempts => {
+                debug!(
+                    "GET {} returned {} (attempt {}/{}), retrying in {:?}",
+                    url,
+                    response.status(),
+                    attempt,
+                    attempts,
+                    delay
+                );
+            }
+            Ok(response) => return Ok(response),
+            Err(e) if (e.is_connect() || e.is_timeout() || e.is_request()) && attempt < attempts => {
+                debug!(
+                    "GET {} failed: {} (attempt {}/{}), retrying in {:?}",
+                    url, e, attempt, attempts, delay
+                );
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("Request to {} failed after {} attempt(s)", url, attempt));
+            }
+        }
+
+        tokio::time::sleep(delay).await;
+        delay *= 2;
+        attempt += 1;
+    }
+}
+
 /// Execute a command with modified PATH to use a specific Flutter version
 ///
 /// This prepends the Flutter bin directories to PATH and executes the command
@@ -58,13 +598,110 @@ pub fn shared_engine_hash_dir(hash: &str) -> Result<PathBuf> {
 pub fn execute_with_flutter_path(
     command: &str,
     args: &[String],
-    flutter_path: &PathBuf,
+    flutter_path: &Path,
 ) -> Result<i32> {
-    // Construct bin paths to prepend to PATH
+    debug!("Executing {} with Flutter at: {}", command, flutter_path.display());
+    let new_path = flutter_path_env(flutter_path);
+
+    // Execute command with modified environment
+    let mut cmd = Command::new(command);
+    cmd.args(args).env("PATH", new_path);
+    if let Some(pub_cache) = scoped_pub_cache_dir(flutter_path)? {
+        cmd.env("PUB_CACHE", pub_cache);
+    }
+    cmd.stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit());
+
+    debug!("Running: {} {}", command, args.join(" "));
+
+    let _run_lock = acquire_run_lock(flutter_path);
+    let status = cmd.status()
+        .context(format!("Failed to execute {}", command))?;
+
+    let exit_code = status.code().unwrap_or(1);
+    debug!("Command exited with code: {}", exit_code);
+
+    Ok(exit_code)
+}
+
+/// Run a command with modified PATH to use a specific Flutter version, capturing its output
+///
+/// Unlike `execute_with_flutter_path`, stdout and stderr are captured instead of
+/// inherited, so callers can inspect what the command printed.
+pub fn output_with_flutter_path(
+    command: &str,
+    args: &[String],
+    flutter_path: &Path,
+) -> Result<Output> {
+    debug!("Capturing output of {} with Flutter at: {}", command, flutter_path.display());
+    let new_path = flutter_path_env(flutter_path);
+
+    debug!("Running: {} {}", command, args.join(" "));
+
+    let mut cmd = Command::new(command);
+    cmd.args(args).env("PATH", new_path);
+    if let Some(pub_cache) = scoped_pub_cache_dir(flutter_path)? {
+        cmd.env("PUB_CACHE", pub_cache);
+    }
+
+    let _run_lock = acquire_run_lock(flutter_path);
+    let output = cmd
+        .stdin(Stdio::null())
+        .output()
+        .context(format!("Failed to execute {}", command))?;
+
+    debug!("Command exited with code: {}", output.status.code().unwrap_or(1));
+
+    Ok(output)
+}
+
+/// Get the version-scoped PUB_CACHE for a Flutter SDK, if scoped pub caches are enabled
+///
+/// The SDK path is resolved through symlinks (global link, .fvm/flutter_sdk) so
+/// every route to the same version shares one cache. Returns None when disabled,
+/// leaving the user's own PUB_CACHE untouched.
+///
+/// An unreadable global config falls back to the unscoped cache with a warning,
+/// so a corrupt config doesn't stop every flutter/dart invocation.
+pub fn scoped_pub_cache_dir(flutter_path: &Path) -> Result<Option<PathBuf>> {
+    let config = match crate::config_manager::GlobalConfig::read_blocking() {
+        Ok(config) => config,
+        Err(e) => {
+            warn!("Could not read the global config, using the default PUB_CACHE: {:#}", e);
+            return Ok(None);
+        }
+    };
+    if !config.get_scoped_pub_cache() {
+        return Ok(None);
+    }
+
+    let sdk_path = flutter_path.canonicalize().unwrap_or_else(|_| flutter_path.to_path_buf());
+    let versions_dir = flutter_dir()?.canonicalize().unwrap_or(flutter_dir()?);
+
+    // Forks live one level deeper (flutter/<alias>/<version>), so keep the relative path
+    let version = match sdk_path.strip_prefix(&versions_dir) {
+        Ok(relative) => relative.to_path_buf(),
+        Err(_) => PathBuf::from(sdk_path.file_name().context("Invalid Flutter SDK path")?),
+    };
+
+    let pub_cache = config.get_pub_cache_path()?.join(version);
+    debug!("Using scoped PUB_CACHE: {}", pub_cache.display());
+    Ok(Some(pub_cache))
+}
+
+/// Get the Flutter and Dart bin directories of an SDK, in the order they go on PATH
+pub fn flutter_bin_dirs(flutter_path: &Path) -> [PathBuf; 2] {
     let flutter_bin = flutter_path.join("bin");
-    let dart_bin = flutter_path.join("bin").join("cache").join("dart-sdk").join("bin");
+    let dart_bin = flutter_bin.join("cache").join("dart-sdk").join("bin");
+    [flutter_bin, dart_bin]
+}
+
+/// Build a PATH value with the Flutter and Dart bin directories prepended
+fn flutter_path_env(flutter_path: &Path) -> String {
+    // Construct bin paths to prepend to PATH
+    let [flutter_bin, dart_bin] = flutter_bin_dirs(flutter_path);
 
-    debug!("Executing {} with Flutter at: {}", command, flutter_path.display());
     debug!("Flutter bin: {}", flutter_bin.display());
     debug!("Dart bin: {}", dart_bin.display());
 
@@ -83,24 +720,7 @@ pub fn execute_with_flutter_path(
     );
 
     debug!("Modified PATH: {}", new_path);
-
-    // Execute command with modified environment
-    let mut cmd = Command::new(command);
-    cmd.args(args)
-        .env("PATH", new_path)
-        .stdin(Stdio::inherit())
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit());
-
-    debug!("Running: {} {}", command, args.join(" "));
-
-    let status = cmd.status()
-        .context(format!("Failed to execute {}", command))?;
-
-    let exit_code = status.code().unwrap_or(1);
-    debug!("Command exited with code: {}", exit_code);
-
-    Ok(exit_code)
+    new_path
 }
 
 /// Execute a command using system PATH (fallback when no version is configured)
@@ -124,3 +744,219 @@ pub fn execute_with_system_path(command: &str, args: &[String]) -> Result<i32> {
 
     Ok(exit_code)
 }
+
+/// Filesystem types that indicate the path lives on a network mount
+#[cfg(not(windows))]
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smbfs", "smb3", "afpfs", "webdav", "9p", "ceph", "glusterfs",
+    "sshfs", "fuse.sshfs", "davfs", "fuse.davfs2",
+];
+
+/// Detect whether a path resides on a network filesystem (NFS, SMB, ...)
+///
+/// The path does not need to exist yet; the nearest existing ancestor is checked.
+/// Returns the filesystem type (e.g. "nfs4") for network mounts, or None for local
+/// filesystems or when mount information is unavailable on this platform.
+pub fn network_filesystem_type(path: &Path) -> Option<String> {
+    let existing = path.ancestors().find(|p| p.exists())?;
+    let resolved = existing.canonicalize().unwrap_or_else(|_| existing.to_path_buf());
+
+    #[cfg(windows)]
+    {
+        // UNC paths (\\server\share) are always remote
+        let display = resolved.to_string_lossy();
+        if display.starts_with(r"\\?\UNC\")
+            || (display.starts_with(r"\\") && !display.starts_with(r"\\?\"))
+        {
+            return Some("smb".to_string());
+        }
+        None
+    }
+
+    #[cfg(not(windows))]
+    {
+        // Pick the most specific mount point containing the path
+        let (mount_point, fs_type) = mount_table()
+            .into_iter()
+            .filter(|(mount_point, _)| resolved.starts_with(mount_point))
+            .max_by_key(|(mount_point, _)| mount_point.components().count())?;
+
+        debug!("{} is mounted at {} ({})", resolved.display(), mount_point.display(), fs_type);
+
+        if NETWORK_FILESYSTEMS.contains(&fs_type.as_str()) {
+            Some(fs_type)
+        } else {
+            None
+        }
+    }
+}
+
+/// Read the mount table as (mount point, filesystem type) pairs
+#[cfg(target_os = "linux")]
+fn mount_table() -> Vec<(PathBuf, String)> {
+    let Ok(contents) = std::fs::read_to_string("/proc/mounts") else {
+        debug!("Could not read /proc/mounts");
+        return vec![];
+    };
+
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            // Spaces in mount points are escaped as \040
+            let mount_point = fields.next()?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            Some((PathBuf::from(mount_point), fs_type.to_string()))
+        })
+        .collect()
+}
+
+/// Read the mount table as (mount point, filesystem type) pairs
+#[cfg(all(unix, not(target_os = "linux")))]
+fn mount_table() -> Vec<(PathBuf, String)> {
+    // `mount` prints lines like: //user@server/share on /Volumes/share (smbfs, nodev, ...)
+    let Ok(output) = Command::new("mount").output() else {
+        debug!("Could not run mount to read the mount table");
+        return vec![];
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once(" on ")?;
+            let (mount_point, options) = rest.rsplit_once(" (")?;
+            let fs_type = options.split([',', ')']).next()?.trim();
+            Some((PathBuf::from(mount_point), fs_type.to_string()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn dir_size_counts_linked_engine_once() {
+        let root = tempfile::tempdir().unwrap();
+        let engine = root.path().join("engine");
+        std::fs::create_dir_all(engine.join("bin")).unwrap();
+        std::fs::write(engine.join("bin/dart"), vec![0u8; 4096]).unwrap();
+
+        let version = root.path().join("3.24.0");
+        std::fs::create_dir_all(version.join("bin/cache")).unwrap();
+        std::fs::write(version.join("bin/flutter"), vec![0u8; 100]).unwrap();
+        std::os::unix::fs::symlink(&engine, version.join("bin/cache/dart-sdk")).unwrap();
+        std::os::unix::fs::symlink(&engine, version.join("bin/cache/engine")).unwrap();
+
+        let link_len = version.join("bin/cache/dart-sdk").symlink_metadata().unwrap().len();
+        assert_eq!(dir_size(&version).unwrap(), 100 + 2 * link_len);
+        assert_eq!(dir_size_following_links(&version).unwrap(), 100 + 4096);
+    }
+
+    #[test]
+    fn expand_path_expands_home_and_variables() {
+        let home = dirs::home_dir().unwrap();
+        // Set by cargo for test binaries
+        let var = std::env::var("CARGO_PKG_NAME").unwrap();
+
+        assert_eq!(expand_path("~").unwrap(), home);
+        assert_eq!(expand_path("~/fvm").unwrap(), home.join("fvm"));
+        assert_eq!(expand_path("/cache/$CARGO_PKG_NAME").unwrap(), Path::new("/cache").join(&var));
+        assert_eq!(expand_path("/cache/${CARGO_PKG_NAME}/x").unwrap(), Path::new("/cache").join(&var).join("x"));
+        assert!(expand_path("$FVM_RS_TEST_UNSET_VARIABLE/cache").is_err());
+    }
+
+    #[test]
+    fn compare_versions_orders_releases_then_channels() {
+        let mut versions = vec![
+            "master", "3.24.0", "custom", "stable", "3.24.0-0.1.pre", "3.10.0", "beta",
+            "3.9.2", "3.24.0-0.2.pre",
+        ];
+        versions.sort_by(|a, b| compare_versions(a, b));
+
+        assert_eq!(
+            versions,
+            [
+                "3.9.2", "3.10.0", "3.24.0-0.1.pre", "3.24.0-0.2.pre", "3.24.0", "stable", "beta",
+                "master", "custom",
+            ]
+        );
+    }
+
+    #[test]
+    fn write_file_atomic_never_exposes_partial_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.json");
+        let old = vec![b'a'; 1024];
+        let new = vec![b'b'; 32 * 1024 * 1024];
+        std::fs::write(&path, &old).unwrap();
+
+        let done = std::sync::atomic::AtomicBool::new(false);
+        std::thread::scope(|scope| {
+            let reader = scope.spawn(|| {
+                while !done.load(std::sync::atomic::Ordering::Relaxed) {
+                    let seen = std::fs::read(&path).unwrap();
+                    assert!(seen == old || seen == new, "saw a partial file of {} bytes", seen.len());
+                }
+            });
+
+            write_file_atomic(&path, &new).unwrap();
+            done.store(true, std::sync::atomic::Ordering::Relaxed);
+            reader.join().unwrap();
+        });
+
+        assert_eq!(std::fs::read(&path).unwrap(), new);
+    }
+
+    #[test]
+    fn write_file_atomic_concurrent_writers_keep_whole_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("releases.json");
+        let size = 1024 * 1024;
+
+        std::thread::scope(|scope| {
+            for writer in 0..8u8 {
+                let path = &path;
+                scope.spawn(move || {
+                    for _ in 0..10 {
+                        write_file_atomic(path, vec![writer; size]).unwrap();
+                    }
+                });
+            }
+        });
+
+        // Each writer's contents are uniform, so a mix means a shared temp file
+        let contents = std::fs::read(&path).unwrap();
+        assert_eq!(contents.len(), size);
+        assert!(contents.iter().all(|&b| b == contents[0]), "file mixes writers' contents");
+
+        let leftovers: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.file_name())
+            .filter(|name| name != "releases.json")
+            .collect();
+        assert!(leftovers.is_empty(), "temp files left behind: {:?}", leftovers);
+    }
+
+    #[test]
+    fn write_file_atomic_removes_temp_file_on_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        // A non-empty directory can't be replaced by a file, so the rename fails
+        let path = dir.path().join("config.json");
+        std::fs::create_dir(&path).unwrap();
+        std::fs::write(path.join("keep"), "x").unwrap();
+
+        assert!(write_file_atomic(&path, "{}").is_err());
+
+        let leftovers: Vec<_> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .flatten()
+            .map(|entry| entry.file_name())
+            .filter(|name| name != "config.json")
+            .collect();
+        assert!(leftovers.is_empty(), "temp files left behind: {:?}", leftovers);
+    }
+}